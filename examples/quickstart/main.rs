@@ -140,24 +140,23 @@ async fn graph_feature_serving(
     let user = fields.first().unwrap().entity_id.as_ref().unwrap();
 
     let topos = graph.project_topology(vec!["sameRates", "rates"]);
+    let table_view =
+        TableFeatureView::default("fraud_detection_train_user_features", user.clone(), &fields);
+    let topo_view = TopologyFeatureView::default("fraud_detection_train_topo", &topos);
     let dataset = GraphDataset::new(
         "fraud_detection_train_dataset",
-        vec![TableFeatureView::default(
-            "fraud_detection_train_user_features",
-            user.clone(),
-            &fields,
-        )],
-        vec![TopologyFeatureView::default(
-            "fraud_detection_train_topo",
-            &topos,
-        )],
+        vec![table_view.clone()],
+        vec![topo_view.clone()],
         serving::GraphDatasetRenderingOptions::default(), // sample_k_hop_neighbors(2, vec![5, 3], None, true),
     );
     info!("graph dataset {dataset:?}");
-    fs.registry()
-        .register_resources(&topos.iter().collect())
-        .await?;
-    fs.registry().register_resource(&dataset).await?;
+
+    // each resource is registered after the resources it references
+    let registry = fs.registry();
+    registry.register_resources(&topos.iter().collect()).await?;
+    registry.register_resources(&vec![&table_view]).await?;
+    registry.register_resources(&vec![&topo_view]).await?;
+    registry.register_resources(&vec![&dataset]).await?;
     Ok(dataset)
 }
 
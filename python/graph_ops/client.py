@@ -27,6 +27,12 @@ class Client:
         # TODO(tatiana): wrap the result with a topo retrieval interface?
         return self.client.get_topology_view(view_id)
 
+    def export_bundle(self, ids: List[str], path: str):
+        return self.client.export_bundle(ids, path)
+
+    def import_bundle(self, path: str, overwrite: bool = False, dry_run: bool = False):
+        return self.client.import_bundle(path, overwrite, dry_run)
+
     def neighbor_sampled_dataloader(self, graph_dataset: str, **kwargs):
         (topos, tables) = self.client.get_graph_dataset(graph_dataset)
         assert len(topos) == 1
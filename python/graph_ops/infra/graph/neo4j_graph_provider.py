@@ -1,5 +1,3 @@
-from neo4j import GraphDatabase
-
 from ofnil.infra.graph.graph_provider import GraphProvider
 from ofnil.ofnil import TopologyFeatureViewInfo
 
@@ -9,24 +7,16 @@ class Neo4jGraphProvider(GraphProvider):
     def __init__(self, topo_info: TopologyFeatureViewInfo, seed_type: str = None):
         super().__init__(seed_type=seed_type)
 
-        self.driver = GraphDatabase.driver(
-            uri=topo_info.infra_info["uri"],
-            auth=(topo_info.infra_info["username"], topo_info.infra_info["password"]),
-        )
+        # queries run through the connection of the feature store, which keeps the credentials
+        self.connection = topo_info.connection
 
         self.seed_query = (
             f"MATCH (n:{seed_type}) RETURN ID(n) AS seed, LABELS(n)[0] as label"
             if seed_type is not None
             else "MATCH (n) RETURN ID(n) AS seed, LABELS(n)[0] as label"
         )
-
-        self.seed_session = self.driver.session()
         self.seed_stream = None
 
-    def __del__(self):
-        self.seed_session.close()
-        self.driver.close()
-
     def __iter__(self):
         """Implement an iterable-style dataset
 
@@ -35,7 +25,7 @@ class Neo4jGraphProvider(GraphProvider):
         int
             The internal id of a seed vertex
         """
-        self.seed_stream = self.seed_session.run(self.seed_query)
+        self.seed_stream = self.connection.run(self.seed_query)
         for record in self.seed_stream:
             yield {record["label"]: record["seed"]}
 
@@ -48,6 +38,4 @@ class Neo4jGraphProvider(GraphProvider):
             if vertex_type is not None
             else "MATCH (n) RETURN count(n) AS cnt"
         )
-        with self.driver.session() as session:
-            cnt = [record["cnt"] for record in session.run(query)][0]
-        return cnt
+        return self.connection.run(query)[0]["cnt"]
@@ -1,6 +1,5 @@
 from typing import Dict, List, Tuple, TypeVar, Union
 
-from neo4j import GraphDatabase
 from torch import Tensor
 
 from ofnil.infra.graph.hop_collectors.dgl_hop_collector import DGLHopCollector
@@ -107,10 +106,8 @@ class Neo4jNeighborSampler(NeighborSampler):
             self.edge_types.append(edge_types)
             self.dst_types.append(dst_types)
 
-        self.driver = GraphDatabase.driver(
-            uri=topo_info.infra_info["uri"],
-            auth=(topo_info.infra_info["username"], topo_info.infra_info["password"]),
-        )
+        # queries run through the connection of the feature store, which keeps the credentials
+        self.connection = topo_info.connection
 
         if type(num_neighbors) == list:
             self.fan_outs = num_neighbors
@@ -152,9 +149,6 @@ class Neo4jNeighborSampler(NeighborSampler):
 
         # TODO(tatiana): support rendering opt in topo_info
 
-    def __del__(self):
-        self.driver.close()
-
     def _get_vertex_primary_keys(self, tlabel, internal_ids: list):
         if tlabel not in self.vertex_primary_keys:
             return internal_ids
@@ -165,10 +159,9 @@ class Neo4jNeighborSampler(NeighborSampler):
         id_query = (
             f"MATCH(v) WHERE ID(v) in $ids RETURN ID(v) as internal, v.{primary_key} as id, LABELS(v)[0] as label"
         )
-        with self.driver.session() as id_session:
-            id_stream = id_session.run(id_query, parameters={"ids": internal_ids})
-            # format: {internal_id: "{type}/{id}"}
-            return {record["internal"]: "{}/{}".format(record["label"], record["id"]) for record in id_stream}
+        id_stream = self.connection.run(id_query, parameters={"ids": internal_ids})
+        # format: {internal_id: "{type}/{id}"}
+        return {record["internal"]: "{}/{}".format(record["label"], record["id"]) for record in id_stream}
 
     def sample_neighbors(self, seeds: Tuple[str, Union[Tensor, List[int]]]):
         frontier = seeds
@@ -209,44 +202,43 @@ class Neo4jNeighborSampler(NeighborSampler):
 
     def _construct_csc(self, frontier, edge_type):
         assert not self.need_edge, "Unsupported yet"
-        with self.driver.session() as neighbor_session:
-            edge_stream = neighbor_session.run(
-                self.neighbor_query,
-                parameters={"seed_ids": frontier, "edge_type": edge_type},
-            )
-
-            col_offsets = [0]
-            row = []
-            nbr_type = None  # TODO(tatiana): get nbr type and edge direction info if edge_type is specific
-            current_seed = None
-            seeds = []
-            for record in edge_stream:
-                seed, nbr, nbr_label = record
-                # check
-                if nbr_type is not None:
-                    assert nbr_type == nbr_label, "All sampled vertices in the same hop should be of the same type"
-                else:
-                    nbr_type = nbr_label
-                # construct CSC
-                if seed == current_seed:
-                    row.append(nbr)
-                else:
-                    if current_seed != None:
-                        col_offsets.append(len(row))
-                    current_seed = seed
-                    seeds.append(seed)
-                    row.append(nbr)
-            if current_seed != None:
-                col_offsets.append(len(row))
+        edge_stream = self.connection.run(
+            self.neighbor_query,
+            parameters={"seed_ids": frontier, "edge_type": edge_type},
+        )
+
+        col_offsets = [0]
+        row = []
+        nbr_type = None  # TODO(tatiana): get nbr type and edge direction info if edge_type is specific
+        current_seed = None
+        seeds = []
+        for record in edge_stream:
+            seed, nbr, nbr_label = record["seed"], record["nbr"], record["nbr_label"]
+            # check
+            if nbr_type is not None:
+                assert nbr_type == nbr_label, "All sampled vertices in the same hop should be of the same type"
+            else:
+                nbr_type = nbr_label
+            # construct CSC
+            if seed == current_seed:
+                row.append(nbr)
+            else:
+                if current_seed != None:
+                    col_offsets.append(len(row))
                 current_seed = seed
-            # handle vertices in frontier that are without nbrs
-            if len(seeds) < len(frontier):
-                seed_set = set(seeds)
-                for v in frontier:
-                    if v not in seed_set:
-                        seeds.append(v)
-                        col_offsets.append(col_offsets[-1])
-            assert (
-                len(col_offsets) == len(frontier) + 1
-            ), f"len(col_offsets) != len(frontier): {len(col_offsets)} vs {len(frontier)}, seeds={seeds}, frontier={frontier}"
-            return (seeds, col_offsets, row, nbr_type)
+                seeds.append(seed)
+                row.append(nbr)
+        if current_seed != None:
+            col_offsets.append(len(row))
+            current_seed = seed
+        # handle vertices in frontier that are without nbrs
+        if len(seeds) < len(frontier):
+            seed_set = set(seeds)
+            for v in frontier:
+                if v not in seed_set:
+                    seeds.append(v)
+                    col_offsets.append(col_offsets[-1])
+        assert (
+            len(col_offsets) == len(frontier) + 1
+        ), f"len(col_offsets) != len(frontier): {len(col_offsets)} vs {len(frontier)}, seeds={seeds}, frontier={frontier}"
+        return (seeds, col_offsets, row, nbr_type)
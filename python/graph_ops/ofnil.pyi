@@ -134,6 +134,49 @@ class ClientInner:
             A topology freature of the graph.
         """
         ...
+    def export_bundle(self, ids: List[ResourceId], path: str) -> None:
+        """Export resources and all resources they reference into a bundle file.
+
+        Args:
+            ids: ResourceIds of the resources to export.
+
+            path: Path of the bundle file to write.
+        """
+        ...
+    def import_bundle(
+        self,
+        path: str,
+        overwrite: bool = False,
+        dry_run: bool = False,
+    ) -> Dict[str, List[ResourceId]]:
+        """Import a bundle file written by `export_bundle`.
+
+        Args:
+            path: Path of the bundle file.
+
+            overwrite: Whether to overwrite registered resources that differ from the bundle.
+
+            dry_run: Only check the bundle against the registry without importing.
+
+        Returns:
+            ResourceIds of the `created`, `unchanged` and `conflicts` resources.
+        """
+        ...
+
+class InfraConnection:
+    """Connection to an infra of the feature store, which keeps the credentials of the infra on the Rust side."""
+
+    infra_id: Dict[str, str]
+    def run(self, query: str, parameters: Dict[str, Any] | None = None) -> List[Dict[str, Any]]:
+        """Run a Cypher query on a Neo4j infra.
+
+        Returns:
+            The records as dicts of the returned fields.
+        """
+        ...
+    def mget(self, keys: List[str]) -> List[str | None]:
+        """Get the values of keys from a Redis infra, None for the keys that do not exist."""
+        ...
 
 class TableFeatureViewInfo:
     entity_label: str
@@ -142,11 +185,13 @@ class TableFeatureViewInfo:
     entity_type: str
     rendering_opt: FeatureRenderingOptions
     infra_info: Dict[str, str]
+    connection: InfraConnection
 
 class TopologyFeatureViewInfo:
     topologies: List[Tuple[Entity, Entity, Entity, Topology]]
     rendering_opt: TopologyRenderingOptions
     infra_info: Dict[str, str]
+    connection: InfraConnection
 
 def neighbor_sample(
     input_vertices: List[int],
@@ -1,19 +1,26 @@
 use super::responses::GenericResponse;
 
+use ofnil::Variant;
 use rocket::{http::Status, response::status::Custom, serde::json::Json};
 
+pub mod audit_handler;
 pub mod config_handler;
 pub mod entity_handler;
 pub mod field_handler;
 pub mod gaf_handler;
 pub mod graph_dataset_handler;
 pub mod graph_handler;
+pub mod history_handler;
 pub mod infra_handler;
+pub mod lineage_handler;
+pub mod project_handler;
 pub mod provider_handler;
+pub mod search_handler;
 pub mod table_feature_view_handler;
 pub mod topology_feature_view_handler;
 pub mod topology_handler;
 pub mod transformation_handler;
+pub mod watch_handler;
 
 fn generate_error_response(message: String) -> Custom<Json<GenericResponse>> {
     let error_response = GenericResponse {
@@ -22,3 +29,8 @@ fn generate_error_response(message: String) -> Custom<Json<GenericResponse>> {
     };
     Custom(Status::InternalServerError, Json(error_response))
 }
+
+/// Parses the `variant` query parameter of listing requests, which defaults to `default`
+fn parse_variant(variant: Option<String>) -> Variant {
+    variant.as_deref().map(Variant::from).unwrap_or_default()
+}
@@ -0,0 +1,65 @@
+use super::{generate_error_response, GenericResponse};
+use chrono::{DateTime, TimeZone, Utc};
+use ofnil::{AuditQuery, AuditRecord, FeatureStore};
+use rocket::{get, info, response::status::Custom, serde::json::Json, State};
+
+/// Converts unix seconds of a query parameter to a time
+fn parse_time(
+    name: &str,
+    seconds: Option<i64>,
+) -> Result<Option<DateTime<Utc>>, Custom<Json<GenericResponse>>> {
+    match seconds {
+        Some(seconds) => match Utc.timestamp_opt(seconds, 0).single() {
+            Some(time) => Ok(Some(time)),
+            None => Err(generate_error_response(format!(
+                "Invalid {name} timestamp {seconds}"
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// `action` can be repeated, `resource` is a resource id prefix, and `since` and `until` are
+/// unix timestamps in seconds
+#[allow(clippy::too_many_arguments)]
+#[get("/audit?<actor>&<action>&<resource>&<client>&<since>&<until>&<offset>&<limit>")]
+pub async fn get_audit_log_handler(
+    fs: &State<FeatureStore>,
+    actor: Option<String>,
+    action: Vec<String>,
+    resource: Option<String>,
+    client: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Json<Vec<AuditRecord>>, Custom<Json<GenericResponse>>> {
+    let mut actions = Vec::with_capacity(action.len());
+    for action in action {
+        match action.parse() {
+            Ok(action) => actions.push(action),
+            Err(_) => {
+                return Err(generate_error_response(format!(
+                    "Unknown audit action {action}"
+                )))
+            }
+        }
+    }
+    let query = AuditQuery {
+        actor,
+        actions,
+        resource_prefix: resource,
+        client,
+        since: parse_time("since", since)?,
+        until: parse_time("until", until)?,
+        offset: offset.unwrap_or(0),
+        limit,
+    };
+    info!("Querying audit log {:?}", query);
+    match fs.registry().query_audit_log(&query).await {
+        Ok(records) => Ok(Json(records)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error querying audit log: {e}"
+        ))),
+    }
+}
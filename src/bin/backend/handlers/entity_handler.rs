@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -26,12 +26,17 @@ pub async fn get_entity_handler(
     }
 }
 
-#[get("/entities")]
+#[get("/entities?<variant>")]
 pub async fn get_entities_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::Entity>>, Custom<Json<GenericResponse>>> {
     info!("Getting all entities");
-    match fs.registry().get_all_entities().await {
+    match fs
+        .registry()
+        .get_all_entities(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(entities) => {
             let mut entities_map = HashMap::new();
             for entity in entities {
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
-use super::{generate_error_response, GenericResponse};
-use ofnil::{feature::ResourceOp, FeatureStore, Variant};
+use super::{generate_error_response, parse_variant, GenericResponse};
+use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 
 #[get("/field?<id>")]
@@ -26,17 +26,19 @@ pub async fn get_field_handler(
 }
 
 // Note: here is entity name instead of entity id
-#[get("/fields?<entity_name>")]
+#[get("/fields?<entity_name>&<variant>")]
 pub async fn get_fields_handler(
     fs: &State<FeatureStore>,
     entity_name: Option<String>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::Field>>, Custom<Json<GenericResponse>>> {
+    let variant = parse_variant(variant);
     match entity_name {
         Some(entity_name) => {
             info!("Getting all fields for entity {}", entity_name);
             match fs
                 .registry()
-                .get_entity_fields(entity_name.as_str(), &Variant::default())
+                .get_entity_fields(entity_name.as_str(), &variant)
                 .await
             {
                 Ok(fields) => {
@@ -53,7 +55,7 @@ pub async fn get_fields_handler(
         }
         None => {
             info!("Getting all fields");
-            match fs.registry().get_all_fields().await {
+            match fs.registry().get_all_fields(Some(&variant)).await {
                 Ok(fields) => {
                     let mut fields_map = HashMap::new();
                     for field in fields {
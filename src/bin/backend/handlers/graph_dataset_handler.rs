@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -28,12 +28,17 @@ pub async fn get_graph_dataset_handler(
     }
 }
 
-#[get("/graph_datasets")]
+#[get("/graph_datasets?<variant>")]
 pub async fn get_graph_datasets_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::GraphDataset>>, Custom<Json<GenericResponse>>> {
     info!("Getting all graph_datasets");
-    match fs.registry().get_all_graph_datasets().await {
+    match fs
+        .registry()
+        .get_all_graph_datasets(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(graph_datasets) => {
             let mut graph_datasets_map = HashMap::new();
             for graph_dataset in graph_datasets {
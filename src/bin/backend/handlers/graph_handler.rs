@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -24,12 +24,17 @@ pub async fn get_graph_handler(
     }
 }
 
-#[get("/graphs")]
+#[get("/graphs?<variant>")]
 pub async fn get_graphs_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::Graph>>, Custom<Json<GenericResponse>>> {
     info!("Getting all graphs");
-    match fs.registry().get_all_graphs().await {
+    match fs
+        .registry()
+        .get_all_graphs(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(graphs) => {
             let mut graphs_map = HashMap::new();
             for graph in graphs {
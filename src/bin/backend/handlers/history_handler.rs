@@ -0,0 +1,83 @@
+use super::{generate_error_response, GenericResponse};
+use ofnil::{FeatureStore, ResourceRevision, RevisionDiff};
+use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
+
+/// Checks that the resource id (e.g. `default/TableFeatureView/user_view`) is of the resource kind
+/// in the request path (e.g. `table_feature_view`)
+fn check_resource_kind(resource: &str, id: &str) -> Result<(), Custom<Json<GenericResponse>>> {
+    let kind = match resource {
+        "entity" => "Entity",
+        "field" => "Field",
+        "topology" => "Topology",
+        "graph" => "Graph",
+        "transformation" => "Transformation",
+        "table_feature_view" => "TableFeatureView",
+        "topology_feature_view" => "TopologyFeatureView",
+        "graph_dataset" => "GraphDataset",
+        _ => {
+            return Err(generate_error_response(format!(
+                "Unknown resource type {resource}"
+            )))
+        }
+    };
+    if id.split('/').nth(1) == Some(kind) {
+        Ok(())
+    } else {
+        Err(generate_error_response(format!(
+            "{id} is not a {kind} resource id"
+        )))
+    }
+}
+
+/// `id` is the url-encoded resource id, e.g. `default%2FEntity%2FReviewer`
+#[get("/<resource>/<id>/history")]
+pub async fn get_history_handler(
+    fs: &State<FeatureStore>,
+    resource: String,
+    id: String,
+) -> Result<Json<Vec<ResourceRevision>>, Custom<Json<GenericResponse>>> {
+    info!("Getting revision history of {resource} {id}");
+    check_resource_kind(&resource, &id)?;
+    match fs.registry().list_revisions(&id).await {
+        Ok(revisions) => Ok(Json(revisions)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error getting revision history of {id}: {e}",
+        ))),
+    }
+}
+
+#[get("/<resource>/<id>/diff?<from>&<to>")]
+pub async fn get_diff_handler(
+    fs: &State<FeatureStore>,
+    resource: String,
+    id: String,
+    from: u64,
+    to: u64,
+) -> Result<Json<RevisionDiff>, Custom<Json<GenericResponse>>> {
+    info!("Getting diff of {resource} {id} from revision {from} to {to}");
+    check_resource_kind(&resource, &id)?;
+    match fs.registry().diff_revisions(&id, from, to).await {
+        Ok(diff) => Ok(Json(diff)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error comparing revisions {from} and {to} of {id}: {e}",
+        ))),
+    }
+}
+
+/// Returns the new revision number created by the rollback
+#[post("/<resource>/<id>/rollback?<revision>")]
+pub async fn post_rollback_handler(
+    fs: &State<FeatureStore>,
+    resource: String,
+    id: String,
+    revision: u64,
+) -> Result<String, Custom<Json<GenericResponse>>> {
+    info!("Rolling back {resource} {id} to revision {revision}");
+    check_resource_kind(&resource, &id)?;
+    match fs.registry().rollback_resource(&id, revision).await {
+        Ok(new_revision) => Ok(new_revision.to_string()),
+        Err(e) => Err(generate_error_response(format!(
+            "Error rolling back {id} to revision {revision}: {e}",
+        ))),
+    }
+}
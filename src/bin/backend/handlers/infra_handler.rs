@@ -1,6 +1,6 @@
-use super::GenericResponse;
-use ofnil::{FeatureStore, InfraIdentifier};
-use rocket::{get, info, response::status::Custom, serde::json::Json, State};
+use super::{generate_error_response, GenericResponse};
+use ofnil::{FeatureStore, InfraConfig, InfraHealth, InfraIdentifier};
+use rocket::{delete, get, info, put, response::status::Custom, serde::json::Json, State};
 
 type InfraInfo = (InfraIdentifier, String);
 
@@ -11,3 +11,42 @@ pub fn get_infras_handler(
     info!("Getting all infras");
     Ok(Json(fs.infra_manager().get_infra_info()))
 }
+
+#[get("/infras/health")]
+pub async fn get_infras_health_handler(fs: &State<FeatureStore>) -> Json<Vec<InfraHealth>> {
+    info!("Checking the health of all infras");
+    Json(fs.infra_manager().health().await)
+}
+
+/// Adds infra `name` of the config, or replaces the infra of the same identifier
+#[put("/infras/<name>", data = "<config>")]
+pub async fn put_infra_handler(
+    fs: &State<FeatureStore>,
+    name: String,
+    config: Json<InfraConfig>,
+) -> Result<Json<InfraIdentifier>, Custom<Json<GenericResponse>>> {
+    info!("Adding infra {name}");
+    match fs.add_infra(&name, &config).await {
+        Ok(infra_id) => Ok(Json(infra_id)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error adding infra {name}: {e}"
+        ))),
+    }
+}
+
+/// Removes infra `name` of `infra_type`, e.g. `neo4j`
+#[delete("/infras/<infra_type>/<name>")]
+pub fn delete_infra_handler(
+    fs: &State<FeatureStore>,
+    infra_type: String,
+    name: String,
+) -> Result<(), Custom<Json<GenericResponse>>> {
+    info!("Removing infra {infra_type} {name}");
+    if fs.remove_infra(&InfraIdentifier::new(&infra_type, name.as_str())) {
+        Ok(())
+    } else {
+        Err(generate_error_response(format!(
+            "Infra {infra_type} {name} is not found"
+        )))
+    }
+}
@@ -0,0 +1,38 @@
+use super::{generate_error_response, GenericResponse};
+use ofnil::{FeatureStore, LineageDirection, LineageGraph};
+use rocket::{get, info, response::status::Custom, serde::json::Json, State};
+
+/// `direction` is one of `upstream`, `downstream` and `both` (default)
+#[get("/lineage?<id>&<direction>")]
+pub async fn get_lineage_handler(
+    fs: &State<FeatureStore>,
+    id: Option<String>,
+    direction: Option<String>,
+) -> Result<Json<LineageGraph>, Custom<Json<GenericResponse>>> {
+    let Some(id) = id else {
+        info!("Resource ID not provided");
+        return Err(generate_error_response(
+            "Resource ID not provided".to_string(),
+        ));
+    };
+    let direction = match direction
+        .as_deref()
+        .unwrap_or("both")
+        .parse::<LineageDirection>()
+    {
+        Ok(direction) => direction,
+        Err(_) => {
+            return Err(generate_error_response(format!(
+                "Unknown lineage direction {}",
+                direction.unwrap_or_default()
+            )))
+        }
+    };
+    info!("Getting {direction} lineage of {id}");
+    match fs.registry().get_lineage(&id, direction).await {
+        Ok(lineage) => Ok(Json(lineage)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error getting lineage of {id}: {e}",
+        ))),
+    }
+}
@@ -0,0 +1,16 @@
+use super::{generate_error_response, GenericResponse};
+use ofnil::FeatureStore;
+use rocket::{get, info, response::status::Custom, serde::json::Json, State};
+
+#[get("/projects")]
+pub async fn get_projects_handler(
+    fs: &State<FeatureStore>,
+) -> Result<Json<Vec<String>>, Custom<Json<GenericResponse>>> {
+    info!("Getting all projects");
+    match fs.registry().list_projects().await {
+        Ok(projects) => Ok(Json(projects)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error getting projects: {e}"
+        ))),
+    }
+}
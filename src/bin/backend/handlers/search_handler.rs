@@ -0,0 +1,52 @@
+use super::{generate_error_response, GenericResponse};
+use ofnil::{FeatureStore, FeatureValueType, SearchQuery, SearchResult, Variant};
+use rocket::{get, info, response::status::Custom, serde::json::Json, State};
+
+/// `kind` and `tag` can be repeated, and each `tag` is either `key` or `key:value`
+#[allow(clippy::too_many_arguments)]
+#[get("/search?<kind>&<variant>&<tag>&<owner>&<value_type>&<text>&<offset>&<limit>")]
+pub async fn search_handler(
+    fs: &State<FeatureStore>,
+    kind: Vec<String>,
+    variant: Option<String>,
+    tag: Vec<String>,
+    owner: Option<String>,
+    value_type: Option<String>,
+    text: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Json<SearchResult>, Custom<Json<GenericResponse>>> {
+    let mut kinds = Vec::with_capacity(kind.len());
+    for kind in kind {
+        match kind.parse() {
+            Ok(kind) => kinds.push(kind),
+            Err(_) => {
+                return Err(generate_error_response(format!(
+                    "Unknown resource kind {kind}"
+                )))
+            }
+        }
+    }
+    let value_type = match value_type.map(|value_type| value_type.parse::<FeatureValueType>()) {
+        Some(Ok(value_type)) => Some(value_type),
+        Some(Err(e)) => return Err(generate_error_response(e.to_string())),
+        None => None,
+    };
+    let query = SearchQuery {
+        kinds,
+        variant: variant.as_deref().map(Variant::from),
+        tags: tag.iter().map(|tag| tag.parse().unwrap()).collect(),
+        owner,
+        value_type,
+        text,
+        offset: offset.unwrap_or(0),
+        limit,
+    };
+    info!("Searching resources {:?}", query);
+    match fs.registry().search(&query).await {
+        Ok(result) => Ok(Json(result)),
+        Err(e) => Err(generate_error_response(format!(
+            "Error searching resources: {e}",
+        ))),
+    }
+}
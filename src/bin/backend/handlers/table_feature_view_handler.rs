@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -28,12 +28,17 @@ pub async fn get_table_feature_view_handler(
     }
 }
 
-#[get("/table_feature_views")]
+#[get("/table_feature_views?<variant>")]
 pub async fn get_table_feature_views_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::TableFeatureView>>, Custom<Json<GenericResponse>>> {
     info!("Getting all table_feature_views");
-    match fs.registry().get_all_table_feature_views().await {
+    match fs
+        .registry()
+        .get_all_table_feature_views(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(table_feature_views) => {
             let mut table_feature_views_map = HashMap::new();
             for table_feature_view in table_feature_views {
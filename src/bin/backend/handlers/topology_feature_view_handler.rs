@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -28,12 +28,17 @@ pub async fn get_topology_feature_view_handler(
     }
 }
 
-#[get("/topology_feature_views")]
+#[get("/topology_feature_views?<variant>")]
 pub async fn get_topology_feature_views_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::TopologyFeatureView>>, Custom<Json<GenericResponse>>> {
     info!("Getting all topology_feature_views");
-    match fs.registry().get_all_topology_feature_views().await {
+    match fs
+        .registry()
+        .get_all_topology_feature_views(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(topology_feature_views) => {
             let mut topology_feature_views_map = HashMap::new();
             for topology_feature_view in topology_feature_views {
@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -26,12 +26,17 @@ pub async fn get_topology_handler(
     }
 }
 
-#[get("/topologies")]
+#[get("/topologies?<variant>")]
 pub async fn get_topologies_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::Topology>>, Custom<Json<GenericResponse>>> {
     info!("Getting all topologies");
-    match fs.registry().get_all_topologies().await {
+    match fs
+        .registry()
+        .get_all_topologies(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(topologies) => {
             let mut topologies_map = HashMap::new();
             for topology in topologies {
@@ -1,4 +1,4 @@
-use super::{generate_error_response, GenericResponse};
+use super::{generate_error_response, parse_variant, GenericResponse};
 use ofnil::{feature::ResourceOp, FeatureStore};
 use rocket::{get, info, post, response::status::Custom, serde::json::Json, State};
 use std::collections::HashMap;
@@ -28,12 +28,17 @@ pub async fn get_transformation_handler(
     }
 }
 
-#[get("/transformations")]
+#[get("/transformations?<variant>")]
 pub async fn get_transformations_handler(
     fs: &State<FeatureStore>,
+    variant: Option<String>,
 ) -> Result<Json<HashMap<String, ofnil::Transformation>>, Custom<Json<GenericResponse>>> {
     info!("Getting all transformations");
-    match fs.registry().get_all_transformations().await {
+    match fs
+        .registry()
+        .get_all_transformations(Some(&parse_variant(variant)))
+        .await
+    {
         Ok(transformations) => {
             let mut transformations_map = HashMap::new();
             for transformation in transformations {
@@ -0,0 +1,44 @@
+use super::{generate_error_response, GenericResponse};
+use futures::StreamExt;
+use ofnil::FeatureStore;
+use rocket::{
+    get, info,
+    response::{
+        status::Custom,
+        stream::{Event, EventStream},
+    },
+    serde::json::Json,
+    tokio::select,
+    Shutdown, State,
+};
+
+/// Server-sent events of the changes of resources whose ids start with `prefix`, e.g.
+/// `default/Field/`. All resources are watched if `prefix` is not given.
+#[get("/watch?<prefix>")]
+pub async fn watch_handler(
+    fs: &State<FeatureStore>,
+    prefix: Option<String>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], Custom<Json<GenericResponse>>> {
+    let prefix = prefix.unwrap_or_default();
+    info!("Watching resources with prefix {prefix:?}");
+    let mut events = match fs.registry().watch(&prefix).await {
+        Ok(events) => events,
+        Err(e) => {
+            return Err(generate_error_response(format!(
+                "Error watching resources with prefix {prefix}: {e}",
+            )))
+        }
+    };
+    Ok(EventStream! {
+        loop {
+            select! {
+                event = events.next() => match event {
+                    Some(event) => yield Event::json(&event),
+                    None => break,
+                },
+                _ = &mut shutdown => break,
+            }
+        }
+    })
+}
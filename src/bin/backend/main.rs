@@ -9,6 +9,7 @@ use rocket::{
 
 mod handlers;
 use handlers::{
+    audit_handler::get_audit_log_handler,
     config_handler::get_configs_handler,
     entity_handler::{get_entities_handler, get_entity_handler, post_entity_handler},
     field_handler::{get_field_handler, get_fields_handler, post_field_handler},
@@ -17,10 +18,16 @@ use handlers::{
         get_graph_dataset_handler, get_graph_datasets_handler, post_graph_dataset_handler,
     },
     graph_handler::{get_graph_handler, get_graphs_handler, post_graph_handler},
-    infra_handler::get_infras_handler,
+    history_handler::{get_diff_handler, get_history_handler, post_rollback_handler},
+    infra_handler::{
+        delete_infra_handler, get_infras_handler, get_infras_health_handler, put_infra_handler,
+    },
+    lineage_handler::get_lineage_handler,
+    project_handler::get_projects_handler,
     provider_handler::{
         get_entities_provider_handler, get_fields_via_post_entity_provider_handler,
     },
+    search_handler::search_handler,
     table_feature_view_handler::{
         get_table_feature_view_handler, get_table_feature_views_handler,
         post_table_feature_view_handler,
@@ -33,6 +40,7 @@ use handlers::{
     transformation_handler::{
         get_transformation_handler, get_transformations_handler, post_transformation_handler,
     },
+    watch_handler::watch_handler,
 };
 
 mod responses;
@@ -66,7 +74,8 @@ async fn rocket() -> _ {
         PathBuf::from_iter(vec![env!("CARGO_MANIFEST_DIR"), "examples/quickstart/"]).to_str(),
     )
     .await
-    .unwrap();
+    .unwrap()
+    .with_client("dashboard");
 
     rocket::build().manage(fs).attach(CORS).mount(
         "/",
@@ -89,6 +98,9 @@ async fn rocket() -> _ {
             post_graph_handler,
             // infra
             get_infras_handler,
+            get_infras_health_handler,
+            put_infra_handler,
+            delete_infra_handler,
             // table feature view
             get_table_feature_view_handler,
             get_table_feature_views_handler,
@@ -111,6 +123,20 @@ async fn rocket() -> _ {
             get_topology_handler,
             get_topologies_handler,
             post_topology_handler,
+            // resource revision history
+            get_history_handler,
+            get_diff_handler,
+            post_rollback_handler,
+            // resource lineage
+            get_lineage_handler,
+            // resource search
+            search_handler,
+            // resource changes
+            watch_handler,
+            // projects sharing the registry storage
+            get_projects_handler,
+            // audit log of registry changes and deployments
+            get_audit_log_handler,
         ],
     )
 }
@@ -0,0 +1,207 @@
+//! Command line tool to manage the feature registry configured in `OFNIL_HOME`.
+
+use clap::{Parser, Subcommand};
+use ofnil::{Bundle, FeatureRegistry, FeatureRepo, FeatureStore, SCHEMA_VERSION};
+use std::{error::Error, path::PathBuf};
+
+#[derive(Parser)]
+#[command(about = "Manage the ofnil feature registry")]
+struct Cli {
+    /// Directory of `ofnil.toml`, defaults to env var `OFNIL_HOME`
+    #[arg(long)]
+    home: Option<PathBuf>,
+    /// Project of the registry, defaults to `project` in `ofnil.toml`
+    #[arg(long)]
+    project: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export resources and all resources they reference into a bundle file
+    Export {
+        /// Resource ids, e.g. `default/GraphDataset/reviewer_dataset`
+        #[arg(required = true)]
+        ids: Vec<String>,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Import a bundle file, failing on conflicts with registered resources unless `--overwrite`
+    Import {
+        bundle: PathBuf,
+        #[arg(long)]
+        overwrite: bool,
+        /// Only report what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the projects of the registry storage
+    Projects,
+    /// Move the keys registered before registries were scoped to projects into the project
+    MigrateProject,
+    /// Show the changes needed to register the feature repository
+    Plan {
+        /// Feature repository directory, defaults to `repo_path` in `ofnil.toml`
+        #[arg(long)]
+        repo: Option<PathBuf>,
+    },
+    /// Register the changes of the feature repository
+    Apply {
+        /// Feature repository directory, defaults to `repo_path` in `ofnil.toml`
+        #[arg(long)]
+        repo: Option<PathBuf>,
+    },
+    /// Rewrite the resources stored in older schema versions in the current format
+    MigrateSchema {
+        /// Only report what would be rewritten
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete the transformations, fields and topologies that nothing uses anymore
+    Gc {
+        /// Only report what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Also drop the graph projections left in the Neo4j infras of `ofnil.toml`
+        #[arg(long)]
+        projections: bool,
+        /// Minimum age in hours of the graph projections to drop
+        #[arg(long, default_value_t = 24)]
+        projection_age: i64,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+    let cli = Cli::parse();
+    let home = match cli.home {
+        Some(home) => home,
+        None => std::env::var("OFNIL_HOME")
+            .map_err(|_| "OFNIL_HOME is neither given by --home nor set in environment variable")?
+            .into(),
+    };
+    let mut registry = FeatureRegistry::from_home(&home).await?.with_client("cli");
+    if let Some(project) = cli.project {
+        FeatureRegistry::validate_project_name(&project)?;
+        registry = registry.with_project(project);
+    }
+
+    match cli.command {
+        Command::Export { ids, output } => {
+            let bundle = registry.export_bundle(&ids).await?;
+            bundle.save(&output)?;
+            println!(
+                "Exported {} resources to {}",
+                bundle.resources.len(),
+                output.display()
+            );
+        }
+        Command::Import {
+            bundle,
+            overwrite,
+            dry_run,
+        } => {
+            let bundle = Bundle::load(&bundle)?;
+            let report = if dry_run {
+                registry.plan_import(&bundle).await?
+            } else {
+                registry.import_bundle(&bundle, overwrite).await?
+            };
+            for id in &report.created {
+                println!("+ {id}");
+            }
+            for conflict in &report.conflicts {
+                println!("~ {}", conflict.id);
+                for change in &conflict.changes {
+                    println!("    {change}");
+                }
+            }
+            println!(
+                "{} created, {} unchanged, {} conflicts",
+                report.created.len(),
+                report.unchanged.len(),
+                report.conflicts.len()
+            );
+        }
+        Command::Projects => {
+            for project in registry.list_projects().await? {
+                println!("{project}");
+            }
+        }
+        Command::MigrateProject => {
+            let moved = registry.migrate_to_project().await?;
+            println!("Moved {moved} keys into project {}", registry.project());
+        }
+        Command::Plan { repo } => {
+            let repo = load_repo(&home, repo)?;
+            println!("{}", registry.plan(&repo).await?);
+        }
+        Command::Apply { repo } => {
+            let repo = load_repo(&home, repo)?;
+            let plan = registry.plan(&repo).await?;
+            println!("{plan}");
+            if !plan.is_empty() {
+                registry.apply(&plan).await?;
+                println!("Applied");
+            }
+        }
+        Command::MigrateSchema { dry_run } => {
+            let report = registry.migrate_schema(dry_run).await?;
+            for id in &report.resources {
+                println!("~ {id}");
+            }
+            println!(
+                "{} {} resources and {} revisions to schema version {SCHEMA_VERSION}",
+                if dry_run { "Would migrate" } else { "Migrated" },
+                report.resources.len(),
+                report.revisions
+            );
+        }
+        Command::Gc {
+            dry_run,
+            projections,
+            projection_age,
+        } => {
+            let verb = if dry_run { "Would delete" } else { "Deleted" };
+            let report = registry.collect_garbage(dry_run).await?;
+            let ids = report
+                .transformations
+                .iter()
+                .chain(&report.fields)
+                .chain(&report.topologies);
+            for id in ids {
+                println!("- {id}");
+            }
+            println!(
+                "{verb} {} transformations, {} fields, {} topologies",
+                report.transformations.len(),
+                report.fields.len(),
+                report.topologies.len()
+            );
+            if projections {
+                let fs = FeatureStore::init(home.to_str()).await?;
+                let stale = fs
+                    .infra_manager()
+                    .collect_stale_projections(chrono::Duration::hours(projection_age), dry_run)
+                    .await?;
+                for (infra_id, projection) in &stale {
+                    println!(
+                        "- {infra_id:?} {} (created at {})",
+                        projection.graph_name, projection.created_at
+                    );
+                }
+                println!("{verb} {} graph projections", stale.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_repo(home: &PathBuf, repo: Option<PathBuf>) -> Result<FeatureRepo, Box<dyn Error>> {
+    match repo {
+        Some(repo) => FeatureRepo::load(repo),
+        None => FeatureRepo::from_home(home),
+    }
+}
@@ -1,32 +1,203 @@
-use std::{collections::HashMap, error::Error, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use log::debug;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    infra::pi::{plugin::get_connector_factory, storage::FileFormat},
+    FeatureRegistry, RegistryError,
+};
+
+/// Environment variable selecting a profile of `ofnil.toml`, e.g. `dev`, `staging` or `prod`
+pub const PROFILE_ENV_VAR: &str = "OFNIL_PROFILE";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read config file {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Unknown profile {profile} selected by {PROFILE_ENV_VAR}, profiles are {available:?}")]
+    UnknownProfile {
+        profile: String,
+        available: Vec<String>,
+    },
+    #[error("Unknown registry backend {backend}")]
+    UnknownRegistryBackend { backend: String },
+    #[error("Invalid project of the config: {0}")]
+    InvalidProject(RegistryError),
+    #[error("Unknown infra type {infra_type} of infra {infra}")]
+    UnknownInfraType { infra: String, infra_type: String },
+    #[error("Missing {field} of infra {infra}")]
+    MissingValue { infra: String, field: String },
+    #[error("Invalid {field} of infra {infra}: {reason}")]
+    InvalidValue {
+        infra: String,
+        field: String,
+        reason: String,
+    },
+    #[error("Environment variable {var} of the {field} of infra {infra} is not set")]
+    MissingEnvVar {
+        infra: String,
+        field: String,
+        var: String,
+    },
+    #[error("Cannot read secret file {} of the {field} of infra {infra}: {source}", path.display())]
+    SecretFile {
+        infra: String,
+        field: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct FeatureStoreConfig {
     pub(crate) project: String,
-    pub(crate) registry_endpoints: Vec<String>,
+    pub(crate) registry: RegistryConfig,
     pub(crate) infra_manager: HashMap<String, InfraConfig>,
+    /// Directory of the declarative feature repository
+    pub(crate) repo_path: PathBuf,
 }
 
+/// Storage backend of the feature registry, selected by `registry_backend` in `ofnil.toml`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum RegistryConfig {
+    Etcd { endpoints: Vec<String> },
+    Memory,
+    Local { path: PathBuf },
+}
+
+/// Connection config of an infra. Passwords are redacted in the [Debug] output, e.g. in logs.
+#[derive(Clone, Serialize, Deserialize)]
 pub enum InfraConfig {
     Neo4jDatabaseProviderConfig {
+        /// Address of the Bolt server, `{host}:{port}`
         uri: String,
         username: String,
         password: String,
+        #[serde(default)]
+        options: Neo4jOptions,
     },
     RedisClientConfig {
+        /// `redis://{host}:{port}`, or `rediss://{host}:{port}` with TLS
         uri: String,
-        // TODO(han): add the support for password in connection info
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default)]
+        options: RedisOptions,
+    },
+    /// Config of an infra of a [ConnectorFactory](crate::ConnectorFactory)
+    Plugin {
+        infra_type: String,
+        /// Keys of the infra in `ofnil.toml` other than `name` and `infra_type`, with `uri`,
+        /// `username` and `password` resolved to strings
+        properties: toml::Table,
     },
 }
 
+impl std::fmt::Debug for InfraConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const REDACTED: &str = "<redacted>";
+        match self {
+            InfraConfig::Neo4jDatabaseProviderConfig {
+                uri,
+                username,
+                options,
+                ..
+            } => f
+                .debug_struct("Neo4jDatabaseProviderConfig")
+                .field("uri", uri)
+                .field("username", username)
+                .field("password", &REDACTED)
+                .field("options", options)
+                .finish(),
+            InfraConfig::RedisClientConfig {
+                uri,
+                username,
+                password,
+                options,
+            } => f
+                .debug_struct("RedisClientConfig")
+                .field("uri", uri)
+                .field("username", username)
+                .field("password", &password.as_ref().map(|_| REDACTED))
+                .field("options", options)
+                .finish(),
+            InfraConfig::Plugin {
+                infra_type,
+                properties,
+            } => {
+                let mut properties = properties.clone();
+                if let Some(password) = properties.get_mut("password") {
+                    *password = REDACTED.into();
+                }
+                f.debug_struct("Plugin")
+                    .field("infra_type", infra_type)
+                    .field("properties", &properties)
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Connection options of a Neo4j infra
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Neo4jOptions {
+    /// Whether to connect with TLS, as by the `bolt+s://` and `neo4j+s://` URI schemes
+    #[serde(default)]
+    pub tls: bool,
+    /// Database of the queries, or the default database of the server if `None`
+    pub database: Option<String>,
+    /// Maximum number of pooled connections, 128 if `None`
+    pub max_pool_size: Option<u32>,
+    /// Timeout of establishing a connection in seconds, or no timeout if `None`
+    pub connect_timeout_secs: Option<u64>,
+}
+
+/// Connection options of a Redis infra
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedisOptions {
+    /// Whether to connect with TLS, as by the `rediss://` URI scheme
+    #[serde(default)]
+    pub tls: bool,
+    /// Index of the logical database
+    #[serde(default)]
+    pub db: i64,
+    /// Timeout of establishing a connection in seconds, or no timeout if `None`
+    pub connect_timeout_secs: Option<u64>,
+    /// Timeout of reading a response in seconds, or no timeout if `None`
+    pub read_timeout_secs: Option<u64>,
+    /// Timeout of writing a request in seconds, or no timeout if `None`
+    pub write_timeout_secs: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct RawFeatureStoreConfig {
     project: String,
+    /// One of `etcd` (default), `memory` or `local`
+    registry_backend: Option<String>,
+    /// Required by the `etcd` backend
+    #[serde(default)]
     registry_endpoints: Vec<String>,
+    /// Directory of the `local` backend, relative to the config directory if not absolute
+    registry_path: Option<String>,
+    /// Directory of the feature repository TOML files, `features` relative to the config
+    /// directory by default
+    repo_path: Option<String>,
+    #[serde(default)]
     infra: Vec<RawInfraConfig>,
 }
 
@@ -37,85 +208,390 @@ struct RawInfraConfig {
     env_uri: Option<String>,
     env_username: Option<String>,
     env_password: Option<String>,
-    uri: Option<String>,
-    username: Option<String>,
-    password: Option<String>,
+    uri: Option<SecretRef>,
+    username: Option<SecretRef>,
+    password: Option<SecretRef>,
+    /// Connection options, see [Neo4jOptions] and [RedisOptions], and file options of
+    /// [LocalFileConnectorFactory](crate::infra::connectors::LocalFileConnectorFactory)
+    tls: Option<bool>,
+    database: Option<String>,
+    max_pool_size: Option<u32>,
+    db: Option<i64>,
+    connect_timeout_secs: Option<u64>,
+    read_timeout_secs: Option<u64>,
+    write_timeout_secs: Option<u64>,
+    /// One of `csv` (default) or `parquet`
+    format: Option<String>,
+    header: Option<bool>,
+    /// Further keys of the infras of connector factories
+    #[serde(flatten)]
+    properties: toml::Table,
+}
+
+/// Value of a connection field of an infra, either given in `ofnil.toml` or referring to where
+/// the value is kept, e.g. `password = { env = "NEO4J_PASSWORD" }` or
+/// `password = { file = "/run/secrets/neo4j_password" }`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SecretRef {
+    Value(String),
+    /// Environment variable, which may be set in the `.env` file of the config directory
+    Env {
+        env: String,
+    },
+    /// File of the value, relative to the config directory if not absolute. Trailing newlines
+    /// are stripped.
+    File {
+        file: PathBuf,
+    },
+}
+
+impl RawInfraConfig {
+    /// Resolves the value of `field`, given directly or as a secret reference, or else by its
+    /// `env_` variable. Returns `None` if neither is given.
+    fn resolve(
+        &self,
+        field: &str,
+        value: &Option<SecretRef>,
+        env: &Option<String>,
+        dir: &Path,
+    ) -> Result<Option<String>, ConfigError> {
+        let reference = match (value, env) {
+            (Some(reference), _) => reference,
+            (None, Some(var)) => return self.read_env(field, var).map(Some),
+            (None, None) => return Ok(None),
+        };
+        match reference {
+            SecretRef::Value(value) => Ok(Some(value.clone())),
+            SecretRef::Env { env } => self.read_env(field, env).map(Some),
+            SecretRef::File { file } => {
+                let path = dir.join(file);
+                let secret =
+                    std::fs::read_to_string(&path).map_err(|source| ConfigError::SecretFile {
+                        infra: self.name.clone(),
+                        field: field.to_string(),
+                        path,
+                        source,
+                    })?;
+                Ok(Some(secret.trim_end_matches(['\r', '\n']).to_string()))
+            }
+        }
+    }
+
+    fn read_env(&self, field: &str, var: &str) -> Result<String, ConfigError> {
+        dotenv::var(var).map_err(|_| ConfigError::MissingEnvVar {
+            infra: self.name.clone(),
+            field: field.to_string(),
+            var: var.to_string(),
+        })
+    }
+
+    fn uri(&self, dir: &Path) -> Result<String, ConfigError> {
+        self.resolve("uri", &self.uri, &self.env_uri, dir)?
+            .ok_or_else(|| ConfigError::MissingValue {
+                infra: self.name.clone(),
+                field: "uri".to_string(),
+            })
+    }
+
+    /// Splits the URI of a Neo4j infra into the address of the Bolt server and whether its
+    /// scheme requires TLS
+    fn neo4j_address(&self, dir: &Path) -> Result<(String, bool), ConfigError> {
+        let uri = self.uri(dir)?;
+        let (scheme, address) = uri.split_once("://").unwrap_or(("bolt", &uri));
+        let tls = match scheme {
+            "bolt" | "neo4j" => false,
+            "bolt+s" | "neo4j+s" => true,
+            // TODO: trust self-signed certificates of the `+ssc` schemes
+            _ => {
+                return Err(ConfigError::InvalidValue {
+                    infra: self.name.clone(),
+                    field: "uri".to_string(),
+                    reason: format!("unsupported scheme {scheme}"),
+                })
+            }
+        };
+        Ok((address.to_string(), tls))
+    }
+
+    /// Properties of an infra of a connector factory, see [InfraConfig::Plugin]
+    fn plugin_properties(&self, dir: &Path) -> Result<toml::Table, ConfigError> {
+        let mut properties = self.properties.clone();
+        let fields = [
+            ("uri", &self.uri, &self.env_uri),
+            ("username", &self.username, &self.env_username),
+            ("password", &self.password, &self.env_password),
+        ];
+        for (field, value, env) in fields {
+            if let Some(value) = self.resolve(field, value, env, dir)? {
+                properties.insert(field.to_string(), value.into());
+            }
+        }
+        let options = [
+            ("tls", self.tls.map(toml::Value::from)),
+            ("database", self.database.clone().map(toml::Value::from)),
+            ("max_pool_size", self.max_pool_size.map(toml::Value::from)),
+            ("db", self.db.map(toml::Value::from)),
+            (
+                "connect_timeout_secs",
+                self.connect_timeout_secs.map(|secs| (secs as i64).into()),
+            ),
+            (
+                "read_timeout_secs",
+                self.read_timeout_secs.map(|secs| (secs as i64).into()),
+            ),
+            (
+                "write_timeout_secs",
+                self.write_timeout_secs.map(|secs| (secs as i64).into()),
+            ),
+            ("format", self.format.clone().map(toml::Value::from)),
+            ("header", self.header.map(toml::Value::from)),
+        ];
+        for (key, value) in options {
+            if let Some(value) = value {
+                properties.insert(key.to_string(), value);
+            }
+        }
+        Ok(properties)
+    }
+
+    /// Directory of a local file infra, given by `uri` as a path or a `file://` URI, relative to
+    /// the config directory if not absolute
+    fn local_file_path(&self, dir: &Path) -> Result<PathBuf, ConfigError> {
+        let uri = self.uri(dir)?;
+        Ok(dir.join(uri.strip_prefix("file://").unwrap_or(&uri)))
+    }
+
+    fn file_format(&self) -> Result<FileFormat, ConfigError> {
+        match self.format.as_deref().unwrap_or("csv") {
+            "csv" => Ok(FileFormat::CSV),
+            "parquet" => Ok(FileFormat::Parquet),
+            format => Err(ConfigError::InvalidValue {
+                infra: self.name.clone(),
+                field: "format".to_string(),
+                reason: format!("unsupported file format {format}"),
+            }),
+        }
+    }
+
+    /// Credentials that are not given are empty, e.g. of a database without authentication
+    fn credential(
+        &self,
+        field: &str,
+        value: &Option<SecretRef>,
+        env: &Option<String>,
+        dir: &Path,
+    ) -> Result<String, ConfigError> {
+        Ok(self.resolve(field, value, env, dir)?.unwrap_or_default())
+    }
 }
 
 impl FeatureStoreConfig {
-    pub(crate) fn from_dir(path: &Path) -> Result<Self, Box<dyn Error>> {
+    /// Loads `ofnil.toml` of directory `path`, with the profile selected by [PROFILE_ENV_VAR] if
+    /// set. Environment variables are also read from the `.env` file of the directory.
+    pub(crate) fn from_dir(path: &Path) -> Result<Self, ConfigError> {
         let config_path = path.join("ofnil.toml");
         let dotenv_path = path.join(".env");
         dotenv::from_path(dotenv_path.as_path()).ok();
 
-        let raw_config = read_toml_to_raw_config(config_path.as_path()).unwrap();
-        let config = raw_to_config(raw_config);
+        let profile = dotenv::var(PROFILE_ENV_VAR).ok();
+        let raw_config = read_toml_to_raw_config(config_path.as_path(), profile.as_deref())?;
+        let mut config = raw_to_config(raw_config, path)?;
+        if let RegistryConfig::Local {
+            path: registry_path,
+        } = &mut config.registry
+        {
+            *registry_path = path.join(&registry_path);
+        }
+        config.repo_path = path.join(&config.repo_path);
         debug!("Config: {:?}", config);
 
         Ok(config)
     }
 }
 
-fn read_toml_to_raw_config(filename: &Path) -> Result<RawFeatureStoreConfig, Box<dyn Error>> {
-    let config: RawFeatureStoreConfig =
-        toml::from_str(std::fs::read_to_string(filename)?.as_str())?;
+fn read_toml_to_raw_config(
+    filename: &Path,
+    profile: Option<&str>,
+) -> Result<RawFeatureStoreConfig, ConfigError> {
+    let content = std::fs::read_to_string(filename).map_err(|source| ConfigError::Read {
+        path: filename.to_path_buf(),
+        source,
+    })?;
+    let parse_error = |source| ConfigError::Parse {
+        path: filename.to_path_buf(),
+        source,
+    };
+    let table: toml::Table = toml::from_str(&content).map_err(parse_error)?;
+    toml::Value::Table(apply_profile(table, profile)?)
+        .try_into()
+        .map_err(parse_error)
+}
+
+/// Overrides the top-level keys of the config by those of table `[profiles.{profile}]`. Infras
+/// of the profile override the keys of the infras of the same name, or are added if there are
+/// none.
+fn apply_profile(
+    mut config: toml::Table,
+    profile: Option<&str>,
+) -> Result<toml::Table, ConfigError> {
+    let mut profiles = match config.remove("profiles") {
+        Some(toml::Value::Table(profiles)) => profiles,
+        _ => toml::Table::new(),
+    };
+    let Some(profile) = profile else {
+        return Ok(config);
+    };
+    let overrides = match profiles.remove(profile) {
+        Some(toml::Value::Table(overrides)) => overrides,
+        _ => {
+            return Err(ConfigError::UnknownProfile {
+                profile: profile.to_string(),
+                available: profiles.keys().cloned().collect(),
+            })
+        }
+    };
+    debug!("Profile: {profile}");
+    for (key, value) in overrides {
+        match (key.as_str(), value, config.get_mut("infra")) {
+            ("infra", toml::Value::Array(infras), Some(toml::Value::Array(base))) => {
+                for infra in infras {
+                    let name = infra.get("name").cloned();
+                    let existing = base
+                        .iter_mut()
+                        .find(|base| name.is_some() && base.get("name") == name.as_ref());
+                    match (existing, infra) {
+                        (Some(toml::Value::Table(existing)), toml::Value::Table(infra)) => {
+                            existing.extend(infra)
+                        }
+                        (_, infra) => base.push(infra),
+                    }
+                }
+            }
+            (_, value, _) => {
+                config.insert(key, value);
+            }
+        }
+    }
     Ok(config)
 }
 
-fn raw_to_config(raw_config: RawFeatureStoreConfig) -> FeatureStoreConfig {
+/// Converts the raw config of directory `dir`, which resolves the relative paths of secret files.
+fn raw_to_config(
+    raw_config: RawFeatureStoreConfig,
+    dir: &Path,
+) -> Result<FeatureStoreConfig, ConfigError> {
+    FeatureRegistry::validate_project_name(&raw_config.project)
+        .map_err(ConfigError::InvalidProject)?;
     let mut infra_manager = HashMap::new();
     for infra in raw_config.infra {
-        debug!("Infra Config: {:?}", infra);
+        debug!("Infra Config: {:?}", infra.name);
         let infra_config = match infra.infra_type.as_str() {
-            "neo4j" => InfraConfig::Neo4jDatabaseProviderConfig {
-                uri: {
-                    let uri = infra.uri.unwrap_or_else(|| {
-                        dotenv::var(infra.env_uri.clone().unwrap_or_default())
-                            .unwrap_or_else(|_| "".to_string())
-                    });
-
-                    // TODO(tatiana): stripping prefix URI scheme can be problematic
-                    if uri.starts_with("bolt://") {
-                        uri.strip_prefix("bolt://").unwrap().to_string()
-                    } else if uri.starts_with("neo4j://") {
-                        uri.strip_prefix("neo4j://").unwrap().to_string()
-                    } else {
-                        uri
-                    }
-                },
-                username: infra.username.unwrap_or_else(|| {
-                    dotenv::var(infra.env_username.unwrap_or_default())
-                        .unwrap_or_else(|_| "".to_string())
-                }),
-                password: infra.password.unwrap_or_else(|| {
-                    dotenv::var(infra.env_password.unwrap_or_default())
-                        .unwrap_or_else(|_| "".to_string())
-                }),
+            "neo4j" => {
+                let (uri, tls) = infra.neo4j_address(dir)?;
+                InfraConfig::Neo4jDatabaseProviderConfig {
+                    uri,
+                    username: infra.credential(
+                        "username",
+                        &infra.username,
+                        &infra.env_username,
+                        dir,
+                    )?,
+                    password: infra.credential(
+                        "password",
+                        &infra.password,
+                        &infra.env_password,
+                        dir,
+                    )?,
+                    options: Neo4jOptions {
+                        tls: infra.tls.unwrap_or(tls),
+                        database: infra.database.clone(),
+                        max_pool_size: infra.max_pool_size,
+                        connect_timeout_secs: infra.connect_timeout_secs,
+                    },
+                }
+            }
+            "redis" => {
+                let uri = infra.uri(dir)?;
+                let uri = if uri.starts_with("redis://") || uri.starts_with("rediss://") {
+                    uri
+                } else {
+                    format!("redis://{uri}")
+                };
+                InfraConfig::RedisClientConfig {
+                    username: infra.resolve(
+                        "username",
+                        &infra.username,
+                        &infra.env_username,
+                        dir,
+                    )?,
+                    password: infra.resolve(
+                        "password",
+                        &infra.password,
+                        &infra.env_password,
+                        dir,
+                    )?,
+                    options: RedisOptions {
+                        tls: infra.tls.unwrap_or(uri.starts_with("rediss://")),
+                        db: infra.db.unwrap_or_default(),
+                        connect_timeout_secs: infra.connect_timeout_secs,
+                        read_timeout_secs: infra.read_timeout_secs,
+                        write_timeout_secs: infra.write_timeout_secs,
+                    },
+                    uri,
+                }
+            }
+            "local_file" => {
+                infra.file_format()?;
+                let mut properties = infra.plugin_properties(dir)?;
+                let path = infra.local_file_path(dir)?;
+                properties.insert("uri".to_string(), path.display().to_string().into());
+                InfraConfig::Plugin {
+                    infra_type: infra.infra_type.clone(),
+                    properties,
+                }
+            }
+            infra_type if get_connector_factory(infra_type).is_some() => InfraConfig::Plugin {
+                infra_type: infra_type.to_string(),
+                properties: infra.plugin_properties(dir)?,
             },
-            "redis" => InfraConfig::RedisClientConfig {
-                uri: {
-                    let uri = infra.uri.unwrap_or_else(|| {
-                        dotenv::var(infra.env_uri.unwrap_or_default())
-                            .unwrap_or_else(|_| "".to_string())
-                    });
-                    if uri.starts_with("redis://") {
-                        uri
-                    } else {
-                        format!("redis://{uri}")
-                    }
-                },
-            },
-
-            _ => panic!("Unknown infra type"),
+            infra_type => {
+                return Err(ConfigError::UnknownInfraType {
+                    infra: infra.name.clone(),
+                    infra_type: infra_type.to_string(),
+                })
+            }
         };
         infra_manager.insert(infra.name, infra_config);
     }
-    debug!("Infra manager: {:?}", infra_manager);
-    FeatureStoreConfig {
+    let registry = match raw_config.registry_backend.as_deref().unwrap_or("etcd") {
+        "etcd" => RegistryConfig::Etcd {
+            endpoints: raw_config.registry_endpoints,
+        },
+        "memory" => RegistryConfig::Memory,
+        "local" => RegistryConfig::Local {
+            path: PathBuf::from(
+                raw_config
+                    .registry_path
+                    .unwrap_or_else(|| "registry".to_string()),
+            ),
+        },
+        backend => {
+            return Err(ConfigError::UnknownRegistryBackend {
+                backend: backend.to_string(),
+            })
+        }
+    };
+    Ok(FeatureStoreConfig {
         project: raw_config.project,
-        registry_endpoints: raw_config.registry_endpoints,
+        registry,
         infra_manager,
-    }
+        repo_path: PathBuf::from(
+            raw_config
+                .repo_path
+                .unwrap_or_else(|| "features".to_string()),
+        ),
+    })
 }
 
 #[cfg(test)]
@@ -123,12 +599,14 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::infra::connectors::RedisConnector;
 
     #[test]
     fn test_parse_toml() {
         let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         let config =
-            read_toml_to_raw_config(dir.join("examples/quickstart/ofnil.toml").as_path()).unwrap();
+            read_toml_to_raw_config(dir.join("examples/quickstart/ofnil.toml").as_path(), None)
+                .unwrap();
         println!("{config:?}");
     }
 
@@ -136,9 +614,302 @@ mod tests {
     fn test_raw_to_config() {
         let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         dotenv::from_path(dir.join("examples/quickstart/.env")).ok();
+        let quickstart = dir.join("examples/quickstart");
         let raw_config =
-            read_toml_to_raw_config(dir.join("examples/quickstart/ofnil.toml").as_path()).unwrap();
-        let config = raw_to_config(raw_config);
+            read_toml_to_raw_config(quickstart.join("ofnil.toml").as_path(), None).unwrap();
+        let config = raw_to_config(raw_config, &quickstart).unwrap();
         println!("{config:?}");
     }
+
+    #[test]
+    fn test_registry_backend() {
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            registry_backend = "local"
+            registry_path = "/tmp/ofnil_registry"
+            infra = []
+            "#,
+        )
+        .unwrap();
+        let config = raw_to_config(raw_config, Path::new(".")).unwrap();
+        assert!(
+            matches!(config.registry, RegistryConfig::Local { path } if path == Path::new("/tmp/ofnil_registry"))
+        );
+
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            registry_endpoints = ["http://localhost:2379"]
+            infra = []
+            "#,
+        )
+        .unwrap();
+        let config = raw_to_config(raw_config, Path::new(".")).unwrap();
+        assert!(
+            matches!(config.registry, RegistryConfig::Etcd { endpoints } if endpoints.len() == 1)
+        );
+    }
+
+    #[test]
+    fn test_profiles_and_secrets() {
+        let dir = std::env::temp_dir().join(format!("ofnil_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("neo4j_password"), "secret\n").unwrap();
+        std::fs::write(
+            dir.join("ofnil.toml"),
+            r#"
+            project = "test"
+            registry_backend = "memory"
+
+            [[infra]]
+            name = "neo4j"
+            infra_type = "neo4j"
+            uri = "bolt://localhost:7687"
+            username = { env = "OFNIL_TEST_CONFIG_NEO4J_USERNAME" }
+            password = { file = "neo4j_password" }
+
+            [profiles.prod]
+            registry_backend = "etcd"
+            registry_endpoints = ["http://etcd:2379"]
+
+            [[profiles.prod.infra]]
+            name = "neo4j"
+            uri = "bolt+s://neo4j:7687"
+            database = "features"
+
+            [[profiles.prod.infra]]
+            name = "redis"
+            infra_type = "redis"
+            env_uri = "OFNIL_TEST_CONFIG_REDIS_URI"
+            password = { file = "neo4j_password" }
+            db = 2
+            "#,
+        )
+        .unwrap();
+        let config_path = dir.join("ofnil.toml");
+        let load = |profile| {
+            let raw_config = read_toml_to_raw_config(&config_path, profile)?;
+            raw_to_config(raw_config, &dir)
+        };
+
+        let err = load(None).unwrap_err();
+        assert!(
+            matches!(&err, ConfigError::MissingEnvVar { infra, field, .. } if infra == "neo4j" && field == "username"),
+            "{err}"
+        );
+        std::env::set_var("OFNIL_TEST_CONFIG_NEO4J_USERNAME", "neo4j");
+        let config = load(None).unwrap();
+        assert!(matches!(config.registry, RegistryConfig::Memory));
+        assert!(matches!(
+            &config.infra_manager["neo4j"],
+            InfraConfig::Neo4jDatabaseProviderConfig { uri, username, password, options }
+                if uri == "localhost:7687" && username == "neo4j" && password == "secret" && !options.tls
+        ));
+
+        assert!(matches!(
+            load(Some("prod")).unwrap_err(),
+            ConfigError::MissingEnvVar { infra, .. } if infra == "redis"
+        ));
+        std::env::set_var("OFNIL_TEST_CONFIG_REDIS_URI", "redis:6379");
+        let config = load(Some("prod")).unwrap();
+        assert!(!format!("{config:?}").contains("secret"));
+        assert!(matches!(config.registry, RegistryConfig::Etcd { .. }));
+        assert!(matches!(
+            &config.infra_manager["neo4j"],
+            InfraConfig::Neo4jDatabaseProviderConfig { uri, password, options, .. }
+                if uri == "neo4j:7687" && password == "secret" && options.tls
+                    && options.database.as_deref() == Some("features")
+        ));
+        assert!(matches!(
+            &config.infra_manager["redis"],
+            InfraConfig::RedisClientConfig { uri, username, password, options }
+                if uri == "redis://redis:6379" && username.is_none()
+                    && password.as_deref() == Some("secret") && options.db == 2
+        ));
+        assert!(matches!(
+            load(Some("staging")).unwrap_err(),
+            ConfigError::UnknownProfile { available, .. } if available == vec!["prod"]
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_infra() {
+        use crate::{Connector, ConnectorFactory, InfraIdentifier, SeResult};
+
+        struct MemcachedFactory;
+
+        #[async_trait::async_trait]
+        impl ConnectorFactory for MemcachedFactory {
+            fn infra_type(&self) -> &str {
+                "test_memcached"
+            }
+
+            async fn create(
+                &self,
+                _infra_id: &InfraIdentifier,
+                _properties: &toml::Table,
+            ) -> SeResult<Box<dyn Connector>> {
+                Err("Not connected in the test".into())
+            }
+        }
+
+        crate::register_connector_factory(MemcachedFactory);
+        std::env::set_var("OFNIL_TEST_CONFIG_MEMCACHED_PASSWORD", "secret");
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            [[infra]]
+            name = "cache"
+            infra_type = "test_memcached"
+            uri = "localhost:11211"
+            password = { env = "OFNIL_TEST_CONFIG_MEMCACHED_PASSWORD" }
+            connect_timeout_secs = 5
+            compression = true
+            "#,
+        )
+        .unwrap();
+        let config = raw_to_config(raw_config, Path::new(".")).unwrap();
+        let InfraConfig::Plugin {
+            infra_type,
+            properties,
+        } = &config.infra_manager["cache"]
+        else {
+            panic!("Expected a plugin config");
+        };
+        assert_eq!(infra_type, "test_memcached");
+        assert_eq!(
+            properties,
+            &toml::toml! {
+                uri = "localhost:11211"
+                password = "secret"
+                connect_timeout_secs = 5
+                compression = true
+            }
+        );
+        assert!(!format!("{config:?}").contains("secret"));
+    }
+
+    #[test]
+    fn test_local_file_infra() {
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            [[infra]]
+            name = "offline"
+            infra_type = "local_file"
+            uri = "file://offline_store"
+            header = false
+            "#,
+        )
+        .unwrap();
+        let config = raw_to_config(raw_config, Path::new("/etc/ofnil")).unwrap();
+        assert!(matches!(
+            &config.infra_manager["offline"],
+            InfraConfig::Plugin { infra_type, properties }
+                if infra_type == "local_file" && properties == &toml::toml! {
+                    uri = "/etc/ofnil/offline_store"
+                    header = false
+                }
+        ));
+
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            [[infra]]
+            name = "offline"
+            infra_type = "local_file"
+            uri = "/data/offline_store"
+            format = "orc"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            raw_to_config(raw_config, Path::new(".")).unwrap_err(),
+            ConfigError::InvalidValue { field, .. } if field == "format"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_infra() {
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            [[infra]]
+            name = "cache"
+            infra_type = "memcached"
+            uri = "localhost:11211"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            raw_to_config(raw_config, Path::new(".")).unwrap_err(),
+            ConfigError::UnknownInfraType { infra, infra_type } if infra == "cache" && infra_type == "memcached"
+        ));
+
+        let raw_config: RawFeatureStoreConfig = toml::from_str(
+            r#"
+            project = "test"
+            [[infra]]
+            name = "redis"
+            infra_type = "redis"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            raw_to_config(raw_config, Path::new(".")).unwrap_err(),
+            ConfigError::MissingValue { infra, field } if infra == "redis" && field == "uri"
+        ));
+    }
+
+    #[test]
+    fn test_redis_tls() {
+        for tls in [
+            "uri = \"rediss://localhost:6380\"",
+            "uri = \"localhost:6380\"\ntls = true",
+        ] {
+            let raw_config: RawFeatureStoreConfig = toml::from_str(&format!(
+                r#"
+                project = "test"
+                [[infra]]
+                name = "redis"
+                infra_type = "redis"
+                {tls}
+                "#
+            ))
+            .unwrap();
+            let config = raw_to_config(raw_config, Path::new(".")).unwrap();
+            let InfraConfig::RedisClientConfig {
+                uri,
+                username,
+                password,
+                options,
+            } = &config.infra_manager["redis"]
+            else {
+                panic!(
+                    "Unexpected infra config {:?}",
+                    config.infra_manager["redis"]
+                );
+            };
+            assert!(options.tls);
+            let connector = RedisConnector::with_options(
+                uri.as_str(),
+                username.clone(),
+                password.clone(),
+                options,
+            )
+            .unwrap();
+            let info = connector
+                .get_client()
+                .lock()
+                .unwrap()
+                .get_connection_info()
+                .clone();
+            assert!(matches!(
+                info.addr,
+                redis::ConnectionAddr::TcpTls { port: 6380, .. }
+            ));
+        }
+    }
 }
@@ -9,6 +9,7 @@ mod feature_value_type;
 mod feature_view;
 mod field;
 mod graph;
+mod resource;
 mod topology;
 mod transformation;
 mod variant;
@@ -23,7 +24,8 @@ pub use feature_value_type::FeatureValueType;
 pub use feature_view::{TableFeatureView, TopologyFeatureView};
 pub use field::Field;
 pub use graph::Graph;
-pub use topology::{Topology, TopologyType};
+pub use resource::{Resource, ResourceKind};
+pub use topology::{Topology, TopologySnapshot, TopologyType};
 pub use transformation::{Transformation, TransformationType};
 pub use variant::Variant;
 pub type ResourceId = String;
@@ -45,6 +47,19 @@ pub trait ResourceOp: Serialize + DeserializeOwned + Debug + Clone {
     fn sink_infra_id(&self) -> Option<InfraIdentifier> {
         None
     }
+
+    /// Ids of the registry resources that this resource refers to
+    fn references(&self) -> Vec<ResourceId> {
+        Vec::new()
+    }
+
+    /// Deprecated resources stay readable but cannot be used by new [GraphDataset]s
+    fn is_deprecated(&self) -> bool {
+        false
+    }
+
+    /// Marks the resource as deprecated, ignored by resources that cannot be deprecated
+    fn set_deprecated(&mut self, _deprecated: bool) {}
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -54,9 +69,13 @@ pub struct GraphDataset {
     pub description: Option<String>,
     pub table_feature_views: Vec<TableFeatureView>,
     pub topology_feature_views: Vec<TopologyFeatureView>,
+    /// Defaults for datasets registered by builds without the `serving` feature
     #[cfg(feature = "serving")]
+    #[serde(default)]
     pub rendering_opt: GraphDatasetRenderingOptions,
     pub deployed: bool,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl GraphDataset {
@@ -81,6 +100,26 @@ impl ResourceOp for GraphDataset {
     fn resource_id(&self) -> ResourceId {
         format!("{}/{}/{}", &self.variant, "GraphDataset", &self.name)
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.table_feature_views
+            .iter()
+            .map(|view| view.resource_id())
+            .chain(
+                self.topology_feature_views
+                    .iter()
+                    .map(|view| view.resource_id()),
+            )
+            .collect()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 pub(crate) fn init_module(module: &PyModule) -> PyResult<()> {
@@ -13,6 +13,7 @@ macro_rules! entity {
             variant: $variant,
             tlabel: $tlabel.to_owned(),
             primary_key: $primary_key.to_owned(),
+            deprecated: false,
         })
     };
 
@@ -28,6 +29,7 @@ macro_rules! entity {
             dst_entity_id: $dst_entity.resource_id(),
             directed: false,
             primary_key: None,
+            deprecated: false,
         })
     }};
 }
@@ -45,6 +47,8 @@ pub struct VertexEntity {
     pub tlabel: String,
     pub primary_key: String,
     pub variant: Variant,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 #[pyclass(get_all)]
@@ -59,6 +63,8 @@ pub struct EdgeEntity {
     pub directed: bool,
     pub primary_key: Option<String>,
     pub variant: Variant,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl Entity {
@@ -115,6 +121,29 @@ impl ResourceOp for Entity {
     fn id_to_name(id: &str) -> &str {
         id.split('/').nth(2).unwrap()
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        match self {
+            Entity::Vertex(_) => Vec::new(),
+            Entity::Edge(entity) => {
+                vec![entity.src_entity_id.clone(), entity.dst_entity_id.clone()]
+            }
+        }
+    }
+
+    fn is_deprecated(&self) -> bool {
+        match self {
+            Entity::Vertex(entity) => entity.deprecated,
+            Entity::Edge(entity) => entity.deprecated,
+        }
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        match self {
+            Entity::Vertex(entity) => entity.deprecated = deprecated,
+            Entity::Edge(entity) => entity.deprecated = deprecated,
+        }
+    }
 }
 
 impl IntoPy<PyObject> for Entity {
@@ -154,6 +183,7 @@ pub fn vertex_entity(
         },
         tlabel,
         primary_key,
+        deprecated: false,
     })
 }
 
@@ -180,5 +210,6 @@ pub fn edge_entity(
         src_entity_id: src_entity.resource_id(),
         dst_entity_id: dst_entity.resource_id(),
         directed: directed.unwrap_or(false),
+        deprecated: false,
     })
 }
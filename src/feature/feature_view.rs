@@ -25,12 +25,29 @@ pub struct TableFeatureView {
     pub owner: Option<String>,
     #[cfg(feature = "serving")]
     pub rendering_opt: FeatureRenderingOptions,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl ResourceOp for TableFeatureView {
     fn resource_id(&self) -> ResourceId {
         format!("{}/{}/{}", &self.variant, "TableFeatureView", &self.name)
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        std::iter::once(&self.entity_id)
+            .chain(self.field_ids.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,12 +66,26 @@ pub struct TopologyFeatureView {
     pub owners: Vec<String>,
     #[cfg(feature = "serving")]
     pub rendering_opt: TopologyRenderingOptions,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl ResourceOp for TopologyFeatureView {
     fn resource_id(&self) -> ResourceId {
         format!("{}/{}/{}", self.variant, "TopologyFeatureView", &self.name)
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.topology_ids.clone()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 impl TableFeatureView {
@@ -75,6 +106,7 @@ impl TableFeatureView {
                 FeatureServingOutputType::NdArray,
                 ServingMode::PythonBinding,
             ),
+            deprecated: false,
         }
     }
 }
@@ -97,6 +129,7 @@ impl TopologyFeatureView {
                 TopologyServingLayout::CompressedSparseRow,
                 ServingMode::PythonBinding,
             ),
+            deprecated: false,
         }
     }
 }
@@ -27,6 +27,8 @@ pub struct Field {
     pub tags: HashMap<String, String>,
     pub owners: Vec<String>,
     pub sink_infra_id: Option<InfraIdentifier>,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 // Now the Field resource id is set to be `Field/{EntityName}/{FieldName}/{FieldVariant}`
@@ -52,6 +54,22 @@ impl ResourceOp for Field {
     fn transformation_id(&self) -> Option<ResourceId> {
         self.transformation_id.clone()
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.entity_id
+            .iter()
+            .chain(self.transformation_id.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 impl Field {
@@ -73,6 +91,7 @@ impl Field {
                 tags: HashMap::new(),
                 owners: Vec::new(),
                 sink_infra_id: sink_infra_id.clone(),
+                deprecated: false,
             })
             .collect()
     }
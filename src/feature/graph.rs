@@ -19,12 +19,26 @@ pub struct Graph {
     pub tags: HashMap<String, String>,
     pub owners: Vec<String>,
     pub sink_infra_id: Option<InfraIdentifier>,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl ResourceOp for Graph {
     fn resource_id(&self) -> ResourceId {
         format!("{}/{}/{}", &self.variant, "Graph", &self.name)
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.entity_ids.values().cloned().collect()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 impl Graph {
@@ -45,6 +59,7 @@ impl Graph {
             tags: HashMap::new(),
             owners: Vec::new(),
             sink_infra_id,
+            deprecated: false,
         }
     }
 
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::{Display, EnumIter, EnumString};
+
+use super::{ResourceId, ResourceOp};
+use crate::{
+    Entity, FeatureValueType, Field, Graph, GraphDataset, InfraIdentifier, TableFeatureView,
+    Topology, TopologyFeatureView, Transformation,
+};
+
+/// Kind of a registry resource, i.e. the second segment of its resource id
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter,
+)]
+pub enum ResourceKind {
+    Entity,
+    Field,
+    Topology,
+    Graph,
+    TableFeatureView,
+    TopologyFeatureView,
+    Transformation,
+    GraphDataset,
+}
+
+impl ResourceKind {
+    /// Parses the kind from a resource id like `default/Entity/Reviewer`
+    pub fn of(id: &str) -> Option<ResourceKind> {
+        id.split('/').nth(1)?.parse().ok()
+    }
+}
+
+/// A resource of any kind, for operations that handle registry resources uniformly
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Resource {
+    Entity(Entity),
+    Field(Field),
+    Topology(Topology),
+    Graph(Graph),
+    TableFeatureView(TableFeatureView),
+    TopologyFeatureView(TopologyFeatureView),
+    Transformation(Transformation),
+    GraphDataset(GraphDataset),
+}
+
+macro_rules! dispatch {
+    ($self: expr, $resource: ident => $expr: expr) => {
+        match $self {
+            Resource::Entity($resource) => $expr,
+            Resource::Field($resource) => $expr,
+            Resource::Topology($resource) => $expr,
+            Resource::Graph($resource) => $expr,
+            Resource::TableFeatureView($resource) => $expr,
+            Resource::TopologyFeatureView($resource) => $expr,
+            Resource::Transformation($resource) => $expr,
+            Resource::GraphDataset($resource) => $expr,
+        }
+    };
+}
+
+impl Resource {
+    /// Deserializes a resource registered as `value` under `id`
+    pub fn from_json(id: &str, value: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let kind = ResourceKind::of(id).ok_or_else(|| format!("Unknown resource kind of {id}"))?;
+        Ok(match kind {
+            ResourceKind::Entity => Resource::Entity(serde_json::from_str(value)?),
+            ResourceKind::Field => Resource::Field(serde_json::from_str(value)?),
+            ResourceKind::Topology => Resource::Topology(serde_json::from_str(value)?),
+            ResourceKind::Graph => Resource::Graph(serde_json::from_str(value)?),
+            ResourceKind::TableFeatureView => {
+                Resource::TableFeatureView(serde_json::from_str(value)?)
+            }
+            ResourceKind::TopologyFeatureView => {
+                Resource::TopologyFeatureView(serde_json::from_str(value)?)
+            }
+            ResourceKind::Transformation => Resource::Transformation(serde_json::from_str(value)?),
+            ResourceKind::GraphDataset => Resource::GraphDataset(serde_json::from_str(value)?),
+        })
+    }
+
+    /// Wraps a resource of a concrete type
+    pub fn from_resource_op(
+        resource: &impl ResourceOp,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Resource::from_json(&resource.resource_id(), &serde_json::to_string(resource)?)
+    }
+
+    /// Serializes the inner resource as it is stored in the registry
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        dispatch!(self, resource => serde_json::to_string(resource))
+    }
+
+    pub fn kind(&self) -> ResourceKind {
+        match self {
+            Resource::Entity(_) => ResourceKind::Entity,
+            Resource::Field(_) => ResourceKind::Field,
+            Resource::Topology(_) => ResourceKind::Topology,
+            Resource::Graph(_) => ResourceKind::Graph,
+            Resource::TableFeatureView(_) => ResourceKind::TableFeatureView,
+            Resource::TopologyFeatureView(_) => ResourceKind::TopologyFeatureView,
+            Resource::Transformation(_) => ResourceKind::Transformation,
+            Resource::GraphDataset(_) => ResourceKind::GraphDataset,
+        }
+    }
+
+    pub fn resource_id(&self) -> ResourceId {
+        dispatch!(self, resource => resource.resource_id())
+    }
+
+    pub fn references(&self) -> Vec<ResourceId> {
+        dispatch!(self, resource => resource.references())
+    }
+
+    pub fn sink_infra_id(&self) -> Option<InfraIdentifier> {
+        dispatch!(self, resource => resource.sink_infra_id())
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Resource::Entity(entity) => entity.name(),
+            Resource::Field(field) => &field.name,
+            Resource::Topology(topology) => &topology.name,
+            Resource::Graph(graph) => &graph.name,
+            Resource::TableFeatureView(view) => &view.name,
+            Resource::TopologyFeatureView(view) => &view.name,
+            Resource::Transformation(transformation) => &transformation.name,
+            Resource::GraphDataset(dataset) => &dataset.name,
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Resource::Entity(_) => None,
+            Resource::Field(field) => field.description.as_deref(),
+            Resource::Topology(topology) => topology.description.as_deref(),
+            Resource::Graph(graph) => graph.description.as_deref(),
+            Resource::TableFeatureView(view) => view.description.as_deref(),
+            Resource::TopologyFeatureView(view) => view.description.as_deref(),
+            Resource::Transformation(transformation) => transformation.description.as_deref(),
+            Resource::GraphDataset(dataset) => dataset.description.as_deref(),
+        }
+    }
+
+    /// Tags of the resource, `None` for kinds without tags
+    pub fn tags(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            Resource::Entity(_) | Resource::GraphDataset(_) => None,
+            Resource::Field(field) => Some(&field.tags),
+            Resource::Topology(topology) => Some(&topology.tags),
+            Resource::Graph(graph) => Some(&graph.tags),
+            Resource::TableFeatureView(view) => Some(&view.tags),
+            Resource::TopologyFeatureView(view) => Some(&view.tags),
+            Resource::Transformation(transformation) => Some(&transformation.tags),
+        }
+    }
+
+    pub fn owners(&self) -> Vec<&str> {
+        match self {
+            Resource::Entity(_) | Resource::GraphDataset(_) => Vec::new(),
+            Resource::Field(field) => field.owners.iter().map(String::as_str).collect(),
+            Resource::Topology(topology) => topology.owners.iter().map(String::as_str).collect(),
+            Resource::Graph(graph) => graph.owners.iter().map(String::as_str).collect(),
+            Resource::TableFeatureView(view) => view.owner.as_deref().into_iter().collect(),
+            Resource::TopologyFeatureView(view) => view.owners.iter().map(String::as_str).collect(),
+            Resource::Transformation(transformation) => {
+                transformation.owners.iter().map(String::as_str).collect()
+            }
+        }
+    }
+
+    /// Value type of the resource, only defined for fields
+    pub fn value_type(&self) -> Option<&FeatureValueType> {
+        match self {
+            Resource::Field(field) => Some(&field.value_type),
+            _ => None,
+        }
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        dispatch!(self, resource => resource.is_deprecated())
+    }
+
+    pub fn set_deprecated(&mut self, deprecated: bool) {
+        dispatch!(self, resource => resource.set_deprecated(deprecated))
+    }
+}
@@ -4,7 +4,7 @@ use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::InfraIdentifier;
+use crate::{infra::pi::storage::FileFormat, InfraIdentifier};
 
 use super::ResourceId;
 use super::ResourceOp;
@@ -42,6 +42,34 @@ pub struct Topology {
     pub owners: Vec<String>,
 
     pub sink_infra_id: Option<InfraIdentifier>,
+    #[serde(default)]
+    pub deprecated: bool,
+    /// Files exported by [FeatureStore::export_topology](crate::FeatureStore::export_topology),
+    /// at most one per infra
+    #[serde(default)]
+    pub snapshots: Vec<TopologySnapshot>,
+}
+
+/// Binary files of a topology exported to a local file infra, see
+/// [LocalFileConnector::write_topology](crate::infra::connectors::file::local::LocalFileConnector::write_topology)
+/// for the layout of the files
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TopologySnapshot {
+    pub infra_id: InfraIdentifier,
+    /// Directory of the files
+    pub path: String,
+    /// [FileFormat::CSR] and/or [FileFormat::COO]
+    pub formats: Vec<FileFormat>,
+    /// Labels of the id mappings of the src and dst vertices
+    pub src_tlabel: String,
+    pub dst_tlabel: String,
+    pub num_src_vertices: u64,
+    pub num_dst_vertices: u64,
+    pub num_edges: u64,
+    /// Whether the ids of the edges in the source infra are exported
+    pub edge_ids: bool,
+    #[serde(with = "ts_seconds_option")]
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 impl ResourceOp for Topology {
@@ -56,4 +84,22 @@ impl ResourceOp for Topology {
     fn transformation_id(&self) -> Option<ResourceId> {
         self.transformation_id.clone()
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.edge_entity_id
+            .iter()
+            .chain(self.src_node_entity_id.iter())
+            .chain(self.dst_node_entity_id.iter())
+            .chain(self.transformation_id.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
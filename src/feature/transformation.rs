@@ -23,6 +23,8 @@ pub struct Transformation {
     pub description: Option<String>,
     pub tags: HashMap<String, String>,
     pub owners: Vec<String>,
+    #[serde(default)]
+    pub deprecated: bool,
 }
 
 impl Default for Transformation {
@@ -36,6 +38,7 @@ impl Default for Transformation {
             description: None,
             tags: HashMap::new(),
             owners: Vec::new(),
+            deprecated: false,
         }
     }
 }
@@ -44,6 +47,18 @@ impl ResourceOp for Transformation {
     fn resource_id(&self) -> ResourceId {
         format!("{}/{}/{}", &self.variant, "Transformation", &self.name)
     }
+
+    fn references(&self) -> Vec<ResourceId> {
+        self.source_field_ids.clone()
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn set_deprecated(&mut self, deprecated: bool) {
+        self.deprecated = deprecated;
+    }
 }
 
 impl Transformation {
@@ -2,7 +2,7 @@ use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Variant {
     Default(),
     UserDefined(String),
@@ -20,6 +20,16 @@ impl Variant {
     }
 }
 
+/// Parses the string form of a variant, i.e. the first segment of resource ids
+impl From<&str> for Variant {
+    fn from(version: &str) -> Variant {
+        match version {
+            "default" => Self::Default(),
+            version => Self::user_defined(version),
+        }
+    }
+}
+
 impl std::fmt::Display for Variant {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let version = match self {
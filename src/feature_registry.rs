@@ -1,50 +1,240 @@
+mod audit;
+mod bundle;
+mod dependency;
+mod gc;
+mod lineage;
+mod project;
+mod repo;
+mod revision;
+mod schema;
+mod search;
 mod storage;
+mod validation;
+mod variant;
+mod watch;
 
 use futures::future::join_all;
 use log::{error, info};
 
 use crate::{
-    feature::{ResourceId, ResourceOp, Transformation},
+    config::{FeatureStoreConfig, RegistryConfig},
+    feature::{Resource, ResourceId, ResourceKind, ResourceOp, Transformation},
     *,
 };
-use std::error::Error;
-use storage::EtcdStorage;
+pub use audit::{AuditAction, AuditQuery, AuditRecord};
+pub use bundle::{Bundle, ImportConflict, ImportReport, BUNDLE_VERSION};
+pub use gc::GarbageReport;
+pub use lineage::{LineageDirection, LineageEdge, LineageGraph, LineageNode};
+pub use repo::{FeatureRepo, Plan, PlannedUpdate};
+pub use revision::{ResourceRevision, RevisionDiff, ValueChange};
+pub use schema::{
+    decode_transformation_body, encode_transformation_body, SchemaMigrationReport, SCHEMA_VERSION,
+    SCHEMA_VERSION_KEY, TRANSFORMATION_BODY_VERSION,
+};
+pub use search::{SearchQuery, SearchResult, TagFilter};
+use std::{collections::HashMap, error::Error, path::Path, sync::Arc};
+pub use storage::{EtcdStorage, LocalStorage, MemoryStorage, RegistryStorage, StorageEvent};
+use storage::{ProjectStorage, VersionedStorage};
+pub use validation::{BrokenReference, ReferenceProblem};
+pub use watch::RegistryEvent;
+
+/// Project of registries that are not scoped to a project by [FeatureRegistry::with_project]
+pub const DEFAULT_PROJECT: &str = "default";
 
+/// Client of registries that are not given one by [FeatureRegistry::with_client]
+pub const DEFAULT_CLIENT: &str = "rust";
+
+/// Registry of the resources of one project. Registries of different projects can share the same
+/// storage without overwriting each other's resources.
 pub struct FeatureRegistry {
-    storage: EtcdStorage,
+    /// The shared storage of all projects
+    root: Arc<dyn RegistryStorage>,
+    /// The keyspace of `project` in `root`
+    storage: Box<dyn RegistryStorage>,
+    project: String,
+    /// Recorded as the author of resource revisions and the actor of audit records
+    author: String,
+    /// Recorded as the client of audit records
+    client: String,
 }
 
 impl FeatureRegistry {
+    /// Creates a registry backed by the etcd cluster at `endpoints`.
     pub async fn new(endpoints: Vec<impl Into<String>>) -> Result<Self, Box<dyn Error>> {
         let storage = EtcdStorage::new(endpoints).await?;
-        Ok(Self { storage })
+        Ok(Self::with_storage(storage))
     }
 
     pub async fn default() -> Result<Self, Box<dyn Error>> {
         FeatureRegistry::new(vec!["http://localhost:2379"]).await
     }
 
+    /// Creates a non-persistent registry, e.g. for tests and notebooks.
+    pub fn in_memory() -> Self {
+        Self::with_storage(MemoryStorage::new())
+    }
+
+    /// Creates a registry persisted in the local directory `dir`.
+    pub fn local(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::with_storage(LocalStorage::new(dir)?))
+    }
+
+    /// Creates a registry of [DEFAULT_PROJECT] on `storage`.
+    pub fn with_storage(storage: impl RegistryStorage + 'static) -> Self {
+        let root: Arc<dyn RegistryStorage> = Arc::new(storage);
+        Self {
+            storage: project_storage(&root, DEFAULT_PROJECT),
+            root,
+            project: DEFAULT_PROJECT.to_string(),
+            author: std::env::var("OFNIL_USER")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            client: DEFAULT_CLIENT.to_string(),
+        }
+    }
+
+    /// Sets the author recorded in resource revisions, defaults to env var `OFNIL_USER` or `USER`.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Sets the client recorded in audit records, e.g. `python` or `cli`, defaults to
+    /// [DEFAULT_CLIENT].
+    pub fn with_client(mut self, client: impl Into<String>) -> Self {
+        self.client = client.into();
+        self
+    }
+
+    /// Scopes the registry to `project`, defaults to [DEFAULT_PROJECT].
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = project.into();
+        self.storage = project_storage(&self.root, &self.project);
+        self
+    }
+
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Opens the registry of the project configured in `ofnil.toml` of the `ofnil_home`
+    /// directory.
+    pub async fn from_home(ofnil_home: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let config = FeatureStoreConfig::from_dir(ofnil_home.as_ref())?;
+        Ok(FeatureRegistry::from_config(&config.registry)
+            .await?
+            .with_project(&config.project))
+    }
+
+    pub(crate) async fn from_config(config: &RegistryConfig) -> Result<Self, Box<dyn Error>> {
+        match config {
+            RegistryConfig::Etcd { endpoints } => FeatureRegistry::new(endpoints.clone()).await,
+            RegistryConfig::Memory => Ok(FeatureRegistry::in_memory()),
+            RegistryConfig::Local { path } => FeatureRegistry::local(path),
+        }
+    }
+
     pub async fn register_resource(
         &self,
         resource: &impl ResourceOp,
     ) -> Result<(), Box<dyn Error>> {
-        let key = resource.resource_id();
-        let value = serde_json::to_string(&resource)?;
-        info!("Registering resource: {} -> {}", &key, &value);
-        self.storage.put(&key, &value).await?;
-        Ok(())
+        self.register_resources(&vec![resource]).await
     }
 
+    /// Registers `resource` if it is still of `mod_revision` as returned by
+    /// [FeatureRegistry::get_with_mod_revision], or `0` if it must not be registered yet.
+    /// Fails with [RegistryError::Conflict] otherwise, e.g. if another user has registered it in
+    /// the meantime.
+    pub async fn register_resource_if(
+        &self,
+        resource: &impl ResourceOp,
+        mod_revision: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        let resource = Resource::from_resource_op(resource)?;
+        let expected = HashMap::from([(resource.resource_id(), mod_revision)]);
+        self.register_batch(&[resource], &expected).await
+    }
+
+    /// Registers resources atomically, after checking that the resources they reference are
+    /// registered or among `resources`. Nothing is written if any reference is broken.
     pub async fn register_resources(
         &self,
         resources: &Vec<&impl ResourceOp>,
     ) -> Result<(), Box<dyn Error>> {
+        let mut batch = Vec::with_capacity(resources.len());
         for &resource in resources {
-            self.register_resource(resource).await?;
+            batch.push(Resource::from_resource_op(resource)?);
         }
+        self.register_all(&batch).await
+    }
+
+    /// Registers resources of any kinds atomically, like [FeatureRegistry::register_resources].
+    pub async fn register_all(&self, resources: &[Resource]) -> Result<(), Box<dyn Error>> {
+        self.register_batch(resources, &HashMap::new()).await
+    }
+
+    /// Registers `resources` in a single write that fails with [RegistryError::Conflict] if a
+    /// resource in `expected` is not of the expected mod revision, or if a revision record to
+    /// write has been written concurrently.
+    async fn register_batch(
+        &self,
+        resources: &[Resource],
+        expected: &HashMap<ResourceId, u64>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut batch: Vec<&Resource> = Vec::with_capacity(resources.len());
+        for resource in resources {
+            // the last definition of a resource in the batch wins
+            let id = resource.resource_id();
+            batch.retain(|other| other.resource_id() != id);
+            batch.push(resource);
+        }
+        let batch: Vec<Resource> = batch.into_iter().cloned().collect();
+        self.validate_references(&batch).await?;
+
+        let mut kvs = Vec::new();
+        let mut conditions: Vec<_> = expected
+            .iter()
+            .map(|(id, revision)| (id.clone(), *revision))
+            .collect();
+        let mut audited = Vec::with_capacity(batch.len());
+        for resource in batch {
+            let key = resource.resource_id();
+            let value = resource.to_json()?;
+            if resource.kind() == ResourceKind::GraphDataset
+                && self.storage.get(&key).await.is_err()
+            {
+                // new graph datasets cannot use deprecated resources
+                self.check_not_deprecated(&resource).await?;
+            }
+            info!("Registering resource: {} -> {}", &key, &value);
+            let mut written = None;
+            if let Some((revision_key, revision)) = self.next_revision(&key, &value, None).await? {
+                written = Some(revision.revision);
+                conditions.push((revision_key.clone(), 0));
+                kvs.push((revision_key, serde_json::to_string(&revision)?));
+            }
+            audited.push((key.clone(), written));
+            kvs.push((key, value));
+        }
+        self.append_audit(AuditAction::Register, &audited, &mut kvs, &mut conditions)?;
+        self.storage.put_all_if(&conditions, &kvs).await?;
         Ok(())
     }
 
+    /// Gets resource `id` together with its mod revision, which can be passed to
+    /// [FeatureRegistry::register_resource_if] to update the resource only if it has not been
+    /// changed since.
+    pub async fn get_with_mod_revision<T>(
+        &self,
+        id: &ResourceId,
+    ) -> Result<(T, u64), Box<dyn Error>>
+    where
+        T: ResourceOp,
+    {
+        let (value, mod_revision) = self.storage.get_with_mod_revision(id).await?;
+        Ok((serde_json::from_str::<T>(&value)?, mod_revision))
+    }
+
     pub async fn get_string(&self, id: &ResourceId) -> Result<String, Box<dyn Error>> {
         self.storage.get(id).await
     }
@@ -58,7 +248,40 @@ impl FeatureRegistry {
     }
 
     pub async fn get_all(&self, prefix: &str) -> Result<Vec<String>, Box<dyn Error>> {
-        self.storage.get_all(prefix).await
+        Ok(self
+            .storage
+            .get_by_prefix(prefix)
+            .await?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect())
+    }
+
+    /// Gets all resources of `kind` in `variant`, or in all variants if `variant` is `None`.
+    async fn get_all_of_kind<T>(
+        &self,
+        kind: ResourceKind,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<T>, Box<dyn Error>>
+    where
+        T: ResourceOp,
+    {
+        let values = match variant {
+            Some(variant) => self.get_all(&format!("{variant}/{kind}/")).await?,
+            None => self
+                .storage
+                .get_by_prefix("")
+                .await?
+                .into_iter()
+                .filter(|(key, _)| !key.starts_with("__") && ResourceKind::of(key) == Some(kind))
+                .map(|(_, value)| value)
+                .collect(),
+        };
+        let mut resources = Vec::with_capacity(values.len());
+        for value in values {
+            resources.push(serde_json::from_str::<T>(&value)?);
+        }
+        Ok(resources)
     }
 
     pub async fn get_entity_fields(
@@ -68,7 +291,6 @@ impl FeatureRegistry {
     ) -> Result<Vec<Field>, Box<dyn Error>> {
         let prefix = &format!("{variant}/Field/{entity_name}");
         let values: Result<Vec<Field>, serde_json::Error> = self
-            .storage
             .get_all(prefix)
             .await?
             .iter()
@@ -107,13 +329,11 @@ impl FeatureRegistry {
         Ok(entities)
     }
 
-    pub async fn get_all_entities(&self) -> Result<Vec<Entity>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/Entity/").await?; // TODO: configurable variant?
-        let mut entities = Vec::new();
-        for value in values {
-            entities.push(serde_json::from_str::<Entity>(&value)?)
-        }
-        Ok(entities)
+    pub async fn get_all_entities(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<Entity>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::Entity, variant).await
     }
 
     pub async fn get_field(&self, field_id: &ResourceId) -> Result<Field, Box<dyn Error>> {
@@ -122,13 +342,11 @@ impl FeatureRegistry {
         Ok(field)
     }
 
-    pub async fn get_all_fields(&self) -> Result<Vec<Field>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/Field/").await?; // TODO: configurable variant?
-        let mut fields = Vec::new();
-        for value in values {
-            fields.push(serde_json::from_str::<Field>(&value)?)
-        }
-        Ok(fields)
+    pub async fn get_all_fields(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<Field>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::Field, variant).await
     }
 
     pub async fn get_topology(&self, id: &ResourceId) -> Result<Topology, Box<dyn Error>> {
@@ -137,13 +355,11 @@ impl FeatureRegistry {
         )?)
     }
 
-    pub async fn get_all_topologies(&self) -> Result<Vec<Topology>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/Topology/").await?;
-        let mut topologies = Vec::new();
-        for value in values {
-            topologies.push(serde_json::from_str::<Topology>(&value)?)
-        }
-        Ok(topologies)
+    pub async fn get_all_topologies(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<Topology>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::Topology, variant).await
     }
 
     pub async fn get_table_feature_view(
@@ -156,13 +372,10 @@ impl FeatureRegistry {
 
     pub async fn get_all_table_feature_views(
         &self,
+        variant: Option<&Variant>,
     ) -> Result<Vec<TableFeatureView>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/TableFeatureView/").await?;
-        let mut table_feature_views = Vec::new();
-        for value in values {
-            table_feature_views.push(serde_json::from_str::<TableFeatureView>(&value)?)
-        }
-        Ok(table_feature_views)
+        self.get_all_of_kind(ResourceKind::TableFeatureView, variant)
+            .await
     }
 
     pub async fn get_topology_feature_view(
@@ -175,13 +388,10 @@ impl FeatureRegistry {
 
     pub async fn get_all_topology_feature_views(
         &self,
+        variant: Option<&Variant>,
     ) -> Result<Vec<TopologyFeatureView>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/TopologyFeatureView/").await?;
-        let mut topology_feature_views = Vec::new();
-        for value in values {
-            topology_feature_views.push(serde_json::from_str::<TopologyFeatureView>(&value)?)
-        }
-        Ok(topology_feature_views)
+        self.get_all_of_kind(ResourceKind::TopologyFeatureView, variant)
+            .await
     }
 
     pub async fn get_transformation(
@@ -193,13 +403,12 @@ impl FeatureRegistry {
         Ok(transformation)
     }
 
-    pub async fn get_all_transformations(&self) -> Result<Vec<Transformation>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/Transformation/").await?;
-        let mut transformations = Vec::new();
-        for value in values {
-            transformations.push(serde_json::from_str::<Transformation>(&value)?)
-        }
-        Ok(transformations)
+    pub async fn get_all_transformations(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<Transformation>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::Transformation, variant)
+            .await
     }
 
     pub async fn get_graph(&self, graph_id: &ResourceId) -> Result<Graph, Box<dyn Error>> {
@@ -208,13 +417,11 @@ impl FeatureRegistry {
         Ok(graph)
     }
 
-    pub async fn get_all_graphs(&self) -> Result<Vec<Graph>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/Graph/").await?;
-        let mut graphs = Vec::new();
-        for value in values {
-            graphs.push(serde_json::from_str::<Graph>(&value)?)
-        }
-        Ok(graphs)
+    pub async fn get_all_graphs(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<Graph>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::Graph, variant).await
     }
 
     pub async fn get_graph_dataset(
@@ -226,16 +433,23 @@ impl FeatureRegistry {
         Ok(dataset)
     }
 
-    pub async fn get_all_graph_datasets(&self) -> Result<Vec<GraphDataset>, Box<dyn Error>> {
-        let values = self.storage.get_all("default/GraphDataset/").await?;
-        let mut datasets = Vec::new();
-        for value in values {
-            datasets.push(serde_json::from_str::<GraphDataset>(&value)?)
-        }
-        Ok(datasets)
+    pub async fn get_all_graph_datasets(
+        &self,
+        variant: Option<&Variant>,
+    ) -> Result<Vec<GraphDataset>, Box<dyn Error>> {
+        self.get_all_of_kind(ResourceKind::GraphDataset, variant)
+            .await
     }
 }
 
+/// Storage of the keyspace of `project` in `root`, in which resources are versioned
+fn project_storage(root: &Arc<dyn RegistryStorage>, project: &str) -> Box<dyn RegistryStorage> {
+    Box::new(VersionedStorage::new(ProjectStorage::new(
+        root.clone(),
+        project,
+    )))
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum RegistryError {
     #[error("Error retrieving fields associated with entity {entity} with version {variant}.\nOriginal error: {super_error}")]
@@ -244,4 +458,138 @@ pub enum RegistryError {
         variant: Variant,
         super_error: Box<dyn Error>,
     },
+    #[error("Resource {id} is used by {dependents:?}")]
+    ResourceInUse {
+        id: ResourceId,
+        dependents: Vec<ResourceId>,
+    },
+    #[error("Resource {id} refers to deprecated resource {reference}")]
+    DeprecatedReference {
+        id: ResourceId,
+        reference: ResourceId,
+    },
+    #[error("Resource {id} refers to {reference}, which is not registered")]
+    MissingReference {
+        id: ResourceId,
+        reference: ResourceId,
+    },
+    #[error("Resource {key} has been modified concurrently, expected mod revision {expected} but found {actual}")]
+    Conflict {
+        key: String,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("Broken references {broken:?}")]
+    BrokenReferences { broken: Vec<BrokenReference> },
+    #[error("Resources {ids:?} are registered with different definitions")]
+    ImportConflict { ids: Vec<ResourceId> },
+    #[error(
+        "Bundle version {version} is not supported, the latest supported version is {supported}"
+    )]
+    UnsupportedBundleVersion { version: u32, supported: u32 },
+    #[error(
+        "Schema version {version} is not supported, the latest supported version is {supported}"
+    )]
+    UnsupportedSchemaVersion { version: u32, supported: u32 },
+    #[error("Error migrating payload of schema version {version}: {reason}")]
+    Migration { version: u32, reason: String },
+    #[error("Invalid project name {project:?}, which must be non-empty and must not contain '/'")]
+    InvalidProjectName { project: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, fields};
+
+    #[tokio::test]
+    async fn test_in_memory_registry() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        registry
+            .register_resources(&vec![&reviewer, &product])
+            .await?;
+        let reviewer_fields = fields!(
+            vec![("name", FeatureValueType::String)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        registry
+            .register_resources(&reviewer_fields.iter().collect())
+            .await?;
+
+        assert_eq!(registry.get_all_entities(None).await?.len(), 2);
+        assert_eq!(
+            registry.get_entity(&reviewer.resource_id()).await?.tlabel(),
+            "Reviewer"
+        );
+        let fields = registry
+            .get_entity_fields("Reviewer", &Variant::Default())
+            .await?;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name, "name");
+        assert!(registry
+            .get_field(&"default/Field/Product/asin".to_string())
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_resource_if() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        registry.register_resource_if(&reviewer, 0).await?;
+        assert!(registry.register_resource_if(&reviewer, 0).await.is_err());
+
+        let mut fields = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        registry.register_resource(&fields[0]).await?;
+        let (field, mod_revision) = registry
+            .get_with_mod_revision::<Field>(&fields[0].resource_id())
+            .await?;
+        assert!(field.description.is_none());
+
+        // another user updates the field in the meantime
+        fields[0].description = Some("Age".to_string());
+        registry.register_resource(&fields[0]).await?;
+        fields[0].description = Some("Age of the reviewer".to_string());
+        let err = registry
+            .register_resource_if(&fields[0], mod_revision)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::Conflict { key, expected, .. })
+                if key == &fields[0].resource_id() && *expected == mod_revision
+        ));
+        assert_eq!(
+            registry
+                .get_field(&fields[0].resource_id())
+                .await?
+                .description,
+            Some("Age".to_string())
+        );
+
+        let (_, mod_revision) = registry
+            .get_with_mod_revision::<Field>(&fields[0].resource_id())
+            .await?;
+        registry
+            .register_resource_if(&fields[0], mod_revision)
+            .await?;
+        assert_eq!(
+            registry
+                .list_revisions(&fields[0].resource_id())
+                .await?
+                .len(),
+            3
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,254 @@
+//! Audit log of the changes to the registry, e.g. to know who changed or deployed which resource
+//! and when.
+//!
+//! Every mutating call of [FeatureRegistry] and every deployment appends an [AuditRecord] per
+//! affected resource under [AUDIT_PREFIX] of the project. Records are only ever added, a write
+//! fails rather than overwriting an existing record.
+
+use super::FeatureRegistry;
+use crate::feature::ResourceId;
+
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use strum::{Display, EnumString};
+
+/// Registry keyspace of audit records, `{AUDIT_PREFIX}{nanoseconds}-{process id}-{sequence}`
+pub(crate) const AUDIT_PREFIX: &str = "__audit__/";
+
+/// Sequence number of the audit records written by this process, to tell apart records of the
+/// same timestamp
+static AUDIT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum AuditAction {
+    /// Registered or updated by [FeatureRegistry::register_resources] and the like
+    Register,
+    Delete,
+    Deprecate,
+    Undeprecate,
+    Rollback,
+    /// Imported from a bundle, or copied from another project
+    Import,
+    /// Applied from a feature repository
+    Apply,
+    /// Copied to another variant
+    Promote,
+    /// Referenced from another project
+    Reference,
+    /// Moved into a project from the keys written before registries were scoped to projects
+    Migrate,
+    /// Rewritten in the current schema version by [FeatureRegistry::migrate_schema]
+    MigrateSchema,
+    /// Deployed by [FeatureStore::deploy](crate::FeatureStore::deploy)
+    Deploy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    #[serde(with = "ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    /// The author of the registry, see [FeatureRegistry::with_author]
+    pub actor: String,
+    pub action: AuditAction,
+    pub resource_id: ResourceId,
+    /// The revision of the resource written by the action, if any
+    pub revision: Option<u64>,
+    /// The client of the registry, see [FeatureRegistry::with_client]
+    pub client: String,
+}
+
+/// Criteria of [FeatureRegistry::query_audit_log]. A record matches if it meets all the given
+/// criteria, and the default query matches all records.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    /// Matches records of any of the actions, or of all actions if empty
+    pub actions: Vec<AuditAction>,
+    /// Matches records of the resources whose ids start with the prefix, e.g. `default/Field/`
+    pub resource_prefix: Option<String>,
+    pub client: Option<String>,
+    /// Matches records written at or after the time
+    pub since: Option<DateTime<Utc>>,
+    /// Matches records written before the time
+    pub until: Option<DateTime<Utc>>,
+    /// Number of matched records to skip
+    pub offset: usize,
+    /// Maximum number of records to return, or all if `None`
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        if self
+            .actor
+            .as_ref()
+            .is_some_and(|actor| actor != &record.actor)
+        {
+            return false;
+        }
+        if !self.actions.is_empty() && !self.actions.contains(&record.action) {
+            return false;
+        }
+        if let Some(prefix) = &self.resource_prefix {
+            if !record.resource_id.starts_with(prefix) {
+                return false;
+            }
+        }
+        if self
+            .client
+            .as_ref()
+            .is_some_and(|client| client != &record.client)
+        {
+            return false;
+        }
+        if self.since.is_some_and(|since| record.timestamp < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| record.timestamp >= until) {
+            return false;
+        }
+        true
+    }
+}
+
+fn audit_key(timestamp: &DateTime<Utc>) -> String {
+    let nanos = timestamp.timestamp_nanos_opt().unwrap_or_default();
+    let sequence = AUDIT_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "{AUDIT_PREFIX}{nanos:020}-{:010}-{sequence:010}",
+        std::process::id()
+    )
+}
+
+impl FeatureRegistry {
+    /// Queries the audit log of the project, ordered from the latest to the oldest record.
+    pub async fn query_audit_log(
+        &self,
+        query: &AuditQuery,
+    ) -> Result<Vec<AuditRecord>, Box<dyn Error>> {
+        let mut records = Vec::new();
+        for (_, value) in self
+            .storage
+            .get_by_prefix(AUDIT_PREFIX)
+            .await?
+            .into_iter()
+            .rev()
+        {
+            let record = serde_json::from_str::<AuditRecord>(&value)?;
+            if query.matches(&record) {
+                records.push(record);
+            }
+        }
+        Ok(records
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Appends the audit records of `action` on resources `ids`, each with the written revision
+    /// if any.
+    pub(crate) async fn audit(
+        &self,
+        action: AuditAction,
+        ids: &[(ResourceId, Option<u64>)],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut kvs = Vec::new();
+        let mut conditions = Vec::new();
+        self.append_audit(action, ids, &mut kvs, &mut conditions)?;
+        self.storage.put_all_if(&conditions, &kvs).await
+    }
+
+    /// Adds the audit records of `action` on resources `ids` to the key-values `kvs` of a write,
+    /// so that they are written atomically with the change. `conditions` of the write get that
+    /// the records are not written yet.
+    pub(super) fn append_audit(
+        &self,
+        action: AuditAction,
+        ids: &[(ResourceId, Option<u64>)],
+        kvs: &mut Vec<(String, String)>,
+        conditions: &mut Vec<(String, u64)>,
+    ) -> Result<(), serde_json::Error> {
+        let timestamp = Utc::now();
+        for (id, revision) in ids {
+            let record = AuditRecord {
+                timestamp,
+                actor: self.author.clone(),
+                action,
+                resource_id: id.clone(),
+                revision: *revision,
+                client: self.client.clone(),
+            };
+            let key = audit_key(&timestamp);
+            conditions.push((key.clone(), 0));
+            kvs.push((key, serde_json::to_string(&record)?));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, feature::ResourceOp, Variant, DEFAULT_PROJECT};
+
+    #[tokio::test]
+    async fn test_audit_log() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory()
+            .with_author("alice")
+            .with_client("test");
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        let id = reviewer.resource_id();
+        registry
+            .register_resources(&vec![&reviewer, &product])
+            .await?;
+        registry
+            .in_project(DEFAULT_PROJECT)
+            .with_author("bob")
+            .deprecate_resource(&id)
+            .await?;
+        registry.delete_resource(&id, false).await?;
+
+        let records = registry.query_audit_log(&AuditQuery::default()).await?;
+        let actions: Vec<_> = records
+            .iter()
+            .map(|record| (record.action, record.resource_id.as_str(), record.revision))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (AuditAction::Delete, id.as_str(), None),
+                (AuditAction::Deprecate, id.as_str(), Some(2)),
+                (
+                    AuditAction::Register,
+                    product.resource_id().as_str(),
+                    Some(1)
+                ),
+                (AuditAction::Register, id.as_str(), Some(1)),
+            ]
+        );
+        assert_eq!(records[0].client, "test");
+
+        let query = AuditQuery {
+            actor: Some("alice".to_string()),
+            actions: vec![AuditAction::Register, AuditAction::Delete],
+            resource_prefix: Some(id.clone()),
+            ..Default::default()
+        };
+        let records = registry.query_audit_log(&query).await?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].action, AuditAction::Register);
+        let query = AuditQuery {
+            limit: Some(1),
+            until: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(registry.query_audit_log(&query).await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+//! Export and import of resources as bundles, e.g. to promote features from a dev registry to a
+//! prod registry.
+//!
+//! A [Bundle] contains the exported resources together with all resources that they reference
+//! transitively, so that it can be imported into an empty registry.
+
+use super::{
+    revision::diff_values,
+    schema::{upgrade_resource, SCHEMA_VERSION},
+    AuditAction, FeatureRegistry, RegistryError, ValueChange,
+};
+use crate::feature::{Resource, ResourceId, ResourceKind};
+
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    error::Error,
+    path::Path,
+};
+
+/// Version of the bundle format written by this version of ofnil
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bundle {
+    pub version: u32,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    /// Ids of the exported resources
+    pub roots: Vec<ResourceId>,
+    /// The exported resources and their dependencies, each one after the resources it references
+    pub resources: Vec<Resource>,
+    /// Schema version of `resources`, `0` for bundles written before versioning
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+impl Bundle {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Loads a bundle file, failing if it is written by a newer version of ofnil. Resources of
+    /// older schema versions are upgraded to the current format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let mut value = serde_json::from_str::<Value>(&std::fs::read_to_string(path)?)?;
+        let version = value["version"].as_u64().unwrap_or(0) as u32;
+        if version > BUNDLE_VERSION {
+            return Err(Box::new(RegistryError::UnsupportedBundleVersion {
+                version,
+                supported: BUNDLE_VERSION,
+            }));
+        }
+        let schema_version = value["schema_version"].as_u64().unwrap_or(0) as u32;
+        if let Some(Value::Array(resources)) = value.get_mut("resources") {
+            for resource in resources {
+                // each resource is tagged by its kind, e.g. `{"Entity": {..}}`
+                if let Some((kind, value)) =
+                    resource.as_object_mut().and_then(|r| r.iter_mut().next())
+                {
+                    let kind = kind
+                        .parse::<ResourceKind>()
+                        .map_err(|_| format!("Unknown resource kind {kind}"))?;
+                    upgrade_resource(kind, value, schema_version)?;
+                }
+            }
+        }
+        value["schema_version"] = Value::from(SCHEMA_VERSION);
+        // round trip through a string, as `serde_json::from_value` rejects empty tuple variants
+        // like `Variant::Default()`
+        Ok(serde_json::from_str::<Bundle>(&serde_json::to_string(
+            &value,
+        )?)?)
+    }
+
+    fn check_version(&self) -> Result<(), RegistryError> {
+        if self.version > BUNDLE_VERSION {
+            return Err(RegistryError::UnsupportedBundleVersion {
+                version: self.version,
+                supported: BUNDLE_VERSION,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A bundle resource that differs from the registered resource of the same id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportConflict {
+    pub id: ResourceId,
+    /// Changes from the registered resource to the bundle resource
+    pub changes: Vec<ValueChange>,
+}
+
+/// Outcome of importing a bundle, or the plan of an import when checked with
+/// [FeatureRegistry::plan_import]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Resources that are not registered yet
+    pub created: Vec<ResourceId>,
+    /// Resources that are registered with the same definition
+    pub unchanged: Vec<ResourceId>,
+    /// Resources that are registered with a different definition
+    pub conflicts: Vec<ImportConflict>,
+}
+
+impl FeatureRegistry {
+    /// Exports resources `ids` and all resources that they reference transitively.
+    pub async fn export_bundle(&self, ids: &[ResourceId]) -> Result<Bundle, Box<dyn Error>> {
+        let mut resources = BTreeMap::new();
+        let mut queue: VecDeque<_> = ids.iter().cloned().collect();
+        while let Some(id) = queue.pop_front() {
+            if resources.contains_key(&id) {
+                continue;
+            }
+            let resource = self.get_resource(&id).await?;
+            for reference in resource.references() {
+                if self.storage.get(&reference).await.is_err() {
+                    return Err(Box::new(RegistryError::MissingReference { id, reference }));
+                }
+                queue.push_back(reference);
+            }
+            resources.insert(id, resource);
+        }
+        info!("Exporting {ids:?} with {} resources", resources.len());
+        Ok(Bundle {
+            version: BUNDLE_VERSION,
+            created_at: Utc::now(),
+            roots: ids.to_vec(),
+            resources: dependency_order(resources),
+            schema_version: SCHEMA_VERSION,
+        })
+    }
+
+    /// Checks a bundle against the registry without writing anything. Fails if a bundle resource
+    /// references a resource that is neither in the bundle nor registered.
+    pub async fn plan_import(&self, bundle: &Bundle) -> Result<ImportReport, Box<dyn Error>> {
+        bundle.check_version()?;
+        let bundled: HashSet<_> = bundle.resources.iter().map(Resource::resource_id).collect();
+        let mut report = ImportReport::default();
+        for resource in &bundle.resources {
+            let id = resource.resource_id();
+            for reference in resource.references() {
+                if !bundled.contains(&reference) && self.storage.get(&reference).await.is_err() {
+                    return Err(Box::new(RegistryError::MissingReference { id, reference }));
+                }
+            }
+            match self.storage.get(&id).await {
+                Ok(registered) => {
+                    let registered = serde_json::from_str::<Value>(&registered)?;
+                    let imported = serde_json::from_str::<Value>(&resource.to_json()?)?;
+                    let mut changes = Vec::new();
+                    diff_values(String::new(), &registered, &imported, &mut changes);
+                    if changes.is_empty() {
+                        report.unchanged.push(id);
+                    } else {
+                        report.conflicts.push(ImportConflict { id, changes });
+                    }
+                }
+                Err(_) => report.created.push(id),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Imports a bundle atomically. Fails with [RegistryError::ImportConflict] without writing
+    /// anything if some bundle resources differ from the registered ones, unless `overwrite` is
+    /// set.
+    pub async fn import_bundle(
+        &self,
+        bundle: &Bundle,
+        overwrite: bool,
+    ) -> Result<ImportReport, Box<dyn Error>> {
+        let report = self.plan_import(bundle).await?;
+        if !report.conflicts.is_empty() && !overwrite {
+            return Err(Box::new(RegistryError::ImportConflict {
+                ids: report.conflicts.iter().map(|c| c.id.clone()).collect(),
+            }));
+        }
+        let mut kvs = Vec::new();
+        let mut audited = Vec::with_capacity(bundle.resources.len());
+        for resource in &bundle.resources {
+            let id = resource.resource_id();
+            let value = resource.to_json()?;
+            let mut written = None;
+            if let Some((key, revision)) = self.next_revision(&id, &value, None).await? {
+                written = Some(revision.revision);
+                kvs.push((key, serde_json::to_string(&revision)?));
+            }
+            audited.push((id.clone(), written));
+            kvs.push((id, value));
+        }
+        let mut conditions = Vec::new();
+        self.append_audit(AuditAction::Import, &audited, &mut kvs, &mut conditions)?;
+        info!(
+            "Importing bundle of {:?}: {} created, {} overwritten",
+            bundle.roots,
+            report.created.len(),
+            report.conflicts.len()
+        );
+        self.storage.put_all_if(&conditions, &kvs).await?;
+        Ok(report)
+    }
+}
+
+/// Orders resources so that each resource comes after the resources it references
+pub(super) fn dependency_order(mut resources: BTreeMap<ResourceId, Resource>) -> Vec<Resource> {
+    let mut ordered = Vec::with_capacity(resources.len());
+    while !resources.is_empty() {
+        let mut ready: Vec<_> = resources
+            .iter()
+            .filter(|(_, resource)| {
+                resource
+                    .references()
+                    .iter()
+                    .all(|reference| !resources.contains_key(reference))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        if ready.is_empty() {
+            // reference cycle, keep the rest in id order
+            ready = resources.keys().cloned().collect();
+        }
+        for id in ready {
+            ordered.extend(resources.remove(&id));
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, feature::ResourceOp, fields, FeatureValueType, TableFeatureView, Variant};
+
+    #[tokio::test]
+    async fn test_bundle() -> Result<(), Box<dyn Error>> {
+        let dev = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        dev.register_resource(&reviewer).await?;
+        let mut fields = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        dev.register_resources(&fields.iter().collect()).await?;
+        let view = TableFeatureView::default("reviewer_view", reviewer.resource_id(), &fields);
+        dev.register_resource(&view).await?;
+
+        let bundle = dev.export_bundle(&[view.resource_id()]).await?;
+        let ids: Vec<_> = bundle.resources.iter().map(Resource::resource_id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                reviewer.resource_id(),
+                fields[0].resource_id(),
+                view.resource_id()
+            ]
+        );
+        let path = std::env::temp_dir().join(format!("ofnil_bundle_{}.json", std::process::id()));
+        bundle.save(&path)?;
+        let bundle = Bundle::load(&path)?;
+        std::fs::remove_file(&path)?;
+
+        let prod = FeatureRegistry::in_memory();
+        let report = prod.import_bundle(&bundle, false).await?;
+        assert_eq!(report.created, ids);
+        assert_eq!(
+            prod.get_table_feature_view(&view.resource_id())
+                .await?
+                .field_ids,
+            view.field_ids
+        );
+
+        fields[0].description = Some("Age of the reviewer".to_string());
+        prod.register_resource(&fields[0]).await?;
+        let report = prod.plan_import(&bundle).await?;
+        assert_eq!(report.unchanged.len(), 2);
+        assert_eq!(report.conflicts[0].id, fields[0].resource_id());
+        assert_eq!(report.conflicts[0].changes[0].path, "/description");
+        assert!(prod.import_bundle(&bundle, false).await.is_err());
+        prod.import_bundle(&bundle, true).await?;
+        assert!(prod
+            .get_field(&fields[0].resource_id())
+            .await?
+            .description
+            .is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+//! Dependency-aware deletion and deprecation of registered resources.
+//!
+//! A resource depends on the resources it references (see [crate::feature::ResourceOp::references]), and a
+//! `Transformation` is also used by the resources it exports. A resource cannot be deleted while
+//! it is used by other resources unless the deletion cascades to them.
+
+use super::{AuditAction, FeatureRegistry, RegistryError};
+use crate::feature::{Resource, ResourceId};
+
+use log::info;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    error::Error,
+};
+
+impl FeatureRegistry {
+    /// Gets a resource of any kind by its id.
+    pub async fn get_resource(&self, id: &ResourceId) -> Result<Resource, Box<dyn Error>> {
+        let value = self.storage.get(id).await?;
+        Resource::from_json(id, &value)
+    }
+
+    /// Gets all registered resources, excluding internal records such as revisions.
+    pub async fn get_all_resources(&self) -> Result<Vec<Resource>, Box<dyn Error>> {
+        let mut resources = Vec::new();
+        for (key, value) in self.storage.get_by_prefix("").await? {
+            if !key.starts_with("__") {
+                resources.push(Resource::from_json(&key, &value)?);
+            }
+        }
+        Ok(resources)
+    }
+
+    /// Gets the ids of the resources that use resource `id`.
+    pub async fn get_dependents(&self, id: &ResourceId) -> Result<Vec<ResourceId>, Box<dyn Error>> {
+        let resources = self.get_all_resources().await?;
+        let existing: HashSet<_> = resources.iter().map(Resource::resource_id).collect();
+        let mut dependents = Vec::new();
+        for resource in &resources {
+            if resource.references().contains(id) {
+                dependents.push(resource.resource_id());
+            }
+        }
+        if let Resource::Transformation(transformation) = self.get_resource(id).await? {
+            for (_, export_id) in transformation.export_resources {
+                if existing.contains(&export_id) && !dependents.contains(&export_id) {
+                    dependents.push(export_id);
+                }
+            }
+        }
+        Ok(dependents)
+    }
+
+    /// Deletes resource `id` and its revision history, and returns the ids of the deleted
+    /// resources. The resources are deleted in one atomic write with their audit records.
+    ///
+    /// Fails with [RegistryError::ResourceInUse] if the resource is used by other resources, unless
+    /// `cascade` is set, in which case the resources that use it are deleted with it, and
+    /// returned before it.
+    pub async fn delete_resource(
+        &self,
+        id: &ResourceId,
+        cascade: bool,
+    ) -> Result<Vec<ResourceId>, Box<dyn Error>> {
+        let dependents = self.get_dependents(id).await?;
+        if !dependents.is_empty() && !cascade {
+            return Err(Box::new(RegistryError::ResourceInUse {
+                id: id.clone(),
+                dependents,
+            }));
+        }
+
+        // collect all transitive dependents and the resources that use each of them
+        let mut users = HashMap::from([(id.clone(), dependents)]);
+        let mut queue: VecDeque<_> = users[id].iter().cloned().collect();
+        while let Some(dependent) = queue.pop_front() {
+            if let Entry::Vacant(entry) = users.entry(dependent) {
+                let dependents = self.get_dependents(entry.key()).await?;
+                queue.extend(dependents.iter().cloned());
+                entry.insert(dependents);
+            }
+        }
+
+        // order each resource after all resources that use it
+        let mut deleted = Vec::new();
+        while !users.is_empty() {
+            let mut unused: Vec<_> = users
+                .iter()
+                .filter(|(_, dependents)| dependents.iter().all(|d| !users.contains_key(d)))
+                .map(|(id, _)| id.clone())
+                .collect();
+            if unused.is_empty() {
+                // dependency cycle, delete the rest in any order
+                unused = users.keys().cloned().collect();
+            }
+            unused.sort();
+            for id in unused {
+                users.remove(&id);
+                deleted.push(id);
+            }
+        }
+
+        // the resources, their revisions and the audit records are written at once
+        let mut deletes = Vec::new();
+        for id in &deleted {
+            info!("Deleting resource {id}");
+            deletes.push(id.clone());
+            deletes.extend(self.revision_keys(id).await?);
+        }
+        let audited: Vec<_> = deleted.iter().map(|id| (id.clone(), None)).collect();
+        let (mut kvs, mut conditions) = (Vec::new(), Vec::new());
+        self.append_audit(AuditAction::Delete, &audited, &mut kvs, &mut conditions)?;
+        self.storage
+            .write_all_if(&conditions, &kvs, &deletes)
+            .await?;
+        Ok(deleted)
+    }
+
+    /// Marks resource `id` as deprecated, and returns the ids of the resources still using it.
+    ///
+    /// Deprecated resources stay readable, but cannot be used by new `GraphDataset`s.
+    pub async fn deprecate_resource(
+        &self,
+        id: &ResourceId,
+    ) -> Result<Vec<ResourceId>, Box<dyn Error>> {
+        self.set_deprecated(id, true).await?;
+        self.get_dependents(id).await
+    }
+
+    pub async fn undeprecate_resource(&self, id: &ResourceId) -> Result<(), Box<dyn Error>> {
+        self.set_deprecated(id, false).await
+    }
+
+    async fn set_deprecated(
+        &self,
+        id: &ResourceId,
+        deprecated: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut resource = self.get_resource(id).await?;
+        if resource.is_deprecated() == deprecated {
+            return Ok(());
+        }
+        resource.set_deprecated(deprecated);
+        let value = resource.to_json()?;
+        info!("Setting deprecated of resource {id} to {deprecated}");
+        let revision = self.record_revision(id, &value, None).await?;
+        self.storage.put(id, &value).await?;
+        let action = if deprecated {
+            AuditAction::Deprecate
+        } else {
+            AuditAction::Undeprecate
+        };
+        self.audit(action, &[(id.clone(), revision)]).await
+    }
+
+    /// Checks that a new `GraphDataset` does not use deprecated resources, directly or
+    /// transitively.
+    pub(super) async fn check_not_deprecated(
+        &self,
+        dataset: &Resource,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut queue: VecDeque<_> = dataset.references().into_iter().collect();
+        let mut visited = HashSet::new();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            // dangling references are left to the caller to handle
+            let Ok(resource) = self.get_resource(&id).await else {
+                continue;
+            };
+            if resource.is_deprecated() {
+                return Err(Box::new(RegistryError::DeprecatedReference {
+                    id: dataset.resource_id(),
+                    reference: id,
+                }));
+            }
+            queue.extend(resource.references());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::ResourceOp;
+    use crate::{entity, fields, AuditQuery, Field, GraphDataset, TableFeatureView, Variant};
+
+    async fn register_reviewer(
+        registry: &FeatureRegistry,
+    ) -> Result<(Vec<Field>, TableFeatureView), Box<dyn Error>> {
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        registry.register_resource(&reviewer).await?;
+        let fields = fields!(
+            vec![("name", crate::FeatureValueType::String)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        registry
+            .register_resources(&fields.iter().collect())
+            .await?;
+        let view = TableFeatureView::default("reviewer_view", reviewer.resource_id(), &fields);
+        registry.register_resource(&view).await?;
+        Ok((fields, view))
+    }
+
+    #[tokio::test]
+    async fn test_delete_resource() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let (fields, view) = register_reviewer(&registry).await?;
+        let field_id = fields[0].resource_id();
+
+        assert_eq!(
+            registry.get_dependents(&field_id).await?,
+            vec![view.resource_id()]
+        );
+        let err = registry
+            .delete_resource(&field_id, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::ResourceInUse { .. })
+        ));
+
+        let deleted = registry
+            .delete_resource(&"default/Entity/Reviewer".to_string(), true)
+            .await?;
+        assert_eq!(
+            deleted,
+            vec![
+                view.resource_id(),
+                field_id.clone(),
+                "default/Entity/Reviewer".to_string()
+            ]
+        );
+        assert!(registry.get_all_resources().await?.is_empty());
+        assert!(registry.list_revisions(&field_id).await?.is_empty());
+        let query = AuditQuery {
+            actions: vec![AuditAction::Delete],
+            ..Default::default()
+        };
+        assert_eq!(registry.query_audit_log(&query).await?.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deprecate_resource() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let (fields, view) = register_reviewer(&registry).await?;
+        let field_id = fields[0].resource_id();
+
+        let users = registry.deprecate_resource(&field_id).await?;
+        assert_eq!(users, vec![view.resource_id()]);
+        assert!(registry.get_field(&field_id).await?.deprecated);
+        assert!(registry.get_all_fields(None).await?[0].deprecated);
+
+        let dataset = GraphDataset {
+            name: "dataset".to_string(),
+            table_feature_views: vec![view],
+            ..Default::default()
+        };
+        let err = registry.register_resource(&dataset).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::DeprecatedReference { reference, .. }) if reference == &field_id
+        ));
+
+        registry.undeprecate_resource(&field_id).await?;
+        registry.register_resource(&dataset).await?;
+        // existing datasets can still be updated after deprecation
+        registry.deprecate_resource(&field_id).await?;
+        registry.register_resource(&dataset).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! Garbage collection of registered resources that nothing uses anymore.
+//!
+//! Each call of `finalize_transformation` registers a new timestamped `Transformation` together
+//! with the fields and topologies it exports. Once no feature view or transformation uses these
+//! fields and topologies, they and their transformation are garbage. Fields and topologies that
+//! are not computed by a transformation describe source data and are never collected.
+
+use super::{
+    bundle::dependency_order,
+    storage::{LINK_PREFIX, PROJECT_PREFIX},
+    FeatureRegistry,
+};
+use crate::feature::{Resource, ResourceId, ResourceKind};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+};
+
+/// Unused resources found by [FeatureRegistry::collect_garbage]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GarbageReport {
+    pub transformations: Vec<ResourceId>,
+    pub fields: Vec<ResourceId>,
+    pub topologies: Vec<ResourceId>,
+    /// Whether the resources are only reported and not deleted
+    pub dry_run: bool,
+}
+
+impl GarbageReport {
+    pub fn is_empty(&self) -> bool {
+        self.transformations.is_empty() && self.fields.is_empty() && self.topologies.is_empty()
+    }
+}
+
+/// Whether `resource` is collected once it is unused
+fn is_collectable(resource: &Resource) -> bool {
+    match resource {
+        Resource::Transformation(_) => true,
+        Resource::Field(field) => field.transformation_id.is_some(),
+        Resource::Topology(topology) => topology.transformation_id.is_some(),
+        _ => false,
+    }
+}
+
+impl FeatureRegistry {
+    /// Finds the transformations, and the fields and topologies computed by transformations,
+    /// that are not used by other resources, and deletes them together with their revision
+    /// history unless `dry_run` is set. A transformation is unused once all the fields and
+    /// topologies it exports are unused. Resources referenced from other projects are kept.
+    pub async fn collect_garbage(&self, dry_run: bool) -> Result<GarbageReport, Box<dyn Error>> {
+        let resources = self.get_all_resources().await?;
+        let linked = self.linked_from_other_projects().await?;
+        let mut garbage = HashSet::new();
+        loop {
+            let mut found = false;
+            for resource in resources.iter().filter(|r| is_collectable(r)) {
+                let id = resource.resource_id();
+                if garbage.contains(&id) || linked.contains(&id) {
+                    continue;
+                }
+                let used = resources.iter().any(|user| {
+                    let user_id = user.resource_id();
+                    user_id != id && !garbage.contains(&user_id) && user.references().contains(&id)
+                });
+                if !used {
+                    garbage.insert(id);
+                    found = true;
+                }
+            }
+            if !found {
+                break;
+            }
+        }
+
+        let collected: BTreeMap<_, _> = resources
+            .into_iter()
+            .filter(|resource| garbage.contains(&resource.resource_id()))
+            .map(|resource| (resource.resource_id(), resource))
+            .collect();
+        let mut report = GarbageReport {
+            dry_run,
+            ..Default::default()
+        };
+        for (id, resource) in &collected {
+            match resource.kind() {
+                ResourceKind::Transformation => report.transformations.push(id.clone()),
+                ResourceKind::Field => report.fields.push(id.clone()),
+                _ => report.topologies.push(id.clone()),
+            }
+        }
+        info!(
+            "Found {} unused transformations, {} unused fields and {} unused topologies",
+            report.transformations.len(),
+            report.fields.len(),
+            report.topologies.len()
+        );
+        if !dry_run {
+            // delete each resource before the resources it references
+            for resource in dependency_order(collected).iter().rev() {
+                self.delete_resource(&resource.resource_id(), false).await?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Gets the ids of the resources of this project that other projects reference
+    async fn linked_from_other_projects(&self) -> Result<HashSet<ResourceId>, Box<dyn Error>> {
+        let mut linked = HashSet::new();
+        for (key, project) in self.root.get_by_prefix(PROJECT_PREFIX).await? {
+            if project != self.project {
+                continue;
+            }
+            if let Some((_, id)) = key.split_once(&format!("/{LINK_PREFIX}")) {
+                linked.insert(id.to_string());
+            }
+        }
+        Ok(linked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity, feature::ResourceOp, fields, FeatureValueType, TableFeatureView, Transformation,
+        Variant,
+    };
+
+    #[tokio::test]
+    async fn test_collect_garbage() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let source = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        registry.register_resource(&reviewer).await?;
+        registry
+            .register_resources(&source.iter().collect())
+            .await?;
+
+        // two runs of a transformation, of which only the latest is used by a view
+        let mut registered = Vec::new();
+        for run in ["TRANSFORMATION_1", "TRANSFORMATION_2"] {
+            let transformation = Transformation {
+                name: run.to_string(),
+                source_field_ids: vec![source[0].resource_id()],
+                ..Default::default()
+            };
+            let mut derived = fields!(
+                vec![(&format!("{run}_rank"), FeatureValueType::Float)],
+                &reviewer,
+                Variant::Default(),
+                None,
+            );
+            derived[0].transformation_id = Some(transformation.resource_id());
+            registry
+                .register_all(&[
+                    Resource::Transformation(transformation.clone()),
+                    Resource::Field(derived[0].clone()),
+                ])
+                .await?;
+            registered.push((transformation, derived));
+        }
+        let (latest, latest_fields) = &registered[1];
+        let view = TableFeatureView::default("rank_view", reviewer.resource_id(), latest_fields);
+        registry.register_resource(&view).await?;
+
+        let (stale, stale_fields) = &registered[0];
+        let expected = GarbageReport {
+            transformations: vec![stale.resource_id()],
+            fields: vec![stale_fields[0].resource_id()],
+            topologies: vec![],
+            dry_run: true,
+        };
+        assert_eq!(registry.collect_garbage(true).await?, expected);
+        assert!(registry.get_resource(&stale.resource_id()).await.is_ok());
+
+        let report = registry.collect_garbage(false).await?;
+        assert_eq!(report.fields, expected.fields);
+        assert!(registry.get_resource(&stale.resource_id()).await.is_err());
+        assert!(registry.get_resource(&latest.resource_id()).await.is_ok());
+        assert!(registry.get_field(&source[0].resource_id()).await.is_ok());
+        assert!(registry.collect_garbage(false).await?.is_empty());
+        Ok(())
+    }
+}
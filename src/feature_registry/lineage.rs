@@ -0,0 +1,264 @@
+//! Lineage of registered resources.
+//!
+//! Resources form a DAG through the resources they reference, e.g. fields are produced by
+//! transformations from source fields, and are used by feature views, which in turn are used by
+//! graph datasets. An edge `from -> to` means that data flows from `from` to `to`, so the
+//! upstream of a resource is where its data comes from and the downstream is what uses it.
+
+use super::FeatureRegistry;
+use crate::{
+    feature::{Resource, ResourceId, ResourceKind},
+    InfraIdentifier,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    error::Error,
+};
+use strum::{Display, EnumString};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum LineageDirection {
+    /// Resources that the resource is derived from
+    Upstream,
+    /// Resources that are derived from the resource
+    Downstream,
+    Both,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LineageNode {
+    pub id: ResourceId,
+    pub kind: ResourceKind,
+    /// The infra where the resource is stored, e.g. the Neo4j instance of source fields
+    pub infra_id: Option<InfraIdentifier>,
+    pub deprecated: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineageEdge {
+    pub from: ResourceId,
+    pub to: ResourceId,
+}
+
+/// Lineage DAG of the resource `root`, in a form that can be served to the dashboard
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LineageGraph {
+    pub root: ResourceId,
+    /// Sorted by id
+    pub nodes: Vec<LineageNode>,
+    /// Sorted by `from` and then `to`
+    pub edges: Vec<LineageEdge>,
+}
+
+impl LineageGraph {
+    pub fn node(&self, id: &str) -> Option<&LineageNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+impl FeatureRegistry {
+    /// Gets the lineage DAG of resource `id` in the given direction. References to resources
+    /// that are not registered are ignored.
+    pub async fn get_lineage(
+        &self,
+        id: &ResourceId,
+        direction: LineageDirection,
+    ) -> Result<LineageGraph, Box<dyn Error>> {
+        // make sure that the root exists
+        self.get_resource(id).await?;
+
+        let resources: HashMap<_, _> = self
+            .get_all_resources()
+            .await?
+            .into_iter()
+            .map(|resource| (resource.resource_id(), resource))
+            .collect();
+        let mut upstream: HashMap<ResourceId, BTreeSet<ResourceId>> = HashMap::new();
+        let mut downstream: HashMap<ResourceId, BTreeSet<ResourceId>> = HashMap::new();
+        for (resource_id, resource) in &resources {
+            if let Resource::Transformation(transformation) = resource {
+                for (_, export_id) in &transformation.export_resources {
+                    downstream
+                        .entry(resource_id.clone())
+                        .or_default()
+                        .insert(export_id.clone());
+                    upstream
+                        .entry(export_id.clone())
+                        .or_default()
+                        .insert(resource_id.clone());
+                }
+            }
+            for source in resource.references() {
+                downstream
+                    .entry(source.clone())
+                    .or_default()
+                    .insert(resource_id.clone());
+                upstream
+                    .entry(resource_id.clone())
+                    .or_default()
+                    .insert(source);
+            }
+        }
+
+        let mut visited = HashSet::from([id.clone()]);
+        let mut edges = BTreeSet::new();
+        if direction != LineageDirection::Downstream {
+            walk(
+                id,
+                &upstream,
+                &resources,
+                &mut visited,
+                &mut |next, current| {
+                    edges.insert(LineageEdge {
+                        from: next.clone(),
+                        to: current.clone(),
+                    });
+                },
+            );
+        }
+        if direction != LineageDirection::Upstream {
+            walk(
+                id,
+                &downstream,
+                &resources,
+                &mut visited,
+                &mut |next, current| {
+                    edges.insert(LineageEdge {
+                        from: current.clone(),
+                        to: next.clone(),
+                    });
+                },
+            );
+        }
+
+        let mut nodes: Vec<_> = visited
+            .into_iter()
+            .map(|id| {
+                let resource = &resources[&id];
+                LineageNode {
+                    kind: resource.kind(),
+                    infra_id: resource.sink_infra_id(),
+                    deprecated: resource.is_deprecated(),
+                    id,
+                }
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(LineageGraph {
+            root: id.clone(),
+            nodes,
+            edges: edges.into_iter().collect(),
+        })
+    }
+}
+
+/// Walks the adjacency list `adjacent` breadth-first from `root` through the registered
+/// `resources`, calling `on_edge(next, current)` for each traversed edge.
+fn walk(
+    root: &ResourceId,
+    adjacent: &HashMap<ResourceId, BTreeSet<ResourceId>>,
+    resources: &HashMap<ResourceId, Resource>,
+    visited: &mut HashSet<ResourceId>,
+    on_edge: &mut impl FnMut(&ResourceId, &ResourceId),
+) {
+    let mut queue = VecDeque::from([root.clone()]);
+    let mut walked = HashSet::from([root.clone()]);
+    while let Some(current) = queue.pop_front() {
+        for next in adjacent.get(&current).into_iter().flatten() {
+            if !resources.contains_key(next) {
+                continue;
+            }
+            on_edge(next, &current);
+            visited.insert(next.clone());
+            if walked.insert(next.clone()) {
+                queue.push_back(next.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity,
+        feature::{ResourceOp, Transformation},
+        fields, FeatureValueType, GraphDataset, TableFeatureView, Variant,
+    };
+
+    #[tokio::test]
+    async fn test_lineage() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let neo4j = InfraIdentifier::Neo4j("neo4j".to_string());
+        let redis = InfraIdentifier::Redis("redis".to_string());
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        registry.register_resource(&reviewer).await?;
+        let sources = fields!(
+            vec![("name", FeatureValueType::String)],
+            &reviewer,
+            Variant::Default(),
+            Some(neo4j.clone()),
+        );
+        registry
+            .register_resources(&sources.iter().collect())
+            .await?;
+
+        let mut transformation = Transformation {
+            name: "name_length".to_string(),
+            source_field_ids: vec![sources[0].resource_id()],
+            ..Transformation::default()
+        };
+        let mut exports = fields!(
+            vec![("name_length", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            Some(redis),
+        );
+        exports[0].transformation_id = Some(transformation.resource_id());
+        transformation.export_resources = vec![(0, exports[0].resource_id())];
+        registry.register_resource(&transformation).await?;
+        registry
+            .register_resources(&exports.iter().collect())
+            .await?;
+
+        let view = TableFeatureView::default("reviewer_view", reviewer.resource_id(), &exports);
+        let dataset = GraphDataset {
+            name: "dataset".to_string(),
+            table_feature_views: vec![view.clone()],
+            ..Default::default()
+        };
+        registry.register_resource(&view).await?;
+        registry.register_resource(&dataset).await?;
+
+        let upstream = registry
+            .get_lineage(&exports[0].resource_id(), LineageDirection::Upstream)
+            .await?;
+        let source = upstream.node(&sources[0].resource_id()).unwrap();
+        assert_eq!(source.infra_id, Some(neo4j));
+        assert!(upstream.node(&view.resource_id()).is_none());
+        assert!(upstream.edges.contains(&LineageEdge {
+            from: transformation.resource_id(),
+            to: exports[0].resource_id(),
+        }));
+
+        let downstream = registry
+            .get_lineage(&sources[0].resource_id(), LineageDirection::Downstream)
+            .await?;
+        let ids: Vec<_> = downstream.nodes.iter().map(|node| &node.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                &sources[0].resource_id(),
+                &exports[0].resource_id(),
+                &dataset.resource_id(),
+                &view.resource_id(),
+                &transformation.resource_id(),
+            ]
+        );
+        assert_eq!(downstream.edges.len(), 4);
+        Ok(())
+    }
+}
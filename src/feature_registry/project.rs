@@ -0,0 +1,351 @@
+//! Projects of a shared registry storage.
+//!
+//! The keys of each project are stored under `__projects__/{project}/`, so that teams sharing one
+//! etcd cluster do not overwrite each other's `default/Entity/Reviewer`. Resources can be copied
+//! between projects, or referenced from another project without being copied.
+
+use super::{
+    project_storage,
+    revision::{revision_prefix, REVISION_PREFIX},
+    storage::{ProjectStorage, LINK_PREFIX, PROJECT_PREFIX},
+    AuditAction, FeatureRegistry, ImportReport, RegistryError,
+};
+use crate::feature::{Resource, ResourceId};
+
+use log::info;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+};
+
+/// Number of keys that are not revision records of a resource moved in one write by
+/// [FeatureRegistry::migrate_to_project]
+pub(crate) const MIGRATION_BATCH_SIZE: usize = 32;
+
+impl FeatureRegistry {
+    /// Checks that `project` can scope the keys of a project, i.e. is neither empty nor contains
+    /// `/`, which separates the project from the keys.
+    pub fn validate_project_name(project: &str) -> Result<(), RegistryError> {
+        if project.is_empty() || project.contains('/') {
+            return Err(RegistryError::InvalidProjectName {
+                project: project.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Gets the registry of `project` on the same storage, with the same author.
+    pub fn in_project(&self, project: impl Into<String>) -> FeatureRegistry {
+        let project = project.into();
+        FeatureRegistry {
+            root: self.root.clone(),
+            storage: project_storage(&self.root, &project),
+            project,
+            author: self.author.clone(),
+            client: self.client.clone(),
+        }
+    }
+
+    /// Lists the projects that have registered resources in the storage.
+    pub async fn list_projects(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let projects: BTreeSet<_> = self
+            .root
+            .get_by_prefix(PROJECT_PREFIX)
+            .await?
+            .into_iter()
+            .filter_map(|(key, _)| {
+                let (project, _) = key[PROJECT_PREFIX.len()..].split_once('/')?;
+                Some(project.to_string())
+            })
+            .collect();
+        Ok(projects.into_iter().collect())
+    }
+
+    /// Copies resources `ids` of `project`, together with all resources they reference, into this
+    /// project. Fails on resources registered here with different definitions unless
+    /// `overwrite` is set.
+    pub async fn copy_from_project(
+        &self,
+        project: &str,
+        ids: &[ResourceId],
+        overwrite: bool,
+    ) -> Result<ImportReport, Box<dyn Error>> {
+        Self::validate_project_name(project)?;
+        let bundle = self.in_project(project).export_bundle(ids).await?;
+        info!(
+            "Copying {ids:?} from project {project} to project {}",
+            self.project
+        );
+        self.import_bundle(&bundle, overwrite).await
+    }
+
+    /// References resources `ids` of `project`, together with all resources they reference, in
+    /// this project without copying them. Referenced resources are read from `project`, so they
+    /// follow its changes, and can be used by the resources of this project. A resource that is
+    /// registered in this project takes precedence over a referenced one of the same id.
+    /// Returns the ids of the newly referenced resources.
+    pub async fn reference_from_project(
+        &self,
+        project: &str,
+        ids: &[ResourceId],
+    ) -> Result<Vec<ResourceId>, Box<dyn Error>> {
+        Self::validate_project_name(project)?;
+        if project == self.project {
+            return Err(format!("Cannot reference resources of project {project} itself").into());
+        }
+        let bundle = self.in_project(project).export_bundle(ids).await?;
+        let mut kvs = Vec::new();
+        for id in bundle.resources.iter().map(Resource::resource_id) {
+            if self.storage.get(&id).await.is_err() {
+                kvs.push((format!("{LINK_PREFIX}{id}"), project.to_string()));
+            }
+        }
+        info!(
+            "Referencing {} resources of project {project} in project {}",
+            kvs.len(),
+            self.project
+        );
+        let linked: Vec<_> = kvs
+            .iter()
+            .map(|(key, _)| key[LINK_PREFIX.len()..].to_string())
+            .collect();
+        let audited: Vec<_> = linked.iter().map(|id| (id.clone(), None)).collect();
+        let mut conditions = Vec::new();
+        self.append_audit(AuditAction::Reference, &audited, &mut kvs, &mut conditions)?;
+        self.storage.put_all_if(&conditions, &kvs).await?;
+        Ok(linked)
+    }
+
+    /// Moves the keys written before registries were scoped to projects, including revision
+    /// records, into this project. Keys that already exist in this project are kept and the old
+    /// keys are dropped. Returns the number of moved keys.
+    ///
+    /// Each resource is moved together with its revision records in one atomic write, and the
+    /// other keys in small batches, so that no write exceeds the transaction size limit of etcd.
+    /// A write fails with [RegistryError::Conflict] if a moved key is registered in this project
+    /// concurrently. As the legacy keys are dropped in the same write that moves them, the
+    /// migration can be run again to move the remaining keys after a failure.
+    pub async fn migrate_to_project(&self) -> Result<usize, Box<dyn Error>> {
+        let legacy: BTreeMap<_, _> = self
+            .root
+            .get_by_prefix("")
+            .await?
+            .into_iter()
+            .filter(|(key, _)| !key.starts_with(PROJECT_PREFIX))
+            .collect();
+        let mut batches = Vec::new();
+        let mut others = Vec::new();
+        for (key, value) in &legacy {
+            if !key.starts_with("__") {
+                let prefix = revision_prefix(key);
+                let mut batch = vec![(key, value)];
+                batch.extend(
+                    legacy
+                        .range(prefix.clone()..)
+                        .take_while(|(key, _)| key.starts_with(&prefix)),
+                );
+                batches.push(batch);
+                continue;
+            }
+            // revision records of legacy resources are moved with their resources
+            let resource = key
+                .strip_prefix(REVISION_PREFIX)
+                .and_then(|key| key.rsplit_once('/'))
+                .map(|(id, _)| id);
+            if !resource.is_some_and(|id| legacy.contains_key(id)) {
+                others.push((key, value));
+            }
+        }
+        batches.extend(
+            others
+                .chunks(MIGRATION_BATCH_SIZE)
+                .map(|batch| batch.to_vec()),
+        );
+
+        let project = ProjectStorage::new(self.root.clone(), &self.project);
+        let mut moved = 0;
+        for batch in batches {
+            let mut kvs = Vec::new();
+            let mut conditions = Vec::new();
+            for (key, value) in &batch {
+                // only keys of this project are kept, not resources linked from other projects
+                if self.root.get(&project.key(key)).await.is_err() {
+                    kvs.push(((*key).clone(), (*value).clone()));
+                    conditions.push(((*key).clone(), 0));
+                }
+            }
+            moved += kvs.len();
+            let audited: Vec<_> = kvs
+                .iter()
+                .filter(|(key, _)| !key.starts_with("__"))
+                .map(|(key, _)| (key.clone(), None))
+                .collect();
+            self.append_audit(AuditAction::Migrate, &audited, &mut kvs, &mut conditions)?;
+            // moved as they are, resources of older schema versions stay upgradable, and the
+            // legacy keys are outside of the project so they are deleted from the root storage
+            let conditions: Vec<_> = conditions
+                .into_iter()
+                .map(|(key, revision)| (project.key(&key), revision))
+                .collect();
+            let kvs: Vec<_> = kvs
+                .into_iter()
+                .map(|(key, value)| (project.key(&key), value))
+                .collect();
+            let deletes: Vec<_> = batch.iter().map(|(key, _)| (*key).clone()).collect();
+            self.root.write_all_if(&conditions, &kvs, &deletes).await?;
+        }
+        info!(
+            "Moved {} keys into project {}, dropped {} existing keys",
+            moved,
+            self.project,
+            legacy.len() - moved
+        );
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity, feature::ResourceOp, fields, FeatureValueType, MemoryStorage, RegistryStorage,
+        TableFeatureView, Variant,
+    };
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_projects() -> Result<(), Box<dyn Error>> {
+        let team_a = FeatureRegistry::in_memory().with_project("team_a");
+        let team_b = team_a.in_project("team_b");
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        team_a
+            .register_resources(&vec![&reviewer, &product])
+            .await?;
+        team_b.register_resource(&reviewer).await?;
+        assert_eq!(team_a.get_all_entities(None).await?.len(), 2);
+        assert_eq!(team_b.get_all_entities(None).await?.len(), 1);
+        assert_eq!(team_a.list_projects().await?, vec!["team_a", "team_b"]);
+
+        let fields = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        team_a.register_resources(&fields.iter().collect()).await?;
+        let view = TableFeatureView::default("reviewer_view", reviewer.resource_id(), &fields);
+        team_a.register_resource(&view).await?;
+
+        // referenced resources follow their project and can be used by local resources
+        let team_c = team_a.in_project("team_c");
+        let linked = team_c
+            .reference_from_project("team_a", &[fields[0].resource_id()])
+            .await?;
+        assert_eq!(
+            linked,
+            vec![reviewer.resource_id(), fields[0].resource_id()]
+        );
+        team_c.register_resource(&view).await?;
+        assert_eq!(team_c.get_all_fields(None).await?.len(), 1);
+        let mut changes = ProjectStorage::new(team_c.root.clone(), "team_c")
+            .watch("default/Field/")
+            .await?;
+        let mut described = fields[0].clone();
+        described.description = Some("Age of the reviewer".to_string());
+        team_a.register_resource(&described).await?;
+        assert_eq!(
+            changes.next().await.map(|event| event.key().to_string()),
+            Some(fields[0].resource_id())
+        );
+        assert!(team_c
+            .get_field(&fields[0].resource_id())
+            .await?
+            .description
+            .is_some());
+
+        // copies are independent of their project
+        let report = team_b
+            .copy_from_project("team_a", &[view.resource_id()], false)
+            .await?;
+        assert_eq!(report.unchanged, vec![reviewer.resource_id()]);
+        assert_eq!(report.created.len(), 2);
+        team_a
+            .delete_resource(&reviewer.resource_id(), true)
+            .await?;
+        assert!(team_b
+            .get_table_feature_view(&view.resource_id())
+            .await
+            .is_ok());
+
+        assert!(FeatureRegistry::validate_project_name("team/a").is_err());
+        assert!(team_b
+            .reference_from_project("", &[reviewer.resource_id()])
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_to_project() -> Result<(), Box<dyn Error>> {
+        let storage = MemoryStorage::new();
+        storage
+            .put(
+                "default/Entity/Reviewer",
+                &serde_json::to_string(&entity!(
+                    "Reviewer",
+                    Variant::Default(),
+                    "Reviewer",
+                    "reviewerID"
+                ))?,
+            )
+            .await?;
+        let registry = FeatureRegistry::with_storage(storage).with_project("team_a");
+        assert!(registry.get_all_entities(None).await?.is_empty());
+        assert_eq!(registry.migrate_to_project().await?, 1);
+        assert_eq!(registry.get_all_entities(None).await?.len(), 1);
+        assert!(registry.root.get("default/Entity/Reviewer").await.is_err());
+        assert_eq!(registry.migrate_to_project().await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_to_project_in_batches() -> Result<(), Box<dyn Error>> {
+        let storage = MemoryStorage::new();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let id = reviewer.resource_id();
+        let value = serde_json::to_string(&reviewer)?;
+        storage.put(&id, &value).await?;
+        storage
+            .put(&format!("{}0000000001", revision_prefix(&id)), &value)
+            .await?;
+        // revision records of deleted resources are moved in batches
+        let deleted = 2 * MIGRATION_BATCH_SIZE + 1;
+        for i in 0..deleted {
+            storage
+                .put(
+                    &format!("{}0000000001", revision_prefix(&format!("{id}{i}"))),
+                    &value,
+                )
+                .await?;
+        }
+        let registry = FeatureRegistry::with_storage(storage).with_project("team_a");
+        // a resource linked from another project does not shadow the legacy one
+        let team_b = registry.in_project("team_b");
+        team_b.register_resource(&reviewer).await?;
+        registry
+            .reference_from_project("team_b", &[reviewer.resource_id()])
+            .await?;
+
+        assert_eq!(registry.migrate_to_project().await?, deleted + 2);
+        assert!(registry.root.get(&id).await.is_err());
+        let project = ProjectStorage::new(registry.root.clone(), "team_a");
+        assert!(registry.root.get(&project.key(&id)).await.is_ok());
+        assert_eq!(
+            project.get_by_prefix(REVISION_PREFIX).await?.len(),
+            deleted + 1
+        );
+        assert_eq!(registry.migrate_to_project().await?, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,756 @@
+//! Declarative feature repository.
+//!
+//! A feature repository is a directory of TOML files, `features/` in `OFNIL_HOME` by default,
+//! that describes entities, fields, topologies, feature views and graph datasets. Like Terraform,
+//! [FeatureRegistry::plan] computes the changes needed to bring the registry in line with the
+//! repository, and [FeatureRegistry::apply] registers them. Resources that are registered but not
+//! described in the repository are left untouched.
+//!
+//! ```toml
+//! [[entity]]
+//! name = "Reviewer"
+//! primary_key = "reviewerID"
+//!
+//! [[field]]
+//! entity = "Reviewer"
+//! name = "pagerank"
+//! value_type = "Float"
+//! sink_infra = { Redis = "redis" }
+//!
+//! [[table_feature_view]]
+//! name = "reviewer_view"
+//! entity = "Reviewer"
+//! fields = ["pagerank"]
+//! ```
+//!
+//! Resources refer to each other by name within the same variant, or by full resource id, e.g.
+//! `default/Entity/Reviewer`.
+
+use super::{
+    bundle::dependency_order, revision::diff_values, AuditAction, FeatureRegistry, RegistryError,
+    ValueChange,
+};
+use crate::{
+    config::FeatureStoreConfig,
+    feature::{
+        EdgeEntity, Resource, ResourceId, ResourceKind, ResourceOp, TopologyType, VertexEntity,
+    },
+    Entity, FeatureValueType, Field, GraphDataset, InfraIdentifier, TableFeatureView, Topology,
+    TopologyFeatureView, Variant,
+};
+
+use chrono::Utc;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt::{Display, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+/// Keys of resource definitions that are managed by the registry rather than the repository.
+/// They are ignored when comparing definitions, and kept from the registered resource on apply.
+const REGISTRY_MANAGED_KEYS: [&str; 5] = [
+    "created_at",
+    "updated_at",
+    "deployed",
+    "deprecated",
+    "snapshots",
+];
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct EntitySpec {
+    name: String,
+    /// Defaults to `name`
+    tlabel: Option<String>,
+    primary_key: String,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct EdgeEntitySpec {
+    name: String,
+    /// Defaults to `name`
+    tlabel: Option<String>,
+    src_entity: String,
+    dst_entity: String,
+    #[serde(default)]
+    directed: bool,
+    primary_key: Option<String>,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct FieldSpec {
+    name: String,
+    entity: String,
+    /// e.g. `Float` or `Array(Int)`
+    value_type: String,
+    transformation: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    #[serde(default)]
+    owners: Vec<String>,
+    sink_infra: Option<InfraIdentifier>,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct TopologySpec {
+    name: String,
+    edge_entity: String,
+    src_entity: String,
+    dst_entity: String,
+    topology_type: Option<TopologyType>,
+    transformation: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    #[serde(default)]
+    owners: Vec<String>,
+    sink_infra: Option<InfraIdentifier>,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct TableFeatureViewSpec {
+    name: String,
+    entity: String,
+    /// Field names of `entity`, or field resource ids
+    fields: Vec<String>,
+    #[serde(default)]
+    online: bool,
+    description: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    owner: Option<String>,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct TopologyFeatureViewSpec {
+    name: String,
+    topologies: Vec<String>,
+    topology_type: Option<TopologyType>,
+    #[serde(default)]
+    online: bool,
+    description: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    #[serde(default)]
+    owners: Vec<String>,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct GraphDatasetSpec {
+    name: String,
+    #[serde(default)]
+    table_feature_views: Vec<String>,
+    #[serde(default)]
+    topology_feature_views: Vec<String>,
+    description: Option<String>,
+    variant: Option<String>,
+}
+
+/// Resource definitions of a feature repository, merged from all its TOML files
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FeatureRepo {
+    #[serde(default)]
+    entity: Vec<EntitySpec>,
+    #[serde(default)]
+    edge_entity: Vec<EdgeEntitySpec>,
+    #[serde(default)]
+    field: Vec<FieldSpec>,
+    #[serde(default)]
+    topology: Vec<TopologySpec>,
+    #[serde(default)]
+    table_feature_view: Vec<TableFeatureViewSpec>,
+    #[serde(default)]
+    topology_feature_view: Vec<TopologyFeatureViewSpec>,
+    #[serde(default)]
+    graph_dataset: Vec<GraphDatasetSpec>,
+}
+
+impl FromStr for FeatureRepo {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl FeatureRepo {
+    /// Loads all `*.toml` files in directory `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        let mut repo = FeatureRepo::default();
+        for path in paths {
+            let file = std::fs::read_to_string(&path)?
+                .parse::<FeatureRepo>()
+                .map_err(|e| format!("Invalid feature repository file {}: {e}", path.display()))?;
+            repo.merge(file);
+        }
+        Ok(repo)
+    }
+
+    /// Loads the feature repository configured by `repo_path` in `ofnil.toml` of the
+    /// `ofnil_home` directory.
+    pub fn from_home(ofnil_home: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let config = FeatureStoreConfig::from_dir(ofnil_home.as_ref())?;
+        FeatureRepo::load(config.repo_path)
+    }
+
+    fn merge(&mut self, other: FeatureRepo) {
+        self.entity.extend(other.entity);
+        self.edge_entity.extend(other.edge_entity);
+        self.field.extend(other.field);
+        self.topology.extend(other.topology);
+        self.table_feature_view.extend(other.table_feature_view);
+        self.topology_feature_view
+            .extend(other.topology_feature_view);
+        self.graph_dataset.extend(other.graph_dataset);
+    }
+}
+
+/// A repository resource that differs from the registered resource of the same id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlannedUpdate {
+    pub resource: Resource,
+    /// Changes from the registered resource to the repository resource
+    pub changes: Vec<ValueChange>,
+}
+
+/// Changes to bring the registry in line with a feature repository, each list ordered so that a
+/// resource comes after the resources it references
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Plan {
+    /// Resources that are not registered yet
+    pub create: Vec<Resource>,
+    /// Resources that are registered with a different definition
+    pub update: Vec<PlannedUpdate>,
+    /// Resources that are registered with the same definition
+    pub unchanged: Vec<ResourceId>,
+}
+
+impl Plan {
+    /// Whether the registry is already in line with the repository
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty()
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for resource in &self.create {
+            writeln!(f, "+ {}", resource.resource_id())?;
+        }
+        for update in &self.update {
+            writeln!(f, "~ {}", update.resource.resource_id())?;
+            for change in &update.changes {
+                writeln!(f, "    {change}")?;
+            }
+        }
+        write!(
+            f,
+            "{} to create, {} to update, {} unchanged",
+            self.create.len(),
+            self.update.len(),
+            self.unchanged.len()
+        )
+    }
+}
+
+/// Resolves `reference` to the id of a resource of `kind` in `variant`, unless it is already a
+/// resource id of `kind`
+fn resolve(reference: &str, kind: ResourceKind, variant: &Variant) -> ResourceId {
+    if ResourceKind::of(reference) == Some(kind) {
+        reference.to_string()
+    } else {
+        format!("{variant}/{kind}/{reference}")
+    }
+}
+
+fn insert(
+    resources: &mut BTreeMap<ResourceId, Resource>,
+    resource: Resource,
+) -> Result<(), Box<dyn Error>> {
+    let id = resource.resource_id();
+    if resources.insert(id.clone(), resource).is_some() {
+        return Err(format!("Resource {id} is defined more than once in the repository").into());
+    }
+    Ok(())
+}
+
+fn variant_of(variant: &Option<String>) -> Variant {
+    variant.as_deref().map(Variant::from).unwrap_or_default()
+}
+
+/// The resource definition as compared between the repository and the registry
+fn comparable(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for key in REGISTRY_MANAGED_KEYS {
+                map.remove(key);
+            }
+            map.values_mut().for_each(comparable);
+        }
+        Value::Array(values) => values.iter_mut().for_each(comparable),
+        _ => {}
+    }
+}
+
+/// Sets the registry-managed keys of a resource definition to be applied, given the registered
+/// definition if the resource is being updated
+fn stamp(value: &mut Value, registered: Option<&Value>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    let now = Value::from(Utc::now().timestamp());
+    for key in REGISTRY_MANAGED_KEYS {
+        if !map.contains_key(key) {
+            continue;
+        }
+        let kept = registered
+            .and_then(|registered| registered.get(key))
+            .cloned();
+        let stamped = match key {
+            "created_at" => kept.filter(|v| !v.is_null()).unwrap_or(now.clone()),
+            "updated_at" if registered.is_some() => now.clone(),
+            _ => match kept {
+                Some(kept) => kept,
+                None => continue,
+            },
+        };
+        map.insert(key.to_string(), stamped);
+    }
+}
+
+impl FeatureRegistry {
+    /// Computes the changes needed to register the resources of `repo`, without writing
+    /// anything. Fails if a repository resource is defined more than once, or has broken
+    /// references, e.g. to a resource that is neither in the repository nor registered.
+    pub async fn plan(&self, repo: &FeatureRepo) -> Result<Plan, Box<dyn Error>> {
+        let resources = dependency_order(self.build_repo(repo).await?);
+        self.validate_references(&resources).await?;
+        let mut plan = Plan::default();
+        for resource in resources {
+            let id = resource.resource_id();
+            match self.storage.get(&id).await {
+                Ok(registered) => {
+                    let mut registered = serde_json::from_str::<Value>(&registered)?;
+                    let mut desired = serde_json::from_str::<Value>(&resource.to_json()?)?;
+                    comparable(&mut registered);
+                    comparable(&mut desired);
+                    let mut changes = Vec::new();
+                    diff_values(String::new(), &registered, &desired, &mut changes);
+                    if changes.is_empty() {
+                        plan.unchanged.push(id);
+                    } else {
+                        plan.update.push(PlannedUpdate { resource, changes });
+                    }
+                }
+                Err(_) => {
+                    if let Resource::GraphDataset(_) = resource {
+                        self.check_not_deprecated(&resource).await?;
+                    }
+                    plan.create.push(resource)
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Registers the created and updated resources of `plan` atomically. Creation and update
+    /// times, the deployment and deprecation states, and the topology snapshots of updated
+    /// resources are kept by the registry.
+    pub async fn apply(&self, plan: &Plan) -> Result<(), Box<dyn Error>> {
+        let mut kvs = Vec::new();
+        let mut audited = Vec::new();
+        let resources = plan
+            .create
+            .iter()
+            .chain(plan.update.iter().map(|update| &update.resource));
+        for resource in resources {
+            let id = resource.resource_id();
+            let registered = match self.storage.get(&id).await {
+                Ok(registered) => Some(serde_json::from_str::<Value>(&registered)?),
+                Err(_) => None,
+            };
+            let mut value = serde_json::from_str::<Value>(&resource.to_json()?)?;
+            stamp(&mut value, registered.as_ref());
+            let value = serde_json::to_string(&value)?;
+            let mut written = None;
+            if let Some((key, revision)) = self.next_revision(&id, &value, None).await? {
+                written = Some(revision.revision);
+                kvs.push((key, serde_json::to_string(&revision)?));
+            }
+            audited.push((id.clone(), written));
+            kvs.push((id, value));
+        }
+        let mut conditions = Vec::new();
+        self.append_audit(AuditAction::Apply, &audited, &mut kvs, &mut conditions)?;
+        info!(
+            "Applying feature repository: {} created, {} updated",
+            plan.create.len(),
+            plan.update.len()
+        );
+        self.storage.put_all_if(&conditions, &kvs).await?;
+        Ok(())
+    }
+
+    /// Builds the resources of `repo`, looking up referenced entities and feature views in the
+    /// registry if they are not in the repository
+    async fn build_repo(
+        &self,
+        repo: &FeatureRepo,
+    ) -> Result<BTreeMap<ResourceId, Resource>, Box<dyn Error>> {
+        let mut resources = BTreeMap::new();
+
+        for spec in &repo.entity {
+            insert(
+                &mut resources,
+                Resource::Entity(Entity::Vertex(VertexEntity {
+                    name: spec.name.clone(),
+                    tlabel: spec.tlabel.clone().unwrap_or_else(|| spec.name.clone()),
+                    primary_key: spec.primary_key.clone(),
+                    variant: variant_of(&spec.variant),
+                    deprecated: false,
+                })),
+            )?;
+        }
+        let mut tlabels: HashMap<ResourceId, String> = resources
+            .iter()
+            .filter_map(|(id, resource)| match resource {
+                Resource::Entity(entity) => Some((id.clone(), entity.tlabel().to_string())),
+                _ => None,
+            })
+            .collect();
+        for spec in &repo.edge_entity {
+            let variant = variant_of(&spec.variant);
+            let src_entity_id = resolve(&spec.src_entity, ResourceKind::Entity, &variant);
+            let dst_entity_id = resolve(&spec.dst_entity, ResourceKind::Entity, &variant);
+            for entity_id in [&src_entity_id, &dst_entity_id] {
+                if !tlabels.contains_key(entity_id) {
+                    let entity = self.get_entity(entity_id).await.map_err(|_| {
+                        RegistryError::MissingReference {
+                            id: format!("{variant}/Entity/{}", spec.name),
+                            reference: entity_id.clone(),
+                        }
+                    })?;
+                    tlabels.insert(entity_id.clone(), entity.tlabel().to_string());
+                }
+            }
+            insert(
+                &mut resources,
+                Resource::Entity(Entity::Edge(EdgeEntity {
+                    name: spec.name.clone(),
+                    tlabel: spec.tlabel.clone().unwrap_or_else(|| spec.name.clone()),
+                    src_tlabel: tlabels[&src_entity_id].clone(),
+                    dst_tlabel: tlabels[&dst_entity_id].clone(),
+                    src_entity_id,
+                    dst_entity_id,
+                    directed: spec.directed,
+                    primary_key: spec.primary_key.clone(),
+                    variant,
+                    deprecated: false,
+                })),
+            )?;
+        }
+
+        for spec in &repo.field {
+            let variant = variant_of(&spec.variant);
+            insert(
+                &mut resources,
+                Resource::Field(Field {
+                    name: spec.name.clone(),
+                    value_type: FeatureValueType::from_str(&spec.value_type)
+                        .map_err(|e| format!("Invalid value type of field {}: {e}", spec.name))?,
+                    entity_id: Some(resolve(&spec.entity, ResourceKind::Entity, &variant)),
+                    transformation_id: spec
+                        .transformation
+                        .as_ref()
+                        .map(|t| resolve(t, ResourceKind::Transformation, &variant)),
+                    description: spec.description.clone(),
+                    tags: spec.tags.clone(),
+                    owners: spec.owners.clone(),
+                    sink_infra_id: spec.sink_infra.clone(),
+                    variant,
+                    deprecated: false,
+                }),
+            )?;
+        }
+
+        for spec in &repo.topology {
+            let variant = variant_of(&spec.variant);
+            let src_node_entity_id = resolve(&spec.src_entity, ResourceKind::Entity, &variant);
+            let dst_node_entity_id = resolve(&spec.dst_entity, ResourceKind::Entity, &variant);
+            // edge entity ids are suffixed by the names of their source and destination entities
+            let edge_entity_id =
+                if ResourceKind::of(&spec.edge_entity) == Some(ResourceKind::Entity) {
+                    spec.edge_entity.clone()
+                } else {
+                    format!(
+                        "{variant}/Entity/{}/{}/{}",
+                        spec.edge_entity,
+                        Entity::id_to_name(&src_node_entity_id),
+                        Entity::id_to_name(&dst_node_entity_id)
+                    )
+                };
+            insert(
+                &mut resources,
+                Resource::Topology(Topology {
+                    name: spec.name.clone(),
+                    transformation_id: spec
+                        .transformation
+                        .as_ref()
+                        .map(|t| resolve(t, ResourceKind::Transformation, &variant)),
+                    topology_type: spec.topology_type.clone(),
+                    edge_entity_id: Some(edge_entity_id),
+                    src_node_entity_id: Some(src_node_entity_id),
+                    dst_node_entity_id: Some(dst_node_entity_id),
+                    variant,
+                    description: spec.description.clone(),
+                    tags: spec.tags.clone(),
+                    owners: spec.owners.clone(),
+                    sink_infra_id: spec.sink_infra.clone(),
+                    ..Default::default()
+                }),
+            )?;
+        }
+
+        for spec in &repo.table_feature_view {
+            let variant = variant_of(&spec.variant);
+            let entity_id = resolve(&spec.entity, ResourceKind::Entity, &variant);
+            let entity_name = Entity::id_to_name(&entity_id);
+            let mut view = TableFeatureView::default(&spec.name, entity_id.clone(), &[]);
+            view.field_ids = spec
+                .fields
+                .iter()
+                .map(|field| {
+                    if ResourceKind::of(field) == Some(ResourceKind::Field) {
+                        field.clone()
+                    } else {
+                        format!("{variant}/Field/{entity_name}/{field}")
+                    }
+                })
+                .collect();
+            view.online = spec.online;
+            view.description = spec.description.clone();
+            view.tags = spec.tags.clone();
+            view.owner = spec.owner.clone();
+            view.variant = variant;
+            insert(&mut resources, Resource::TableFeatureView(view))?;
+        }
+
+        for spec in &repo.topology_feature_view {
+            let variant = variant_of(&spec.variant);
+            let mut view = TopologyFeatureView::default(&spec.name, &[]);
+            view.topology_ids = spec
+                .topologies
+                .iter()
+                .map(|topology| resolve(topology, ResourceKind::Topology, &variant))
+                .collect();
+            if let Some(topology_type) = &spec.topology_type {
+                view.topology_type = topology_type.clone();
+            }
+            view.online = spec.online;
+            view.description = spec.description.clone();
+            view.tags = spec.tags.clone();
+            view.owners = spec.owners.clone();
+            view.variant = variant;
+            insert(&mut resources, Resource::TopologyFeatureView(view))?;
+        }
+
+        for spec in &repo.graph_dataset {
+            let variant = variant_of(&spec.variant);
+            let id = format!("{variant}/GraphDataset/{}", spec.name);
+            let mut dataset = GraphDataset {
+                name: spec.name.clone(),
+                description: spec.description.clone(),
+                ..Default::default()
+            };
+            for view in &spec.table_feature_views {
+                let view_id = resolve(view, ResourceKind::TableFeatureView, &variant);
+                let view = match resources.get(&view_id) {
+                    Some(Resource::TableFeatureView(view)) => view.clone(),
+                    _ => self.get_table_feature_view(&view_id).await.map_err(|_| {
+                        RegistryError::MissingReference {
+                            id: id.clone(),
+                            reference: view_id.clone(),
+                        }
+                    })?,
+                };
+                dataset.table_feature_views.push(view);
+            }
+            for view in &spec.topology_feature_views {
+                let view_id = resolve(view, ResourceKind::TopologyFeatureView, &variant);
+                let view = match resources.get(&view_id) {
+                    Some(Resource::TopologyFeatureView(view)) => view.clone(),
+                    _ => self
+                        .get_topology_feature_view(&view_id)
+                        .await
+                        .map_err(|_| RegistryError::MissingReference {
+                            id: id.clone(),
+                            reference: view_id.clone(),
+                        })?,
+                };
+                dataset.topology_feature_views.push(view);
+            }
+            dataset.variant = variant;
+            insert(&mut resources, Resource::GraphDataset(dataset))?;
+        }
+        Ok(resources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPO: &str = r#"
+        [[entity]]
+        name = "Reviewer"
+        primary_key = "reviewerID"
+
+        [[entity]]
+        name = "Product"
+        primary_key = "asin"
+
+        [[edge_entity]]
+        name = "Rates"
+        src_entity = "Reviewer"
+        dst_entity = "Product"
+
+        [[field]]
+        entity = "Reviewer"
+        name = "pagerank"
+        value_type = "Float"
+        sink_infra = { Redis = "redis" }
+
+        [[topology]]
+        name = "rates"
+        edge_entity = "Rates"
+        src_entity = "Reviewer"
+        dst_entity = "Product"
+
+        [[table_feature_view]]
+        name = "reviewer_view"
+        entity = "Reviewer"
+        fields = ["pagerank"]
+
+        [[topology_feature_view]]
+        name = "rates_view"
+        topologies = ["rates"]
+        topology_type = "AdjacencyList"
+
+        [[graph_dataset]]
+        name = "reviewer_dataset"
+        table_feature_views = ["reviewer_view"]
+        topology_feature_views = ["rates_view"]
+    "#;
+
+    #[tokio::test]
+    async fn test_plan_apply() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let repo: FeatureRepo = REPO.parse()?;
+        let plan = registry.plan(&repo).await?;
+        let ids: Vec<_> = plan.create.iter().map(Resource::resource_id).collect();
+        assert_eq!(ids.len(), 8);
+        assert_eq!(ids[0], "default/Entity/Product");
+        assert_eq!(ids[7], "default/GraphDataset/reviewer_dataset");
+        assert!(ids.contains(&"default/Entity/Rates/Reviewer/Product".to_string()));
+        registry.apply(&plan).await?;
+
+        let view = registry
+            .get_table_feature_view(&"default/TableFeatureView/reviewer_view".to_string())
+            .await?;
+        assert_eq!(view.field_ids, vec!["default/Field/Reviewer/pagerank"]);
+        let created_at = view.created_at;
+        assert!(created_at.is_some());
+
+        // applying the same repository again changes nothing
+        let plan = registry.plan(&repo).await?;
+        assert!(plan.is_empty());
+        assert_eq!(plan.unchanged.len(), 8);
+
+        let repo: FeatureRepo = REPO
+            .replace("primary_key = \"asin\"", "primary_key = \"productID\"")
+            .replace(
+                "fields = [\"pagerank\"]",
+                "fields = [\"pagerank\"]\n        online = true",
+            )
+            .parse()?;
+        let plan = registry.plan(&repo).await?;
+        let ids: Vec<_> = plan
+            .update
+            .iter()
+            .map(|update| update.resource.resource_id())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "default/Entity/Product",
+                "default/TableFeatureView/reviewer_view",
+                "default/GraphDataset/reviewer_dataset"
+            ]
+        );
+        assert_eq!(plan.update[0].changes[0].path, "/Vertex/primary_key");
+        registry.apply(&plan).await?;
+        let view = registry
+            .get_table_feature_view(&"default/TableFeatureView/reviewer_view".to_string())
+            .await?;
+        assert!(view.online);
+        assert_eq!(view.created_at, created_at);
+        assert!(view.updated_at.is_some());
+        assert_eq!(
+            registry
+                .list_revisions(&"default/Entity/Product".to_string())
+                .await?
+                .len(),
+            2
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_plan_missing_reference() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let repo: FeatureRepo = r#"
+            [[field]]
+            entity = "Reviewer"
+            name = "pagerank"
+            value_type = "Float"
+        "#
+        .parse()?;
+        let err = registry.plan(&repo).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::BrokenReferences { broken })
+                if broken[0].reference == "default/Entity/Reviewer"
+        ));
+        assert!("[[entity]]\nname = \"Reviewer\"\nkey = \"id\""
+            .parse::<FeatureRepo>()
+            .is_err());
+        Ok(())
+    }
+}
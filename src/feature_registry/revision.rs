@@ -0,0 +1,336 @@
+//! Revision history of registered resources.
+//!
+//! Every registration of a resource is recorded as a numbered [ResourceRevision] under
+//! [REVISION_PREFIX], so that old definitions can be listed, compared and restored.
+
+use super::{
+    schema::{upgrade_resource, SCHEMA_VERSION},
+    AuditAction, FeatureRegistry,
+};
+use crate::feature::{ResourceId, ResourceKind, ResourceOp};
+
+use chrono::{serde::ts_seconds, DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+/// Registry keyspace of revision records, `{REVISION_PREFIX}{resource id}/{revision}`
+pub(crate) const REVISION_PREFIX: &str = "__revisions__/";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceRevision {
+    pub resource_id: ResourceId,
+    /// Starts from 1 and increases by 1 on each registration of the resource
+    pub revision: u64,
+    pub author: String,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    /// The revision restored by this revision, if created by a rollback
+    pub rollback_of: Option<u64>,
+    /// The resource definition of this revision
+    pub value: Value,
+    /// Schema version of `value`, `0` for revisions recorded before versioning
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+impl ResourceRevision {
+    /// Parses a revision record, upgrading its value to the current schema version
+    fn from_record(record: &str) -> Result<Self, Box<dyn Error>> {
+        let mut revision = serde_json::from_str::<ResourceRevision>(record)?;
+        let kind = ResourceKind::of(&revision.resource_id)
+            .ok_or_else(|| format!("Unknown resource kind of {}", revision.resource_id))?;
+        upgrade_resource(kind, &mut revision.value, revision.schema_version)?;
+        revision.schema_version = SCHEMA_VERSION;
+        Ok(revision)
+    }
+}
+
+/// Changes from one revision of a resource to another
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevisionDiff {
+    pub resource_id: ResourceId,
+    pub from: u64,
+    pub to: u64,
+    pub changes: Vec<ValueChange>,
+}
+
+/// A changed value in a resource definition. `old` is `None` for added values and `new` is
+/// `None` for removed values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    /// JSON pointer to the changed value, e.g. `/field_ids/1`
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Formats the change as `path: old -> new`, with `(none)` for missing values
+impl std::fmt::Display for ValueChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = |value: &Option<Value>| {
+            value
+                .as_ref()
+                .map_or("(none)".to_string(), |value| value.to_string())
+        };
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.path,
+            format(&self.old),
+            format(&self.new)
+        )
+    }
+}
+
+fn revision_key(id: &str, revision: u64) -> String {
+    format!("{REVISION_PREFIX}{id}/{revision:010}")
+}
+
+pub(super) fn revision_prefix(id: &str) -> String {
+    format!("{REVISION_PREFIX}{id}/")
+}
+
+impl FeatureRegistry {
+    /// Lists all revisions of a resource, ordered from the oldest to the latest.
+    pub async fn list_revisions(
+        &self,
+        id: &ResourceId,
+    ) -> Result<Vec<ResourceRevision>, Box<dyn Error>> {
+        let prefix = revision_prefix(id);
+        let mut revisions = Vec::new();
+        for (key, value) in self.storage.get_by_prefix(&prefix).await? {
+            // skip revisions of other resources whose ids start with `id/`
+            if key[prefix.len()..].bytes().all(|c| c.is_ascii_digit()) {
+                revisions.push(ResourceRevision::from_record(&value)?);
+            }
+        }
+        Ok(revisions)
+    }
+
+    pub async fn get_revision(
+        &self,
+        id: &ResourceId,
+        revision: u64,
+    ) -> Result<ResourceRevision, Box<dyn Error>> {
+        let value = self.storage.get(&revision_key(id, revision)).await?;
+        ResourceRevision::from_record(&value)
+    }
+
+    /// Gets the resource definition of a given revision.
+    pub async fn get_resource_at<T>(
+        &self,
+        id: &ResourceId,
+        revision: u64,
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: ResourceOp,
+    {
+        // round trip through a string, as `serde_json::from_value` rejects empty tuple variants
+        // like `Variant::Default()`
+        let value = serde_json::to_string(&self.get_revision(id, revision).await?.value)?;
+        Ok(serde_json::from_str::<T>(&value)?)
+    }
+
+    /// Compares two revisions of a resource.
+    pub async fn diff_revisions(
+        &self,
+        id: &ResourceId,
+        from: u64,
+        to: u64,
+    ) -> Result<RevisionDiff, Box<dyn Error>> {
+        let old = self.get_revision(id, from).await?;
+        let new = self.get_revision(id, to).await?;
+        let mut changes = Vec::new();
+        diff_values(String::new(), &old.value, &new.value, &mut changes);
+        Ok(RevisionDiff {
+            resource_id: id.clone(),
+            from,
+            to,
+            changes,
+        })
+    }
+
+    /// Restores the definition of a resource to a previous revision. The rollback is recorded as
+    /// a new revision and its number is returned.
+    pub async fn rollback_resource(
+        &self,
+        id: &ResourceId,
+        revision: u64,
+    ) -> Result<u64, Box<dyn Error>> {
+        let target = self.get_revision(id, revision).await?;
+        let value = serde_json::to_string(&target.value)?;
+        info!("Rolling back resource {id} to revision {revision}");
+        let new_revision = self.record_revision(id, &value, Some(revision)).await?;
+        self.storage.put(id, &value).await?;
+        self.audit(AuditAction::Rollback, &[(id.clone(), new_revision)])
+            .await?;
+        Ok(new_revision.unwrap_or(revision))
+    }
+
+    /// Gets the keys of all revisions of a resource, e.g. to delete them with the resource.
+    pub(super) async fn revision_keys(
+        &self,
+        id: &ResourceId,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .list_revisions(id)
+            .await?
+            .iter()
+            .map(|revision| revision_key(id, revision.revision))
+            .collect())
+    }
+
+    /// Records `value` as the next revision of resource `id`. Returns `None` without recording if
+    /// the value is the same as the latest revision.
+    pub(super) async fn record_revision(
+        &self,
+        id: &ResourceId,
+        value: &str,
+        rollback_of: Option<u64>,
+    ) -> Result<Option<u64>, Box<dyn Error>> {
+        let next = self.next_revision(id, value, rollback_of).await?;
+        match next {
+            Some((key, revision)) => {
+                let value = serde_json::to_string(&revision)?;
+                self.storage.put(&key, &value).await?;
+                Ok(Some(revision.revision))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Creates the next revision of resource `id` with `value` and its registry key, without
+    /// writing it. Returns `None` if the value is the same as the latest revision.
+    pub(super) async fn next_revision(
+        &self,
+        id: &ResourceId,
+        value: &str,
+        rollback_of: Option<u64>,
+    ) -> Result<Option<(String, ResourceRevision)>, Box<dyn Error>> {
+        let value = serde_json::from_str::<Value>(value)?;
+        let latest = self.list_revisions(id).await?.pop();
+        if let Some(latest) = &latest {
+            if latest.value == value {
+                return Ok(None);
+            }
+        }
+        let revision = ResourceRevision {
+            resource_id: id.clone(),
+            revision: latest.map_or(1, |latest| latest.revision + 1),
+            author: self.author.clone(),
+            created_at: Utc::now(),
+            rollback_of,
+            value,
+            schema_version: SCHEMA_VERSION,
+        };
+        Ok(Some((revision_key(id, revision.revision), revision)))
+    }
+}
+
+pub(super) fn diff_values(path: String, old: &Value, new: &Value, changes: &mut Vec<ValueChange>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child = format!("{path}/{key}");
+                match new_map.get(key) {
+                    Some(new_value) => diff_values(child, old_value, new_value, changes),
+                    None => changes.push(ValueChange {
+                        path: child,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    changes.push(ValueChange {
+                        path: format!("{path}/{key}"),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_vec), Value::Array(new_vec)) => {
+            for idx in 0..old_vec.len().max(new_vec.len()) {
+                let child = format!("{path}/{idx}");
+                match (old_vec.get(idx), new_vec.get(idx)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_values(child, old_value, new_value, changes)
+                    }
+                    (old_value, new_value) => changes.push(ValueChange {
+                        path: child,
+                        old: old_value.cloned(),
+                        new: new_value.cloned(),
+                    }),
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                changes.push(ValueChange {
+                    path,
+                    old: Some(old.clone()),
+                    new: Some(new.clone()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, Entity, Variant};
+
+    #[test]
+    fn test_diff_values() {
+        let old = serde_json::json!({"name": "a", "ids": [1, 2], "tags": {"k": "v"}});
+        let new = serde_json::json!({"name": "b", "ids": [1], "tags": {}, "owner": "x"});
+        let mut changes = Vec::new();
+        diff_values(String::new(), &old, &new, &mut changes);
+        assert_eq!(changes.len(), 4);
+        assert!(changes.contains(&ValueChange {
+            path: "/ids/1".to_string(),
+            old: Some(serde_json::json!(2)),
+            new: None,
+        }));
+        assert!(changes.contains(&ValueChange {
+            path: "/owner".to_string(),
+            old: None,
+            new: Some(serde_json::json!("x")),
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_revision_rollback() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory().with_author("tester");
+        let v1 = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let v2 = entity!("Reviewer", Variant::Default(), "Reviewer", "name");
+        let id = v1.resource_id();
+        registry.register_resource(&v1).await?;
+        registry.register_resource(&v1).await?; // unchanged, no new revision
+        registry.register_resource(&v2).await?;
+
+        let revisions = registry.list_revisions(&id).await?;
+        assert_eq!(
+            revisions.iter().map(|r| r.revision).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(revisions[0].author, "tester");
+
+        let diff = registry.diff_revisions(&id, 1, 2).await?;
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].path, "/Vertex/primary_key");
+
+        assert_eq!(registry.rollback_resource(&id, 1).await?, 3);
+        let entity = registry.get_entity(&id).await?;
+        assert_eq!(entity.primary_key().unwrap(), "reviewerID");
+        let restored = registry.get_resource_at::<Entity>(&id, 3).await?;
+        assert_eq!(restored.primary_key().unwrap(), "reviewerID");
+        assert_eq!(registry.get_revision(&id, 3).await?.rollback_of, Some(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+//! Versioning of the stored formats of resources and transformation bodies.
+//!
+//! Resources are stored as the JSON of their structs, stamped with the [SCHEMA_VERSION] they are
+//! written in under [SCHEMA_VERSION_KEY]. Transformation bodies are stamped with
+//! [TRANSFORMATION_BODY_VERSION] in the same way. Payloads written before versioning are of
+//! version `0`. When a struct changes, the version is increased and a migration that upgrades
+//! payloads of the previous version is added, so that old payloads keep being readable. Old
+//! payloads are upgraded on read, and [FeatureRegistry::migrate_schema] rewrites them in bulk.
+
+use super::{
+    revision::REVISION_PREFIX,
+    storage::{ProjectStorage, RegistryStorage},
+    AuditAction, FeatureRegistry, RegistryError, ResourceRevision,
+};
+use crate::feature::{ResourceId, ResourceKind};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+/// Version of the stored format of resources written by this version of ofnil
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Version of the format of transformation bodies written by this version of ofnil
+pub const TRANSFORMATION_BODY_VERSION: u32 = 1;
+
+/// Key of the version in stored resources and transformation bodies
+pub const SCHEMA_VERSION_KEY: &str = "__schema_version";
+
+/// Upgrades a stored resource of a kind from one version to the next
+type ResourceMigration = fn(ResourceKind, &mut Value) -> Result<(), String>;
+
+/// `RESOURCE_MIGRATIONS[v]` upgrades stored resources of version `v` to version `v + 1`
+const RESOURCE_MIGRATIONS: [ResourceMigration; SCHEMA_VERSION as usize] = [unversioned_resource];
+
+/// Upgrades a transformation body from one version to the next
+type BodyMigration = fn(&mut Value) -> Result<(), String>;
+
+/// `BODY_MIGRATIONS[v]` upgrades transformation bodies of version `v` to version `v + 1`
+const BODY_MIGRATIONS: [BodyMigration; TRANSFORMATION_BODY_VERSION as usize] = [unversioned_body];
+
+/// Resources written before versioning are of the format of version 1
+fn unversioned_resource(_kind: ResourceKind, _value: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Transformation bodies written before versioning are of the format of version 1
+fn unversioned_body(_value: &mut Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Stamps `value` with `version`
+fn stamp(value: &mut Value, version: u32) {
+    if let Value::Object(map) = value {
+        map.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(version));
+    }
+}
+
+/// Removes the version stamp from `value` and returns the version, `0` if it is not stamped
+fn take_version(value: &mut Value) -> Result<u32, RegistryError> {
+    let stamped = match value {
+        Value::Object(map) => map.remove(SCHEMA_VERSION_KEY),
+        _ => None,
+    };
+    match stamped {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| RegistryError::Migration {
+                version: 0,
+                reason: format!("invalid schema version {version}"),
+            }),
+    }
+}
+
+/// Upgrades a payload of `version` to `current`, with `migrate(v)` upgrading it from version `v`
+fn upgrade<F>(version: u32, current: u32, mut migrate: F) -> Result<(), RegistryError>
+where
+    F: FnMut(usize) -> Result<(), String>,
+{
+    if version > current {
+        return Err(RegistryError::UnsupportedSchemaVersion {
+            version,
+            supported: current,
+        });
+    }
+    for from in version..current {
+        migrate(from as usize).map_err(|reason| RegistryError::Migration {
+            version: from,
+            reason,
+        })?;
+    }
+    Ok(())
+}
+
+/// Whether the registry key `key` is of a resource, rather than of an internal record
+pub(super) fn is_resource_key(key: &str) -> bool {
+    !key.starts_with("__") && ResourceKind::of(key).is_some()
+}
+
+/// Stamps resource JSON `value` of the current format for storage
+pub(super) fn encode_resource(value: &str) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::from_str::<Value>(value)?;
+    stamp(&mut value, SCHEMA_VERSION);
+    serde_json::to_string(&value)
+}
+
+/// Upgrades resource `value` of `version` to the current format
+pub(super) fn upgrade_resource(
+    kind: ResourceKind,
+    value: &mut Value,
+    version: u32,
+) -> Result<(), RegistryError> {
+    upgrade(version, SCHEMA_VERSION, |from| {
+        RESOURCE_MIGRATIONS[from](kind, value)
+    })
+}
+
+/// Decodes stored resource `key` into the JSON of its current format, with the stored version
+pub(super) fn decode_resource(key: &str, stored: &str) -> Result<(String, u32), Box<dyn Error>> {
+    let kind = ResourceKind::of(key).ok_or_else(|| format!("Unknown resource kind of {key}"))?;
+    let mut value = serde_json::from_str::<Value>(stored)?;
+    let version = take_version(&mut value)?;
+    upgrade_resource(kind, &mut value, version)?;
+    Ok((serde_json::to_string(&value)?, version))
+}
+
+/// Serializes a transformation body stamped with [TRANSFORMATION_BODY_VERSION]
+pub fn encode_transformation_body(body: &impl Serialize) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::to_value(body)?;
+    stamp(&mut value, TRANSFORMATION_BODY_VERSION);
+    serde_json::to_string(&value)
+}
+
+/// Deserializes a transformation body of any version, upgrading it to the current format
+pub fn decode_transformation_body<T>(body: &str) -> Result<T, Box<dyn Error>>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let (body, _) = upgrade_transformation_body(body)?;
+    Ok(serde_json::from_str::<T>(&body)?)
+}
+
+/// Upgrades a transformation body to the current format, without the version stamp. Returns the
+/// upgraded body and its original version.
+fn upgrade_transformation_body(body: &str) -> Result<(String, u32), Box<dyn Error>> {
+    let mut value = serde_json::from_str::<Value>(body)?;
+    let version = take_version(&mut value)?;
+    upgrade(version, TRANSFORMATION_BODY_VERSION, |from| {
+        BODY_MIGRATIONS[from](&mut value)
+    })?;
+    Ok((serde_json::to_string(&value)?, version))
+}
+
+/// Upgrades the body of a transformation JSON `value` to the current format, stamped. Returns
+/// whether the body has been upgraded.
+fn upgrade_transformation(value: &mut Value) -> Result<bool, Box<dyn Error>> {
+    let Some(Value::String(body)) = value.get_mut("body") else {
+        return Ok(false);
+    };
+    if body.is_empty() {
+        return Ok(false);
+    }
+    let (upgraded, version) = upgrade_transformation_body(body)?;
+    if version == TRANSFORMATION_BODY_VERSION {
+        return Ok(false);
+    }
+    let mut upgraded = serde_json::from_str::<Value>(&upgraded)?;
+    stamp(&mut upgraded, TRANSFORMATION_BODY_VERSION);
+    *body = serde_json::to_string(&upgraded)?;
+    Ok(true)
+}
+
+/// Outcome of [FeatureRegistry::migrate_schema]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaMigrationReport {
+    /// Resources whose stored format or transformation body is upgraded
+    pub resources: Vec<ResourceId>,
+    /// Number of upgraded revision records
+    pub revisions: usize,
+    /// Whether the upgrades are only reported and not written
+    pub dry_run: bool,
+}
+
+impl FeatureRegistry {
+    /// Rewrites the resources, transformation bodies and revision records of the project that
+    /// are stored in older formats in the current format, unless `dry_run` is set. Resources
+    /// referenced from other projects are left to their projects. Each payload is only rewritten
+    /// if it has not been changed concurrently, and the rewritten resources are audited in the
+    /// same write.
+    pub async fn migrate_schema(
+        &self,
+        dry_run: bool,
+    ) -> Result<SchemaMigrationReport, Box<dyn Error>> {
+        // read the stored payloads, not upgraded on read
+        let raw = ProjectStorage::new(self.root.clone(), &self.project);
+        let mut report = SchemaMigrationReport {
+            dry_run,
+            ..Default::default()
+        };
+        let mut conditions = Vec::new();
+        let mut kvs = Vec::new();
+        let prefix = raw.key("");
+        for (key, _) in self.root.get_by_prefix(&prefix).await? {
+            let key = key[prefix.len()..].to_string();
+            if !is_resource_key(&key) && !key.starts_with(REVISION_PREFIX) {
+                continue;
+            }
+            // the payload is read with its mod revision, to be rewritten only if unchanged since
+            let (stored, mod_revision) = self.root.get_with_mod_revision(&raw.key(&key)).await?;
+            if is_resource_key(&key) {
+                let (value, version) = decode_resource(&key, &stored)?;
+                let mut value = serde_json::from_str::<Value>(&value)?;
+                let body_upgraded = ResourceKind::of(&key) == Some(ResourceKind::Transformation)
+                    && upgrade_transformation(&mut value)?;
+                if version == SCHEMA_VERSION && !body_upgraded {
+                    continue;
+                }
+                stamp(&mut value, SCHEMA_VERSION);
+                conditions.push((key.clone(), mod_revision));
+                kvs.push((key.clone(), serde_json::to_string(&value)?));
+                report.resources.push(key);
+            } else if key.starts_with(REVISION_PREFIX) {
+                let mut revision = serde_json::from_str::<ResourceRevision>(&stored)?;
+                let kind = ResourceKind::of(&revision.resource_id)
+                    .ok_or_else(|| format!("Unknown resource kind of {}", revision.resource_id))?;
+                upgrade_resource(kind, &mut revision.value, revision.schema_version)?;
+                let body_upgraded = kind == ResourceKind::Transformation
+                    && upgrade_transformation(&mut revision.value)?;
+                if revision.schema_version == SCHEMA_VERSION && !body_upgraded {
+                    continue;
+                }
+                revision.schema_version = SCHEMA_VERSION;
+                conditions.push((key.clone(), mod_revision));
+                kvs.push((key, serde_json::to_string(&revision)?));
+                report.revisions += 1;
+            }
+        }
+        info!(
+            "Migrating {} resources and {} revisions of project {} to schema version {SCHEMA_VERSION}",
+            report.resources.len(),
+            report.revisions,
+            self.project
+        );
+        if !dry_run && !kvs.is_empty() {
+            let audited: Vec<_> = report
+                .resources
+                .iter()
+                .map(|id| (id.clone(), None))
+                .collect();
+            self.append_audit(
+                AuditAction::MigrateSchema,
+                &audited,
+                &mut kvs,
+                &mut conditions,
+            )?;
+            raw.put_all_if(&conditions, &kvs).await?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity, feature::ResourceOp, AuditQuery, MemoryStorage, RegistryStorage, Transformation,
+        Variant, DEFAULT_PROJECT,
+    };
+
+    #[tokio::test]
+    async fn test_migrate_schema() -> Result<(), Box<dyn Error>> {
+        let storage = MemoryStorage::new();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let transformation = Transformation {
+            name: "TRANSFORMATION_1".to_string(),
+            body: r#"{"next_data_id":0}"#.to_string(),
+            ..Default::default()
+        };
+        // payloads written before versioning
+        let prefix = format!("__projects__/{DEFAULT_PROJECT}/");
+        for (id, value) in [
+            (reviewer.resource_id(), serde_json::to_string(&reviewer)?),
+            (
+                transformation.resource_id(),
+                serde_json::to_string(&transformation)?,
+            ),
+        ] {
+            storage.put(&format!("{prefix}{id}"), &value).await?;
+        }
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        storage
+            .put(
+                &format!("__projects__/team_b/{}", product.resource_id()),
+                &serde_json::to_string(&product)?,
+            )
+            .await?;
+        let registry = FeatureRegistry::with_storage(storage);
+        // resources referenced from other projects are migrated in their projects
+        registry
+            .reference_from_project("team_b", &[product.resource_id()])
+            .await?;
+
+        // upgraded on read
+        let entity = registry.get_entity(&reviewer.resource_id()).await?;
+        assert_eq!(entity.primary_key().unwrap(), "reviewerID");
+        let read = registry
+            .get_transformation(&transformation.resource_id())
+            .await?;
+        assert_eq!(read.body, transformation.body);
+
+        let report = registry.migrate_schema(true).await?;
+        assert_eq!(
+            report.resources,
+            vec![reviewer.resource_id(), transformation.resource_id()]
+        );
+        assert_eq!(registry.migrate_schema(false).await?.resources.len(), 2);
+        assert!(registry.migrate_schema(false).await?.resources.is_empty());
+        let query = AuditQuery {
+            actions: vec![AuditAction::MigrateSchema],
+            ..Default::default()
+        };
+        assert_eq!(registry.query_audit_log(&query).await?.len(), 2);
+
+        let stored = registry
+            .root
+            .get(&format!("{prefix}{}", transformation.resource_id()))
+            .await?;
+        let stored = serde_json::from_str::<Value>(&stored)?;
+        assert_eq!(stored[SCHEMA_VERSION_KEY], SCHEMA_VERSION);
+        let body = serde_json::from_str::<Value>(stored["body"].as_str().unwrap())?;
+        assert_eq!(body[SCHEMA_VERSION_KEY], TRANSFORMATION_BODY_VERSION);
+        let read = registry
+            .get_transformation(&transformation.resource_id())
+            .await?;
+        assert_eq!(
+            decode_transformation_body::<Value>(&read.body)?,
+            serde_json::json!({"next_data_id": 0})
+        );
+
+        // payloads of newer versions are rejected
+        registry
+            .root
+            .put(
+                &format!("{prefix}{}", reviewer.resource_id()),
+                &format!(r#"{{"{SCHEMA_VERSION_KEY}": {}}}"#, SCHEMA_VERSION + 1),
+            )
+            .await?;
+        let err = registry
+            .get_entity(&reviewer.resource_id())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RegistryError>(),
+            Some(RegistryError::UnsupportedSchemaVersion { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Search of registered resources by their metadata, so that existing features can be found and
+//! reused instead of being recomputed.
+
+use super::FeatureRegistry;
+use crate::{
+    feature::{Resource, ResourceKind},
+    FeatureValueType, Variant,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{error::Error, str::FromStr};
+
+/// Matches resources with tag `key`, and with tag value `value` if given
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// Parses `key` or `key:value`
+impl FromStr for TagFilter {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once(':') {
+            Some((key, value)) => TagFilter {
+                key: key.to_string(),
+                value: Some(value.to_string()),
+            },
+            None => TagFilter {
+                key: s.to_string(),
+                value: None,
+            },
+        })
+    }
+}
+
+/// Criteria of [FeatureRegistry::search]. A resource matches if it meets all the given criteria,
+/// and the default query matches all resources.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Matches resources of any of the kinds, or of all kinds if empty
+    pub kinds: Vec<ResourceKind>,
+    pub variant: Option<Variant>,
+    /// Matches resources with all the tags
+    pub tags: Vec<TagFilter>,
+    pub owner: Option<String>,
+    /// Matches fields of the value type
+    pub value_type: Option<FeatureValueType>,
+    /// Case-insensitive text to look for in the names and descriptions of resources
+    pub text: Option<String>,
+    /// Number of matched resources to skip
+    pub offset: usize,
+    /// Maximum number of resources to return, or all if `None`
+    pub limit: Option<usize>,
+}
+
+impl SearchQuery {
+    pub fn matches(&self, resource: &Resource) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&resource.kind()) {
+            return false;
+        }
+        if let Some(variant) = &self.variant {
+            if !resource.resource_id().starts_with(&format!("{variant}/")) {
+                return false;
+            }
+        }
+        for tag in &self.tags {
+            let value = resource.tags().and_then(|tags| tags.get(&tag.key));
+            match (value, &tag.value) {
+                (None, _) => return false,
+                (Some(value), Some(expected)) if value != expected => return false,
+                _ => {}
+            }
+        }
+        if let Some(owner) = &self.owner {
+            if !resource.owners().contains(&owner.as_str()) {
+                return false;
+            }
+        }
+        if self.value_type.is_some() && resource.value_type() != self.value_type.as_ref() {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            let found = resource.name().to_lowercase().contains(&text)
+                || resource
+                    .description()
+                    .is_some_and(|desc| desc.to_lowercase().contains(&text));
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// A page of search results, ordered by resource id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    /// Total number of matched resources, including those not in this page
+    pub total: usize,
+    pub offset: usize,
+    pub resources: Vec<Resource>,
+}
+
+impl FeatureRegistry {
+    /// Searches registered resources by kind, tags, owner, value type and description.
+    pub async fn search(&self, query: &SearchQuery) -> Result<SearchResult, Box<dyn Error>> {
+        let matched: Vec<_> = self
+            .get_all_resources()
+            .await?
+            .into_iter()
+            .filter(|resource| query.matches(resource))
+            .collect();
+        let total = matched.len();
+        let resources = matched
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(SearchResult {
+            total,
+            offset: query.offset,
+            resources,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, feature::ResourceOp, fields};
+
+    #[tokio::test]
+    async fn test_search() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        registry.register_resource(&reviewer).await?;
+        let mut fields = fields!(
+            vec![
+                ("pagerank", FeatureValueType::Float),
+                ("degree", FeatureValueType::Int),
+                ("name", FeatureValueType::String),
+            ],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        for field in &mut fields {
+            field.owners = vec!["alice".to_string()];
+        }
+        fields[0].description = Some("PageRank score of reviewers".to_string());
+        fields[0]
+            .tags
+            .insert("algorithm".to_string(), "pagerank".to_string());
+        fields[1]
+            .tags
+            .insert("algorithm".to_string(), "degree".to_string());
+        fields[2].owners = vec!["bob".to_string()];
+        registry
+            .register_resources(&fields.iter().collect())
+            .await?;
+
+        let ids = |result: SearchResult| {
+            result
+                .resources
+                .iter()
+                .map(Resource::resource_id)
+                .collect::<Vec<_>>()
+        };
+        let query = SearchQuery {
+            tags: vec!["algorithm".parse()?],
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(registry.search(&query).await?),
+            vec![fields[1].resource_id(), fields[0].resource_id()]
+        );
+        let query = SearchQuery {
+            text: Some("pagerank SCORE".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(registry.search(&query).await?),
+            vec![fields[0].resource_id()]
+        );
+        let query = SearchQuery {
+            kinds: vec![ResourceKind::Field],
+            owner: Some("alice".to_string()),
+            value_type: Some(FeatureValueType::Int),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(registry.search(&query).await?),
+            vec![fields[1].resource_id()]
+        );
+
+        let query = SearchQuery {
+            offset: 1,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let result = registry.search(&query).await?;
+        assert_eq!(result.total, 4);
+        assert_eq!(
+            ids(result),
+            vec![fields[1].resource_id(), fields[2].resource_id()]
+        );
+        Ok(())
+    }
+}
@@ -1,37 +1,149 @@
-use etcd_rs::{Client, ClientConfig, Endpoint, KeyValueOp};
+//! Key-value storage backends of the feature registry.
+//!
+//! The registry only relies on [RegistryStorage], so the backing store can be etcd for shared
+//! deployments, or an in-memory / local directory store for notebooks, unit tests and CI.
+
+mod etcd;
+mod local;
+mod memory;
+mod project;
+mod versioned;
+
+pub use etcd::EtcdStorage;
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+pub(super) use project::{ProjectStorage, LINK_PREFIX, PROJECT_PREFIX};
+pub(super) use versioned::VersionedStorage;
+
+use super::RegistryError;
+use futures::{stream::BoxStream, StreamExt};
+use log::warn;
 use std::error::Error;
+use tokio::sync::broadcast::{self, error::RecvError};
 
-pub(super) struct EtcdStorage {
-    client: Client,
+/// A change of a storage entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageEvent {
+    Put { key: String, value: String },
+    Delete { key: String },
 }
 
-impl EtcdStorage {
-    pub(super) async fn new(endpoints: Vec<impl Into<String>>) -> Result<Self, Box<dyn Error>> {
-        let endpoints: Vec<Endpoint> = endpoints.into_iter().map(Endpoint::new).collect();
-        let client = Client::connect(ClientConfig::new(endpoints)).await?;
-        Ok(Self { client })
+impl StorageEvent {
+    pub fn key(&self) -> &str {
+        match self {
+            StorageEvent::Put { key, .. } | StorageEvent::Delete { key } => key,
+        }
     }
+}
+
+/// Storage interface required by [FeatureRegistry](crate::FeatureRegistry). Keys are resource ids
+/// such as `default/Entity/Reviewer` and values are serialized resources.
+#[async_trait::async_trait]
+pub trait RegistryStorage: Send + Sync {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Returns a `NotFound` io error if the key does not exist.
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>>;
 
-    pub(super) async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
-        let _resp = self.client.put((key, value)).await?;
-        Ok(())
+    /// Returns the value of the key together with its mod revision, which changes on every write
+    /// of the key. Returns a `NotFound` io error if the key does not exist.
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>>;
+
+    /// Returns all `(key, value)` pairs whose key starts with `prefix`, ordered by key.
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>>;
+
+    /// Puts all `(key, value)` pairs atomically, i.e. either all or none of them are written.
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>>;
+
+    /// Puts all `(key, value)` pairs atomically if every `(key, mod revision)` of `expected`
+    /// still holds, where mod revision `0` means that the key does not exist. Otherwise nothing
+    /// is written and [RegistryError::Conflict](crate::RegistryError::Conflict) is returned.
+    async fn put_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+    ) -> Result<(), Box<dyn Error>> {
+        self.write_all_if(expected, kvs, &[]).await
     }
 
-    pub(super) async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
-        let resp = self.client.get(key).await?;
-        let value = resp.kvs.first();
-        if let Some(kv) = value {
-            Ok(kv.value_str().to_string())
-        } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("No entry found for key {key}"),
-            )))
+    /// Like [RegistryStorage::put_all_if], but also deletes the keys `deletes` in the same atomic
+    /// write, e.g. to move keys or to delete a resource together with its audit record.
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Deletes the key. Deleting an absent key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Streams the changes of the keys starting with `prefix` from now on.
+    async fn watch(&self, prefix: &str)
+        -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>>;
+}
+
+/// Broadcasts the changes made through a storage instance to its watchers, for backends that do
+/// not support watching natively.
+#[derive(Debug)]
+pub(super) struct ChangeNotifier {
+    sender: broadcast::Sender<StorageEvent>,
+}
+
+impl Default for ChangeNotifier {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(1024).0,
         }
     }
+}
+
+impl ChangeNotifier {
+    pub(super) fn notify(&self, event: StorageEvent) {
+        // there may be no watchers
+        let _ = self.sender.send(event);
+    }
 
-    pub(super) async fn get_all(&self, key: &str) -> Result<Vec<String>, Box<dyn Error>> {
-        let resp = self.client.get_by_prefix(key).await?;
-        Ok(resp.kvs.iter().map(|e| e.value_str().to_string()).collect())
+    pub(super) fn watch(&self, prefix: &str) -> BoxStream<'static, StorageEvent> {
+        let prefix = prefix.to_string();
+        futures::stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Registry watcher is lagging behind, skipped {skipped} changes")
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .filter(move |event| futures::future::ready(event.key().starts_with(&prefix)))
+        .boxed()
     }
 }
+
+/// Fails with a conflict on the first `(key, mod revision)` of `expected` that does not match
+/// `actual`, which gets the current mod revision of a key or `0` if it does not exist
+pub(super) fn check_expected(
+    expected: &[(String, u64)],
+    actual: impl Fn(&str) -> u64,
+) -> Result<(), RegistryError> {
+    for (key, expected) in expected {
+        let actual = actual(key);
+        if actual != *expected {
+            return Err(RegistryError::Conflict {
+                key: key.clone(),
+                expected: *expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn not_found(key: &str) -> Box<dyn Error> {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("No entry found for key {key}"),
+    ))
+}
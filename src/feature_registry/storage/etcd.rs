@@ -0,0 +1,138 @@
+use super::{check_expected, not_found, RegistryStorage, StorageEvent};
+use etcd_rs::{
+    Client, ClientConfig, DeleteRequest, Endpoint, EventType, KeyRange, KeyValueOp, PutRequest,
+    TxnCmp, TxnRequest, WatchInbound, WatchOp,
+};
+use futures::{stream::BoxStream, StreamExt};
+use log::error;
+use std::{collections::HashMap, error::Error};
+
+pub struct EtcdStorage {
+    client: Client,
+}
+
+impl EtcdStorage {
+    pub async fn new(endpoints: Vec<impl Into<String>>) -> Result<Self, Box<dyn Error>> {
+        let endpoints: Vec<Endpoint> = endpoints.into_iter().map(Endpoint::new).collect();
+        let client = Client::connect(ClientConfig::new(endpoints)).await?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryStorage for EtcdStorage {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let _resp = self.client.put((key, value)).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
+        let resp = self.client.get(key).await?;
+        let value = resp.kvs.first();
+        if let Some(kv) = value {
+            Ok(kv.value_str().to_string())
+        } else {
+            Err(not_found(key))
+        }
+    }
+
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>> {
+        let resp = self.client.get(key).await?;
+        match resp.kvs.first() {
+            Some(kv) => Ok((kv.value_str().to_string(), kv.mod_revision as u64)),
+            None => Err(not_found(key)),
+        }
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let resp = self.client.get_by_prefix(prefix).await?;
+        Ok(resp
+            .kvs
+            .iter()
+            .map(|e| (e.key_str().to_string(), e.value_str().to_string()))
+            .collect())
+    }
+
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+        let txn = kvs.iter().fold(TxnRequest::new(), |txn, (key, value)| {
+            txn.and_then(PutRequest::new(key.as_str(), value.as_str()))
+        });
+        let _resp = self.client.txn(txn).await?;
+        Ok(())
+    }
+
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        // the mod revision of an absent key is 0 in etcd
+        let txn = expected
+            .iter()
+            .fold(TxnRequest::new(), |txn, (key, revision)| {
+                txn.when_mod_revision(
+                    KeyRange::key(key.as_str()),
+                    TxnCmp::Equal,
+                    *revision as usize,
+                )
+            });
+        let txn = kvs.iter().fold(txn, |txn, (key, value)| {
+            txn.and_then(PutRequest::new(key.as_str(), value.as_str()))
+        });
+        let txn = deletes.iter().fold(txn, |txn, key| {
+            txn.and_then(DeleteRequest::new(KeyRange::key(key.as_str())))
+        });
+        let resp = self.client.txn(txn).await?;
+        if resp.succeeded {
+            return Ok(());
+        }
+        // find out which key has changed to report the conflict
+        let mut actual = HashMap::new();
+        for (key, _) in expected {
+            let resp = self.client.get(key.as_str()).await?;
+            let revision = resp.kvs.first().map_or(0, |kv| kv.mod_revision as u64);
+            actual.insert(key.as_str(), revision);
+        }
+        check_expected(expected, |key| actual[key])?;
+        Err("Conditional write failed, but all expected mod revisions hold".into())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        let _resp = self.client.delete(KeyRange::key(key)).await?;
+        Ok(())
+    }
+
+    async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>> {
+        let (stream, canceler) = self.client.watch(KeyRange::prefix(prefix)).await?;
+        // the canceler is kept along with the stream so that the watch lives as long as the stream
+        let responses =
+            futures::stream::unfold((stream, canceler), |(mut stream, canceler)| async move {
+                match stream.inbound().await {
+                    WatchInbound::Ready(resp) => Some((resp.events, (stream, canceler))),
+                    WatchInbound::Interrupted(e) => {
+                        error!("Registry watch is interrupted: {e}");
+                        None
+                    }
+                    WatchInbound::Closed => None,
+                }
+            });
+        Ok(responses
+            .flat_map(|events| {
+                futures::stream::iter(events.into_iter().map(|event| {
+                    let key = event.kv.key_str().to_string();
+                    match event.event_type {
+                        EventType::Put => StorageEvent::Put {
+                            key,
+                            value: event.kv.value_str().to_string(),
+                        },
+                        EventType::Delete => StorageEvent::Delete { key },
+                    }
+                }))
+            })
+            .boxed())
+    }
+}
@@ -0,0 +1,269 @@
+use super::{check_expected, not_found, ChangeNotifier, RegistryStorage, StorageEvent};
+use futures::stream::BoxStream;
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::sync::Mutex;
+
+const FILE_EXTENSION: &str = "json";
+/// Extension of the file next to each entry that keeps its mod revision
+const REVISION_EXTENSION: &str = "rev";
+/// File of the last mod revision given to a write
+const REVISION_FILE: &str = ".revision";
+
+/// Embedded on-disk storage that keeps each entry as a JSON file in a single directory.
+///
+/// Keys are escaped into flat file names (`default/Entity/Reviewer` is stored as
+/// `default%2FEntity%2FReviewer.json`) so that a key can also be a prefix of other keys.
+///
+/// Watchers are only notified of the changes made through the same [LocalStorage] instance, and
+/// conditional writes are only serialized with the writes made through the same instance.
+#[derive(Debug)]
+pub struct LocalStorage {
+    dir: PathBuf,
+    notifier: ChangeNotifier,
+    /// Serializes writes, so that mod revisions are checked and increased consistently
+    write_lock: Mutex<()>,
+}
+
+impl LocalStorage {
+    /// Creates the directory if it does not exist.
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            notifier: ChangeNotifier::default(),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    fn path_of(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.{FILE_EXTENSION}", encode_key(key)))
+    }
+
+    /// Gets the mod revision of `key`, `0` if it does not exist. Entries written before mod
+    /// revisions were kept are of mod revision `1`.
+    async fn mod_revision(&self, key: &str) -> Result<u64, Box<dyn Error>> {
+        let path = self.path_of(key);
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(0);
+        }
+        match tokio::fs::read_to_string(path.with_extension(REVISION_EXTENSION)).await {
+            Ok(revision) => Ok(revision.trim().parse()?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(1),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// Writes all entries to temporary files before renaming any of them or deleting the entries
+    /// of `deletes`, so that a failed write leaves the storage unchanged. Note that the renames
+    /// and deletes themselves are not atomic as a whole. Must be called with the write lock held.
+    async fn write_all(
+        &self,
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let counter_path = self.dir.join(REVISION_FILE);
+        let revision = match tokio::fs::read_to_string(&counter_path).await {
+            Ok(revision) => revision.trim().parse::<u64>()? + 1,
+            Err(e) if e.kind() == ErrorKind::NotFound => 2,
+            Err(e) => return Err(Box::new(e)),
+        };
+        tokio::fs::write(&counter_path, revision.to_string()).await?;
+
+        let mut renames = Vec::with_capacity(kvs.len());
+        for (key, value) in kvs {
+            let path = self.path_of(key);
+            let tmp_path = path.with_extension("tmp");
+            if let Err(e) = tokio::fs::write(&tmp_path, value).await {
+                for (tmp_path, _) in renames {
+                    let _ = tokio::fs::remove_file(tmp_path).await;
+                }
+                return Err(Box::new(e));
+            }
+            renames.push((tmp_path, path));
+        }
+        for (tmp_path, path) in renames {
+            tokio::fs::rename(&tmp_path, &path).await?;
+            tokio::fs::write(
+                path.with_extension(REVISION_EXTENSION),
+                revision.to_string(),
+            )
+            .await?;
+        }
+        for (key, value) in kvs {
+            self.notifier.notify(StorageEvent::Put {
+                key: key.clone(),
+                value: value.clone(),
+            });
+        }
+        for key in deletes {
+            self.delete_entry(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the entry of `key` if it exists. Must be called with the write lock held.
+    async fn delete_entry(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        let path = self.path_of(key);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {
+                let _ = tokio::fs::remove_file(path.with_extension(REVISION_EXTENSION)).await;
+                self.notifier.notify(StorageEvent::Delete {
+                    key: key.to_string(),
+                });
+                Ok(())
+            }
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(Box::new(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn encode_key(key: &str) -> String {
+    key.replace('%', "%25").replace('/', "%2F")
+}
+
+fn decode_key(file_stem: &str) -> String {
+    file_stem.replace("%2F", "/").replace("%25", "%")
+}
+
+#[async_trait::async_trait]
+impl RegistryStorage for LocalStorage {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.put_all(&[(key.to_string(), value.to_string())]).await
+    }
+
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
+        match tokio::fs::read_to_string(self.path_of(key)).await {
+            Ok(value) => Ok(value),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(not_found(key)),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>> {
+        let _guard = self.write_lock.lock().await;
+        let value = self.get(key).await?;
+        Ok((value, self.mod_revision(key).await?))
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut keys = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(FILE_EXTENSION) {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                let key = decode_key(stem);
+                if key.starts_with(prefix) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        let mut kvs = Vec::with_capacity(keys.len());
+        for key in keys {
+            let value = self.get(&key).await?;
+            kvs.push((key, value));
+        }
+        Ok(kvs)
+    }
+
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+        let _guard = self.write_lock.lock().await;
+        self.write_all(kvs, &[]).await
+    }
+
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let _guard = self.write_lock.lock().await;
+        let mut actual = HashMap::new();
+        for (key, _) in expected {
+            actual.insert(key.as_str(), self.mod_revision(key).await?);
+        }
+        check_expected(expected, |key| actual[key])?;
+        self.write_all(kvs, deletes).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        let _guard = self.write_lock.lock().await;
+        self.delete_entry(key).await
+    }
+
+    async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>> {
+        Ok(self.notifier.watch(prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_storage() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("ofnil_local_storage_{}", std::process::id()));
+        let storage = LocalStorage::new(&dir)?;
+        storage.put("default/Entity/Reviewer", "{}").await?;
+        storage
+            .put("default/Entity/rates/Reviewer/Product", "[]")
+            .await?;
+        storage.put("default/Field/Reviewer/100%", "1").await?;
+
+        assert_eq!(storage.get("default/Entity/Reviewer").await?, "{}");
+        let entities = storage.get_by_prefix("default/Entity/").await?;
+        assert_eq!(
+            entities.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(),
+            vec![
+                "default/Entity/Reviewer",
+                "default/Entity/rates/Reviewer/Product"
+            ]
+        );
+        assert_eq!(storage.get("default/Field/Reviewer/100%").await?, "1");
+
+        // persisted across instances
+        let reopened = LocalStorage::new(&dir)?;
+        reopened.delete("default/Entity/Reviewer").await?;
+        reopened.delete("default/Entity/Reviewer").await?;
+        assert!(storage.get("default/Entity/Reviewer").await.is_err());
+        assert_eq!(storage.get_by_prefix("default/").await?.len(), 2);
+
+        // conditional writes
+        let (_, revision) = storage
+            .get_with_mod_revision("default/Field/Reviewer/100%")
+            .await?;
+        storage
+            .put_all_if(
+                &[
+                    ("default/Field/Reviewer/100%".to_string(), revision),
+                    ("default/Entity/Reviewer".to_string(), 0),
+                ],
+                &[("default/Entity/Reviewer".to_string(), "{}".to_string())],
+            )
+            .await?;
+        assert!(storage
+            .put_all_if(
+                &[("default/Entity/Reviewer".to_string(), 0)],
+                &[("default/Entity/Reviewer".to_string(), "[]".to_string())],
+            )
+            .await
+            .is_err());
+        assert_eq!(storage.get("default/Entity/Reviewer").await?, "{}");
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
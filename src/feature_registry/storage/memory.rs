@@ -0,0 +1,124 @@
+use super::{check_expected, not_found, ChangeNotifier, RegistryStorage, StorageEvent};
+use futures::stream::BoxStream;
+use std::{collections::BTreeMap, error::Error, sync::RwLock};
+
+/// Non-persistent storage, mainly for tests and notebooks. All data is lost when dropped.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    kvs: RwLock<Entries>,
+    notifier: ChangeNotifier,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// `key -> (value, mod revision)`
+    kvs: BTreeMap<String, (String, u64)>,
+    /// Increased on every write, like the revision of an etcd cluster
+    revision: u64,
+}
+
+impl Entries {
+    fn put_all(&mut self, kvs: &[(String, String)]) {
+        self.revision += 1;
+        for (key, value) in kvs {
+            self.kvs.insert(key.clone(), (value.clone(), self.revision));
+        }
+    }
+
+    fn mod_revision(&self, key: &str) -> u64 {
+        self.kvs.get(key).map_or(0, |(_, revision)| *revision)
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn notify_puts(&self, kvs: &[(String, String)]) {
+        for (key, value) in kvs {
+            self.notifier.notify(StorageEvent::Put {
+                key: key.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryStorage for MemoryStorage {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.put_all(&[(key.to_string(), value.to_string())]).await
+    }
+
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
+        Ok(self.get_with_mod_revision(key).await?.0)
+    }
+
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>> {
+        self.kvs
+            .read()
+            .unwrap()
+            .kvs
+            .get(key)
+            .cloned()
+            .ok_or_else(|| not_found(key))
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        Ok(self
+            .kvs
+            .read()
+            .unwrap()
+            .kvs
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, (value, _))| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+        self.kvs.write().unwrap().put_all(kvs);
+        self.notify_puts(kvs);
+        Ok(())
+    }
+
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let deleted: Vec<_> = {
+            let mut entries = self.kvs.write().unwrap();
+            check_expected(expected, |key| entries.mod_revision(key))?;
+            entries.put_all(kvs);
+            deletes
+                .iter()
+                .filter(|key| entries.kvs.remove(*key).is_some())
+                .collect()
+        };
+        self.notify_puts(kvs);
+        for key in deleted {
+            self.notifier
+                .notify(StorageEvent::Delete { key: key.clone() });
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        if self.kvs.write().unwrap().kvs.remove(key).is_some() {
+            self.notifier.notify(StorageEvent::Delete {
+                key: key.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>> {
+        Ok(self.notifier.watch(prefix))
+    }
+}
@@ -0,0 +1,222 @@
+use super::{not_found, RegistryError, RegistryStorage, StorageEvent};
+use futures::{stream::BoxStream, StreamExt};
+use std::{collections::BTreeMap, error::Error, sync::Arc};
+
+/// Keyspace of all projects, `{PROJECT_PREFIX}{project}/{key}`
+pub(crate) const PROJECT_PREFIX: &str = "__projects__/";
+
+/// Keyspace of a project for links to resources of other projects,
+/// `{LINK_PREFIX}{resource id}` with the name of the linked project as value
+pub(crate) const LINK_PREFIX: &str = "__links__/";
+
+/// View of a shared storage scoped to the keyspace of one project.
+///
+/// Reads of resource ids that are not in the project fall back to the resources linked from other
+/// projects, see [FeatureRegistry::reference_from_project](crate::FeatureRegistry).
+pub(crate) struct ProjectStorage {
+    root: Arc<dyn RegistryStorage>,
+    prefix: String,
+}
+
+impl ProjectStorage {
+    pub(crate) fn new(root: Arc<dyn RegistryStorage>, project: &str) -> Self {
+        Self {
+            root,
+            prefix: format!("{PROJECT_PREFIX}{project}/"),
+        }
+    }
+
+    /// Key of `key` of this project in the root storage
+    pub(crate) fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+
+    fn linked_key(project: &str, key: &str) -> String {
+        format!("{PROJECT_PREFIX}{project}/{key}")
+    }
+
+    /// Keys of `key` to delete in the root storage, i.e. the key itself and the link of the
+    /// resource of the same id
+    fn delete_keys(&self, key: &str) -> Vec<String> {
+        let mut keys = vec![self.key(key)];
+        if !key.starts_with("__") {
+            keys.push(self.key(&format!("{LINK_PREFIX}{key}")));
+        }
+        keys
+    }
+}
+
+/// Gets `event` for `key` instead of its own key
+fn with_key(event: StorageEvent, key: String) -> StorageEvent {
+    match event {
+        StorageEvent::Put { value, .. } => StorageEvent::Put { key, value },
+        StorageEvent::Delete { .. } => StorageEvent::Delete { key },
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryStorage for ProjectStorage {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.root.put(&self.key(key), value).await
+    }
+
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
+        let own = self.root.get(&self.key(key)).await.ok();
+        if let Some(value) = own {
+            return Ok(value);
+        }
+        if key.starts_with("__") {
+            return Err(not_found(key));
+        }
+        let link = self
+            .root
+            .get(&self.key(&format!("{LINK_PREFIX}{key}")))
+            .await
+            .ok();
+        let linked = match link {
+            Some(project) => self.root.get(&Self::linked_key(&project, key)).await.ok(),
+            None => None,
+        };
+        linked.ok_or_else(|| not_found(key))
+    }
+
+    /// Resources referenced from other projects are of mod revision `0`, as they are not in
+    /// this project.
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>> {
+        let own = self.root.get_with_mod_revision(&self.key(key)).await.ok();
+        match own {
+            Some(own) => Ok(own),
+            None => Ok((self.get(key).await?, 0)),
+        }
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let own = self.root.get_by_prefix(&self.key(prefix)).await?;
+        let mut entries: BTreeMap<_, _> = own
+            .into_iter()
+            .map(|(key, value)| (key[self.prefix.len()..].to_string(), value))
+            .collect();
+        if !prefix.starts_with("__") {
+            let link_prefix = self.key(LINK_PREFIX);
+            let links = self
+                .root
+                .get_by_prefix(&format!("{link_prefix}{prefix}"))
+                .await?;
+            for (link, project) in links {
+                let key = &link[link_prefix.len()..];
+                if entries.contains_key(key) {
+                    continue;
+                }
+                // links to resources deleted in their projects are skipped
+                let linked = self.root.get(&Self::linked_key(&project, key)).await.ok();
+                if let Some(value) = linked {
+                    entries.insert(key.to_string(), value);
+                }
+            }
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+        let kvs: Vec<_> = kvs
+            .iter()
+            .map(|(key, value)| (self.key(key), value.clone()))
+            .collect();
+        self.root.put_all(&kvs).await
+    }
+
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(key, revision)| (self.key(key), *revision))
+            .collect();
+        let kvs: Vec<_> = kvs
+            .iter()
+            .map(|(key, value)| (self.key(key), value.clone()))
+            .collect();
+        let deletes: Vec<_> = deletes
+            .iter()
+            .flat_map(|key| self.delete_keys(key))
+            .collect();
+        match self.root.write_all_if(&expected, &kvs, &deletes).await {
+            Err(e) => match e.downcast::<RegistryError>() {
+                // report the conflicting key of this project
+                Ok(e) => match *e {
+                    RegistryError::Conflict {
+                        key,
+                        expected,
+                        actual,
+                    } => Err(Box::new(RegistryError::Conflict {
+                        key: key[self.prefix.len()..].to_string(),
+                        expected,
+                        actual,
+                    })),
+                    e => Err(Box::new(e)),
+                },
+                Err(e) => Err(e),
+            },
+            ok => ok,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        for key in self.delete_keys(key) {
+            self.root.delete(&key).await?;
+        }
+        Ok(())
+    }
+
+    /// Changes of the resources linked from other projects are watched in their projects, and
+    /// passed on while the link holds and this project has no resource of the same id.
+    async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>> {
+        let own_prefix = self.prefix.clone();
+        if prefix.starts_with("__") {
+            return Ok(self
+                .root
+                .watch(&self.key(prefix))
+                .await?
+                .map(move |event| {
+                    let key = event.key()[own_prefix.len()..].to_string();
+                    with_key(event, key)
+                })
+                .boxed());
+        }
+        let root = self.root.clone();
+        let prefix = prefix.to_string();
+        Ok(self
+            .root
+            .watch(PROJECT_PREFIX)
+            .await?
+            .filter_map(move |event| {
+                let (root, own_prefix, prefix) = (root.clone(), own_prefix.clone(), prefix.clone());
+                async move {
+                    if let Some(key) = event.key().strip_prefix(&own_prefix) {
+                        let key = key.to_string();
+                        return key.starts_with(&prefix).then(|| with_key(event, key));
+                    }
+                    let (project, key) = event.key()[PROJECT_PREFIX.len()..].split_once('/')?;
+                    if key.starts_with("__") || !key.starts_with(&prefix) {
+                        return None;
+                    }
+                    let link = root
+                        .get(&format!("{own_prefix}{LINK_PREFIX}{key}"))
+                        .await
+                        .ok()?;
+                    if link != project || root.get(&format!("{own_prefix}{key}")).await.is_ok() {
+                        return None;
+                    }
+                    let key = key.to_string();
+                    Some(with_key(event, key))
+                }
+            })
+            .boxed())
+    }
+}
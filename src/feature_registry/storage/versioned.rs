@@ -0,0 +1,115 @@
+use super::{RegistryStorage, StorageEvent};
+use crate::feature_registry::schema::{decode_resource, encode_resource, is_resource_key};
+use futures::{stream::BoxStream, StreamExt};
+use log::error;
+use std::error::Error;
+
+/// View of a storage that stamps the resources it writes with the current schema version, and
+/// upgrades the resources it reads to the current format, see
+/// [schema](crate::feature_registry::schema). Internal records are passed through as they are.
+pub(crate) struct VersionedStorage {
+    inner: Box<dyn RegistryStorage>,
+}
+
+impl VersionedStorage {
+    pub(crate) fn new(inner: impl RegistryStorage + 'static) -> Self {
+        Self {
+            inner: Box::new(inner),
+        }
+    }
+
+    fn encode(key: &str, value: &str) -> Result<String, Box<dyn Error>> {
+        if is_resource_key(key) {
+            Ok(encode_resource(value)?)
+        } else {
+            Ok(value.to_string())
+        }
+    }
+
+    fn decode(key: &str, stored: String) -> Result<String, Box<dyn Error>> {
+        if is_resource_key(key) {
+            Ok(decode_resource(key, &stored)?.0)
+        } else {
+            Ok(stored)
+        }
+    }
+
+    fn encode_all(kvs: &[(String, String)]) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        kvs.iter()
+            .map(|(key, value)| Ok((key.clone(), Self::encode(key, value)?)))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryStorage for VersionedStorage {
+    async fn put(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let value = Self::encode(key, value)?;
+        self.inner.put(key, &value).await
+    }
+
+    async fn get(&self, key: &str) -> Result<String, Box<dyn Error>> {
+        let stored = self.inner.get(key).await?;
+        Self::decode(key, stored)
+    }
+
+    async fn get_with_mod_revision(&self, key: &str) -> Result<(String, u64), Box<dyn Error>> {
+        let (stored, mod_revision) = self.inner.get_with_mod_revision(key).await?;
+        Ok((Self::decode(key, stored)?, mod_revision))
+    }
+
+    async fn get_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let stored = self.inner.get_by_prefix(prefix).await?;
+        stored
+            .into_iter()
+            .map(|(key, stored)| {
+                let value = Self::decode(&key, stored)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    async fn put_all(&self, kvs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+        let kvs = Self::encode_all(kvs)?;
+        self.inner.put_all(&kvs).await
+    }
+
+    async fn write_all_if(
+        &self,
+        expected: &[(String, u64)],
+        kvs: &[(String, String)],
+        deletes: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let kvs = Self::encode_all(kvs)?;
+        self.inner.write_all_if(expected, &kvs, deletes).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        self.inner.delete(key).await
+    }
+
+    async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, StorageEvent>, Box<dyn Error>> {
+        Ok(self
+            .inner
+            .watch(prefix)
+            .await?
+            .map(|event| match event {
+                StorageEvent::Put { key, value } => {
+                    // payloads that cannot be upgraded are passed on for the watcher to report
+                    let value = match Self::decode(&key, value.clone()) {
+                        Ok(value) => value,
+                        Err(e) => {
+                            error!("Cannot upgrade change of {key} to the current format: {e}");
+                            value
+                        }
+                    };
+                    StorageEvent::Put { key, value }
+                }
+                event => event,
+            })
+            .boxed())
+    }
+}
@@ -0,0 +1,355 @@
+//! Referential integrity of registered resources.
+//!
+//! Before resources are registered, the resources they reference must be registered or be
+//! registered together with them, and be of the expected kind, e.g. the entity of a field must be
+//! an entity and the fields of a table feature view must belong to the entity of the view.
+
+use super::{FeatureRegistry, RegistryError};
+use crate::{
+    feature::{Resource, ResourceId, ResourceKind, ResourceOp},
+    Entity,
+};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Why a reference is broken
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ReferenceProblem {
+    /// The referenced resource is not registered
+    Missing,
+    /// The referenced resource is of another kind than expected
+    WrongKind {
+        expected: ResourceKind,
+        found: ResourceKind,
+    },
+    /// A vertex entity is referenced where an edge entity is expected, or vice versa
+    WrongEntityType { expected_edge: bool },
+    /// A field of a table feature view belongs to another entity than the view
+    EntityMismatch { field_entity: Option<ResourceId> },
+}
+
+/// A reference from resource `id` through `attribute` to resource `reference` that is broken
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BrokenReference {
+    pub id: ResourceId,
+    /// The referring attribute, e.g. `entity_id` of a field
+    pub attribute: String,
+    pub reference: ResourceId,
+    pub problem: ReferenceProblem,
+}
+
+/// Expected kind of a referenced resource
+#[derive(Clone, Copy)]
+enum Expected {
+    Kind(ResourceKind),
+    VertexEntity,
+    EdgeEntity,
+}
+
+impl FeatureRegistry {
+    /// Checks the references of `resources`, which may reference each other, against the
+    /// registry. Fails with [RegistryError::BrokenReferences] listing all broken references.
+    pub(super) async fn validate_references(
+        &self,
+        resources: &[Resource],
+    ) -> Result<(), RegistryError> {
+        let batch: HashMap<_, _> = resources
+            .iter()
+            .map(|resource| (resource.resource_id(), resource))
+            .collect();
+        let mut registered = HashMap::new();
+        let mut broken = Vec::new();
+        for resource in resources {
+            let id = resource.resource_id();
+            for (attribute, reference, expected) in expected_references(resource) {
+                if !batch.contains_key(&reference) && !registered.contains_key(&reference) {
+                    let found = self.get_resource(&reference).await.ok();
+                    registered.insert(reference.clone(), found);
+                }
+                let target = batch
+                    .get(&reference)
+                    .copied()
+                    .or_else(|| registered[&reference].as_ref());
+                if let Some(problem) = check(target, expected) {
+                    broken.push(BrokenReference {
+                        id: id.clone(),
+                        attribute: attribute.to_string(),
+                        reference,
+                        problem,
+                    });
+                    continue;
+                }
+                if let (Resource::TableFeatureView(view), Some(Resource::Field(field))) =
+                    (resource, target)
+                {
+                    if field.entity_id.as_ref() != Some(&view.entity_id) {
+                        broken.push(BrokenReference {
+                            id: id.clone(),
+                            attribute: attribute.to_string(),
+                            reference,
+                            problem: ReferenceProblem::EntityMismatch {
+                                field_entity: field.entity_id.clone(),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        if broken.is_empty() {
+            Ok(())
+        } else {
+            Err(RegistryError::BrokenReferences { broken })
+        }
+    }
+}
+
+/// Lists the references of `resource` as `(attribute, reference, expected kind)`
+fn expected_references(resource: &Resource) -> Vec<(&'static str, ResourceId, Expected)> {
+    let mut references = Vec::new();
+    let mut push = |attribute, reference: Option<&ResourceId>, expected| {
+        if let Some(reference) = reference {
+            references.push((attribute, reference.clone(), expected));
+        }
+    };
+    match resource {
+        Resource::Entity(Entity::Vertex(_)) => {}
+        Resource::Entity(Entity::Edge(entity)) => {
+            push(
+                "src_entity_id",
+                Some(&entity.src_entity_id),
+                Expected::VertexEntity,
+            );
+            push(
+                "dst_entity_id",
+                Some(&entity.dst_entity_id),
+                Expected::VertexEntity,
+            );
+        }
+        Resource::Field(field) => {
+            push(
+                "entity_id",
+                field.entity_id.as_ref(),
+                Expected::Kind(ResourceKind::Entity),
+            );
+            push(
+                "transformation_id",
+                field.transformation_id.as_ref(),
+                Expected::Kind(ResourceKind::Transformation),
+            );
+        }
+        Resource::Topology(topology) => {
+            push(
+                "edge_entity_id",
+                topology.edge_entity_id.as_ref(),
+                Expected::EdgeEntity,
+            );
+            push(
+                "src_node_entity_id",
+                topology.src_node_entity_id.as_ref(),
+                Expected::VertexEntity,
+            );
+            push(
+                "dst_node_entity_id",
+                topology.dst_node_entity_id.as_ref(),
+                Expected::VertexEntity,
+            );
+            push(
+                "transformation_id",
+                topology.transformation_id.as_ref(),
+                Expected::Kind(ResourceKind::Transformation),
+            );
+        }
+        Resource::Graph(graph) => {
+            for entity_id in graph.entity_ids.values() {
+                push(
+                    "entity_ids",
+                    Some(entity_id),
+                    Expected::Kind(ResourceKind::Entity),
+                );
+            }
+        }
+        Resource::TableFeatureView(view) => {
+            push(
+                "entity_id",
+                Some(&view.entity_id),
+                Expected::Kind(ResourceKind::Entity),
+            );
+            for field_id in &view.field_ids {
+                push(
+                    "field_ids",
+                    Some(field_id),
+                    Expected::Kind(ResourceKind::Field),
+                );
+            }
+        }
+        Resource::TopologyFeatureView(view) => {
+            for topology_id in &view.topology_ids {
+                push(
+                    "topology_ids",
+                    Some(topology_id),
+                    Expected::Kind(ResourceKind::Topology),
+                );
+            }
+        }
+        Resource::Transformation(transformation) => {
+            for field_id in &transformation.source_field_ids {
+                push(
+                    "source_field_ids",
+                    Some(field_id),
+                    Expected::Kind(ResourceKind::Field),
+                );
+            }
+        }
+        Resource::GraphDataset(dataset) => {
+            for view in &dataset.table_feature_views {
+                push(
+                    "table_feature_views",
+                    Some(&view.resource_id()),
+                    Expected::Kind(ResourceKind::TableFeatureView),
+                );
+            }
+            for view in &dataset.topology_feature_views {
+                push(
+                    "topology_feature_views",
+                    Some(&view.resource_id()),
+                    Expected::Kind(ResourceKind::TopologyFeatureView),
+                );
+            }
+        }
+    }
+    references
+}
+
+fn check(target: Option<&Resource>, expected: Expected) -> Option<ReferenceProblem> {
+    let Some(target) = target else {
+        return Some(ReferenceProblem::Missing);
+    };
+    let expected_kind = match expected {
+        Expected::Kind(kind) => kind,
+        Expected::VertexEntity | Expected::EdgeEntity => ResourceKind::Entity,
+    };
+    if target.kind() != expected_kind {
+        return Some(ReferenceProblem::WrongKind {
+            expected: expected_kind,
+            found: target.kind(),
+        });
+    }
+    match (expected, target) {
+        (Expected::VertexEntity, Resource::Entity(Entity::Edge(_))) => {
+            Some(ReferenceProblem::WrongEntityType {
+                expected_edge: false,
+            })
+        }
+        (Expected::EdgeEntity, Resource::Entity(Entity::Vertex(_))) => {
+            Some(ReferenceProblem::WrongEntityType {
+                expected_edge: true,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, fields, FeatureValueType, TableFeatureView, Topology, Variant};
+    use std::error::Error;
+
+    fn broken(err: Box<dyn Error>) -> Vec<BrokenReference> {
+        match err.downcast::<RegistryError>().map(|err| *err) {
+            Ok(RegistryError::BrokenReferences { broken }) => broken,
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_references() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        let rates = entity!("Rates", Variant::Default(), "rates", &reviewer, &product);
+
+        // fields of an unregistered entity are rejected, and nothing of the batch is written
+        let reviewer_fields = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            Variant::Default(),
+            None,
+        );
+        let err = registry
+            .register_resources(&reviewer_fields.iter().collect())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            broken(err),
+            vec![BrokenReference {
+                id: reviewer_fields[0].resource_id(),
+                attribute: "entity_id".to_string(),
+                reference: reviewer.resource_id(),
+                problem: ReferenceProblem::Missing,
+            }]
+        );
+        let err = registry
+            .register_resources(&vec![&reviewer, &rates])
+            .await
+            .unwrap_err();
+        assert_eq!(broken(err)[0].reference, product.resource_id());
+        assert!(registry.get_all_entities(None).await?.is_empty());
+
+        // resources of a batch may reference each other
+        registry
+            .register_resources(&vec![&reviewer, &product, &rates])
+            .await?;
+        registry
+            .register_resources(&reviewer_fields.iter().collect())
+            .await?;
+        let product_fields = fields!(
+            vec![("price", FeatureValueType::Float)],
+            &product,
+            Variant::Default(),
+            None,
+        );
+        registry
+            .register_resources(&product_fields.iter().collect())
+            .await?;
+
+        let view = TableFeatureView::default(
+            "reviewer_view",
+            reviewer.resource_id(),
+            &[reviewer_fields[0].clone(), product_fields[0].clone()],
+        );
+        let err = registry.register_resource(&view).await.unwrap_err();
+        assert_eq!(
+            broken(err)[0].problem,
+            ReferenceProblem::EntityMismatch {
+                field_entity: Some(product.resource_id())
+            }
+        );
+
+        let topology = Topology {
+            name: "rates".to_string(),
+            edge_entity_id: Some(reviewer.resource_id()),
+            src_node_entity_id: Some(reviewer.resource_id()),
+            dst_node_entity_id: Some("default/Entity/User".to_string()),
+            ..Default::default()
+        };
+        let problems: Vec<_> = broken(registry.register_resource(&topology).await.unwrap_err())
+            .into_iter()
+            .map(|broken| (broken.attribute, broken.problem))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    "edge_entity_id".to_string(),
+                    ReferenceProblem::WrongEntityType {
+                        expected_edge: true
+                    }
+                ),
+                ("dst_node_entity_id".to_string(), ReferenceProblem::Missing),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! Variants of registered resources.
+//!
+//! A resource name can be registered in several variants, e.g. `default/Field/Reviewer/age` and
+//! `v2/Field/Reviewer/age`. A user-defined variant can be promoted to `default`, or to an alias
+//! such as `prod`, once it is ready.
+
+use super::{AuditAction, FeatureRegistry};
+use crate::{
+    feature::{Resource, ResourceId, ResourceKind},
+    Variant,
+};
+
+use log::info;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+};
+
+/// Replaces the variant segment of resource id `id`
+fn with_variant(id: &str, variant: &Variant) -> ResourceId {
+    match id.split_once('/') {
+        Some((_, rest)) => format!("{variant}/{rest}"),
+        None => id.to_string(),
+    }
+}
+
+fn variant_of(id: &str) -> Variant {
+    Variant::from(id.split('/').next().unwrap_or_default())
+}
+
+impl FeatureRegistry {
+    /// Lists the variants in which a resource of `kind` named `name` is registered, e.g. the
+    /// variants of field `Reviewer/age`.
+    pub async fn list_variants(
+        &self,
+        kind: ResourceKind,
+        name: &str,
+    ) -> Result<Vec<Variant>, Box<dyn Error>> {
+        Ok(self
+            .storage
+            .get_by_prefix("")
+            .await?
+            .into_iter()
+            .filter_map(|(key, _)| {
+                let mut segments = key.splitn(3, '/');
+                let variant = segments.next()?;
+                (!key.starts_with("__")
+                    && segments.next() == Some(kind.to_string().as_str())
+                    && segments.next() == Some(name))
+                .then(|| Variant::from(variant))
+            })
+            .collect())
+    }
+
+    /// Copies resource `id` to variant `to`, together with the resources of the same variant that
+    /// it references transitively, e.g. the fields of a feature view. References among the copied
+    /// resources are updated to the new variant. All copies are written atomically, and the ids
+    /// of the copies are returned.
+    pub async fn promote_variant(
+        &self,
+        id: &ResourceId,
+        to: &Variant,
+    ) -> Result<Vec<ResourceId>, Box<dyn Error>> {
+        let from = variant_of(id);
+        if &from == to {
+            return Err(format!("Resource {id} is already of variant {to}").into());
+        }
+
+        // collect the resources to promote
+        let mut promoted = BTreeMap::new();
+        let mut queue = VecDeque::from([id.clone()]);
+        while let Some(id) = queue.pop_front() {
+            if promoted.contains_key(&id) {
+                continue;
+            }
+            let resource = self.get_resource(&id).await?;
+            for reference in resource.references() {
+                // references to other variants, e.g. `default` entities, are kept as they are
+                if variant_of(&reference) == from && self.storage.get(&reference).await.is_ok() {
+                    queue.push_back(reference);
+                }
+            }
+            promoted.insert(id, resource);
+        }
+
+        let from_value = serde_json::to_value(&from)?;
+        let to_value = serde_json::to_value(to)?;
+        let mut kvs = Vec::new();
+        let mut ids = Vec::new();
+        let mut audited = Vec::new();
+        for (id, resource) in &promoted {
+            let mut value = serde_json::from_str::<Value>(&resource.to_json()?)?;
+            rewrite_variant(&mut value, &from_value, &to_value, &|reference| {
+                promoted
+                    .contains_key(reference)
+                    .then(|| with_variant(reference, to))
+            });
+            let new_id = with_variant(id, to);
+            let value = serde_json::to_string(&value)?;
+            // make sure that the copy is still a valid resource
+            Resource::from_json(&new_id, &value)?;
+            let mut written = None;
+            if let Some((key, revision)) = self.next_revision(&new_id, &value, None).await? {
+                written = Some(revision.revision);
+                kvs.push((key, serde_json::to_string(&revision)?));
+            }
+            audited.push((new_id.clone(), written));
+            kvs.push((new_id.clone(), value));
+            ids.push(new_id);
+        }
+        info!("Promoting {id} to variant {to}: {ids:?}");
+        let mut conditions = Vec::new();
+        self.append_audit(AuditAction::Promote, &audited, &mut kvs, &mut conditions)?;
+        self.storage.put_all_if(&conditions, &kvs).await?;
+        Ok(ids)
+    }
+}
+
+/// Replaces variant `from` by `to` in `value`, and renames the referenced resource ids that
+/// `rename` maps to new ids.
+fn rewrite_variant(
+    value: &mut Value,
+    from: &Value,
+    to: &Value,
+    rename: &impl Fn(&str) -> Option<ResourceId>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if key == "variant" && child == from {
+                    *child = to.clone();
+                } else {
+                    rewrite_variant(child, from, to, rename);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for child in values {
+                rewrite_variant(child, from, to, rename);
+            }
+        }
+        Value::String(string) => {
+            if let Some(renamed) = rename(string) {
+                *string = renamed;
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, feature::ResourceOp, fields, FeatureValueType, TableFeatureView};
+
+    #[tokio::test]
+    async fn test_promote_variant() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let v2 = Variant::user_defined("v2");
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        registry.register_resource(&reviewer).await?;
+        let fields = fields!(
+            vec![("age", FeatureValueType::Int)],
+            &reviewer,
+            v2.clone(),
+            None,
+        );
+        registry
+            .register_resources(&fields.iter().collect())
+            .await?;
+        let mut view = TableFeatureView::default("reviewer_view", reviewer.resource_id(), &fields);
+        view.variant = v2.clone();
+        registry.register_resource(&view).await?;
+
+        assert!(registry
+            .get_all_fields(Some(&Variant::Default()))
+            .await?
+            .is_empty());
+        assert_eq!(registry.get_all_fields(Some(&v2)).await?.len(), 1);
+        assert_eq!(registry.get_all_fields(None).await?.len(), 1);
+
+        let prod = Variant::user_defined("prod");
+        let promoted = registry.promote_variant(&view.resource_id(), &prod).await?;
+        assert_eq!(
+            promoted,
+            vec![
+                "prod/Field/Reviewer/age".to_string(),
+                "prod/TableFeatureView/reviewer_view".to_string()
+            ]
+        );
+        let view = registry
+            .get_table_feature_view(&"prod/TableFeatureView/reviewer_view".to_string())
+            .await?;
+        assert_eq!(view.entity_id, reviewer.resource_id());
+        assert_eq!(view.field_ids, vec!["prod/Field/Reviewer/age".to_string()]);
+
+        let variants = registry
+            .list_variants(ResourceKind::Field, "Reviewer/age")
+            .await?;
+        assert_eq!(
+            variants.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec!["prod", "v2"]
+        );
+        assert_eq!(
+            registry
+                .list_revisions(&"prod/Field/Reviewer/age".to_string())
+                .await?
+                .len(),
+            1
+        );
+        Ok(())
+    }
+}
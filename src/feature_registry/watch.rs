@@ -0,0 +1,100 @@
+//! Subscriptions to changes of registered resources, e.g. for the dashboard to refresh live or for
+//! long-running serving processes to pick up new definitions.
+
+use super::{storage::StorageEvent, FeatureRegistry};
+use crate::feature::{Resource, ResourceId};
+
+use futures::{stream::BoxStream, StreamExt};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// A change of a registered resource
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RegistryEvent {
+    /// The resource is registered or updated
+    Put {
+        id: ResourceId,
+        resource: Box<Resource>,
+    },
+    Delete {
+        id: ResourceId,
+    },
+}
+
+impl RegistryEvent {
+    pub fn id(&self) -> &ResourceId {
+        match self {
+            RegistryEvent::Put { id, .. } | RegistryEvent::Delete { id } => id,
+        }
+    }
+
+    fn from_storage_event(event: StorageEvent) -> Option<Self> {
+        // skip internal records such as revisions
+        if event.key().starts_with("__") {
+            return None;
+        }
+        match event {
+            StorageEvent::Put { key, value } => match Resource::from_json(&key, &value) {
+                Ok(resource) => Some(RegistryEvent::Put {
+                    id: key,
+                    resource: Box::new(resource),
+                }),
+                Err(e) => {
+                    error!("Skipping change of {key} that is not a valid resource: {e}");
+                    None
+                }
+            },
+            StorageEvent::Delete { key } => Some(RegistryEvent::Delete { id: key }),
+        }
+    }
+}
+
+impl FeatureRegistry {
+    /// Streams the changes of the resources whose ids start with `prefix` from now on, e.g.
+    /// `default/Field/` for all default fields. The stream ends if the underlying watch is
+    /// interrupted.
+    pub async fn watch(
+        &self,
+        prefix: &str,
+    ) -> Result<BoxStream<'static, RegistryEvent>, Box<dyn Error>> {
+        Ok(self
+            .storage
+            .watch(prefix)
+            .await?
+            .filter_map(|event| futures::future::ready(RegistryEvent::from_storage_event(event)))
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity, feature::ResourceOp, Variant};
+
+    #[tokio::test]
+    async fn test_watch() -> Result<(), Box<dyn Error>> {
+        let registry = FeatureRegistry::in_memory();
+        let mut events = registry.watch("default/Entity/").await?;
+        let reviewer = entity!("Reviewer", Variant::Default(), "Reviewer", "reviewerID");
+        let product = entity!("Product", Variant::Default(), "Product", "asin");
+        registry.register_resource(&reviewer).await?;
+        registry.register_resource(&product).await?;
+        registry
+            .delete_resource(&reviewer.resource_id(), false)
+            .await?;
+
+        let event = events.next().await.unwrap();
+        assert_eq!(event.id(), &reviewer.resource_id());
+        assert!(matches!(
+            event,
+            RegistryEvent::Put { ref resource, .. } if matches!(**resource, Resource::Entity(_))
+        ));
+        assert_eq!(events.next().await.unwrap().id(), &product.resource_id());
+        assert!(matches!(
+            events.next().await.unwrap(),
+            RegistryEvent::Delete { id } if id == reviewer.resource_id()
+        ));
+        Ok(())
+    }
+}
@@ -3,16 +3,24 @@ use log::{debug, info};
 
 use crate::{
     config::FeatureStoreConfig,
-    feature::{ResourceId, ResourceOp},
+    feature::{ResourceId, ResourceOp, TopologySnapshot},
+    infra::pi::storage::{CooTopology, FileFormat, IdMapping},
     transformation::*,
     *,
 };
-use std::{collections::HashMap, error::Error, path::Path};
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
 pub struct FeatureStore {
     pub(crate) project: String,
     pub(crate) registry: FeatureRegistry,
-    pub(crate) infra_manager: InfraManager,
+    /// Replaced by a copy on every change, so that running transformations and served views keep
+    /// the infras they started with
+    infra_manager: RwLock<Arc<InfraManager>>,
 }
 
 impl FeatureStore {
@@ -20,7 +28,7 @@ impl FeatureStore {
         Self {
             project,
             registry,
-            infra_manager,
+            infra_manager: RwLock::new(Arc::new(infra_manager)),
         }
     }
 
@@ -46,7 +54,7 @@ impl FeatureStore {
             None => Path::new(p.as_str()),
         };
 
-        let config = FeatureStoreConfig::from_dir(ofnil_home).unwrap();
+        let config = FeatureStoreConfig::from_dir(ofnil_home)?;
         debug!("Config: {:?}", config);
 
         FeatureStore::from_config(&config).await
@@ -54,7 +62,9 @@ impl FeatureStore {
 
     async fn from_config(config: &FeatureStoreConfig) -> Result<Self, Box<dyn Error>> {
         let project = config.project.clone();
-        let registry = FeatureRegistry::new(config.registry_endpoints.clone()).await?;
+        let registry = FeatureRegistry::from_config(&config.registry)
+            .await?
+            .with_project(&project);
         let infra_manager = InfraManager::from_config(&config.infra_manager).await;
 
         Ok(FeatureStore::new(project, registry, infra_manager))
@@ -63,7 +73,8 @@ impl FeatureStore {
     /// @param resource Resource id of FeatureView or (Graph)Dataset
     pub async fn deploy(&self, resource: ResourceId) -> Result<(), Box<dyn Error>> {
         info!("{}: deploy resource {}", self.project, resource);
-        let resource = self.registry.get_string(&resource).await?;
+        let resource_id = resource;
+        let resource = self.registry.get_string(&resource_id).await?;
         let transformation_to_data = if let Ok(table_feature_view) =
             serde_json::from_str::<TableFeatureView>(&resource)
         {
@@ -108,7 +119,9 @@ impl FeatureStore {
             let res = res.as_ref().unwrap();
             info!("Deployment output {:#?}", res);
         });
-        Ok(())
+        self.registry
+            .audit(AuditAction::Deploy, &[(resource_id, None)])
+            .await
     }
 
     async fn execute_transformation(
@@ -117,15 +130,16 @@ impl FeatureStore {
         data_ids: Vec<ResourceId>,
     ) -> Result<TransformationOutputHandler, Box<dyn Error>> {
         let transformation = self.registry.get_transformation(&transformation_id).await?;
-        let tc = serde_json::from_str::<TransformationContext>(&transformation.body)?;
+        let tc = decode_transformation_body::<TransformationContext>(&transformation.body)?;
         let mut plan = tc.get_materialization_plan(
             data_ids
                 .into_iter()
                 .map(|resource_id| transformation.get_data_id(&resource_id))
                 .collect(),
         );
-        plan.orchestrate_infras(&self.infra_manager);
-        plan.execute(&self.infra_manager).await
+        let infra_manager = self.infra_manager();
+        plan.orchestrate_infras(&infra_manager);
+        plan.execute(&infra_manager).await
     }
 
     async fn get_transformations_of_view_items<T>(
@@ -151,11 +165,147 @@ impl FeatureStore {
         transformation_data
     }
 
+    /// Exports topology `topology_id` from the Neo4j infra of the topology to binary files of
+    /// `formats` in local file infra `sink_infra_id`, with the internal ids of the relationships
+    /// if `with_edge_ids`. The vertices of each label are given contiguous ids in the order of
+    /// their internal ids. The export is recorded in the [snapshots](Topology::snapshots) of the
+    /// topology, replacing the previous export to the infra.
+    pub async fn export_topology(
+        &self,
+        topology_id: &ResourceId,
+        sink_infra_id: &InfraIdentifier,
+        formats: &[FileFormat],
+        with_edge_ids: bool,
+    ) -> Result<TopologySnapshot, Box<dyn Error>> {
+        info!(
+            "{}: export topology {topology_id} to {sink_infra_id:?}",
+            self.project
+        );
+        let (mut topology, mod_revision) = self
+            .registry
+            .get_with_mod_revision::<Topology>(topology_id)
+            .await?;
+        let entity_id = |id: &Option<ResourceId>| {
+            id.clone()
+                .ok_or_else(|| format!("Topology {topology_id} has no entities"))
+        };
+        let Entity::Edge(edge) = self
+            .registry
+            .get_entity(&entity_id(&topology.edge_entity_id)?)
+            .await?
+        else {
+            return Err(format!("Topology {topology_id} is not of an edge entity").into());
+        };
+        let get_vertex = |id: ResourceId| async move {
+            match self.registry.get_entity(&id).await? {
+                Entity::Vertex(vertex) => Ok::<_, Box<dyn Error>>(vertex),
+                Entity::Edge(_) => Err(format!("{id} is not a vertex entity").into()),
+            }
+        };
+        let src = get_vertex(entity_id(&topology.src_node_entity_id)?).await?;
+        let dst = get_vertex(entity_id(&topology.dst_node_entity_id)?).await?;
+
+        let infra_manager = self.infra_manager();
+        let database = match &topology.sink_infra_id {
+            Some(InfraIdentifier::Neo4j(name)) => infra_manager.get_neo4j_connector(name),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Topology {topology_id} is not in a connected Neo4j infra"))?
+        .get_database();
+        let connector = match sink_infra_id.infra_type() {
+            "local_file" => infra_manager.get_local_file_connector(sink_infra_id.name()),
+            _ => None,
+        }
+        .ok_or_else(|| format!("{sink_infra_id:?} is not a connected local file infra"))?;
+
+        let get_mapping = |tlabel: String, primary_key: String| {
+            let database = database.clone();
+            async move {
+                let mut ids: Vec<_> = database
+                    .get_id_mapping(&tlabel, &primary_key)
+                    .await?
+                    .into_iter()
+                    .collect();
+                ids.sort_unstable();
+                Ok::<_, Box<dyn Error>>(IdMapping::from_keys(ids.into_iter().map(|(_, key)| key)))
+            }
+        };
+        let src_mapping = get_mapping(src.tlabel.clone(), src.primary_key.clone()).await?;
+        let dst_mapping = if dst.tlabel == src.tlabel {
+            src_mapping.clone()
+        } else {
+            get_mapping(dst.tlabel.clone(), dst.primary_key.clone()).await?
+        };
+        let edges = database
+            .get_edge_list(&edge, &src.primary_key, &dst.primary_key)
+            .await?
+            .into_iter()
+            .map(|(src, dst, id)| (src, dst, Some(id as u64)));
+        let coo = CooTopology::from_edges(edges, &src_mapping, &dst_mapping, with_edge_ids)?;
+        let path = connector.write_topology(
+            &topology.name,
+            (&src.tlabel, &src_mapping),
+            (&dst.tlabel, &dst_mapping),
+            &coo,
+            formats,
+        )?;
+
+        let snapshot = TopologySnapshot {
+            infra_id: sink_infra_id.clone(),
+            path: path.display().to_string(),
+            formats: formats.to_vec(),
+            src_tlabel: src.tlabel,
+            dst_tlabel: dst.tlabel,
+            num_src_vertices: src_mapping.len() as u64,
+            num_dst_vertices: dst_mapping.len() as u64,
+            num_edges: coo.num_edges() as u64,
+            edge_ids: with_edge_ids,
+            created_at: Some(chrono::Utc::now()),
+        };
+        topology
+            .snapshots
+            .retain(|snapshot| &snapshot.infra_id != sink_infra_id);
+        topology.snapshots.push(snapshot.clone());
+        self.registry
+            .register_resource_if(&topology, mod_revision)
+            .await?;
+        Ok(snapshot)
+    }
+
+    /// Sets the client recorded in the audit records of the registry, see
+    /// [FeatureRegistry::with_client].
+    pub fn with_client(mut self, client: impl Into<String>) -> Self {
+        self.registry = self.registry.with_client(client);
+        self
+    }
+
     pub fn registry(&self) -> &FeatureRegistry {
         &self.registry
     }
 
-    pub fn infra_manager(&self) -> &InfraManager {
-        &self.infra_manager
+    /// Gets the current infras, which later changes of the infras do not affect
+    pub fn infra_manager(&self) -> Arc<InfraManager> {
+        self.infra_manager.read().unwrap().clone()
+    }
+
+    /// Connects infra `name` of `config` and adds it, replacing the infra of the same identifier
+    /// if any. The infras are left unchanged if the infra cannot be connected.
+    pub async fn add_infra(
+        &self,
+        name: &str,
+        config: &InfraConfig,
+    ) -> Result<InfraIdentifier, Box<dyn Error>> {
+        let (infra_id, infra) = InfraManager::connect(name, config).await?;
+        info!("{}: add infra {infra_id:?}", self.project);
+        let mut infra_manager = self.infra_manager.write().unwrap();
+        Arc::make_mut(&mut infra_manager).add_infra(infra_id.clone(), infra);
+        Ok(infra_id)
+    }
+
+    /// Removes infra `infra_id`. Returns whether there was one.
+    pub fn remove_infra(&self, infra_id: &InfraIdentifier) -> bool {
+        info!("{}: remove infra {infra_id:?}", self.project);
+        let mut infra_manager = self.infra_manager.write().unwrap();
+        Arc::make_mut(&mut infra_manager).remove_infra(infra_id)
     }
 }
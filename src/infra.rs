@@ -3,23 +3,107 @@
 //! to provide storage and/or transformation functionalities.
 
 pub mod connectors;
+mod health;
 pub mod pi;
 
 use connectors::*;
-use pi::{StorageConnector, TransformationConnector};
+use pi::{plugin::get_connector_factory, Connector, StorageConnector, TransformationConnector};
 
-use log::debug;
+use futures::future::join_all;
+pub use health::{HealthCheck, InfraHealth};
+use log::{debug, error};
 use pyo3::{exceptions::PyValueError, prelude::*};
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeMap, Deserialize, Serialize};
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
 
-use crate::{config::InfraConfig, SchemaProvider};
+use crate::{config::InfraConfig, SchemaProvider, SeResult};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+/// Identifier of an infra, written as a map from its type to its name, e.g. `{"Neo4j": "neo4j"}`,
+/// or `{"csv": "features"}` of an infra of a registered [ConnectorFactory](pi::ConnectorFactory).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum InfraIdentifier {
     Neo4j(String),
     Redis(String),
+    Plugin { infra_type: String, name: String },
+}
+
+impl InfraIdentifier {
+    /// Identifies infra `name` of `infra_type` of `ofnil.toml`
+    pub fn new(infra_type: &str, name: impl Into<String>) -> Self {
+        match infra_type {
+            "neo4j" => InfraIdentifier::Neo4j(name.into()),
+            "redis" => InfraIdentifier::Redis(name.into()),
+            _ => InfraIdentifier::Plugin {
+                infra_type: infra_type.to_string(),
+                name: name.into(),
+            },
+        }
+    }
+
+    fn of_config(name: &str, config: &InfraConfig) -> Self {
+        match config {
+            InfraConfig::Neo4jDatabaseProviderConfig { .. } => Self::new("neo4j", name),
+            InfraConfig::RedisClientConfig { .. } => Self::new("redis", name),
+            InfraConfig::Plugin { infra_type, .. } => Self::new(infra_type, name),
+        }
+    }
+
+    pub fn infra_type(&self) -> &str {
+        match self {
+            InfraIdentifier::Neo4j(_) => "neo4j",
+            InfraIdentifier::Redis(_) => "redis",
+            InfraIdentifier::Plugin { infra_type, .. } => infra_type,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            InfraIdentifier::Neo4j(name) | InfraIdentifier::Redis(name) => name,
+            InfraIdentifier::Plugin { name, .. } => name,
+        }
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            InfraIdentifier::Neo4j(_) => "Neo4j",
+            InfraIdentifier::Redis(_) => "Redis",
+            InfraIdentifier::Plugin { infra_type, .. } => infra_type,
+        }
+    }
+
+    fn from_key(key: String, name: String) -> Self {
+        match key.as_str() {
+            "Neo4j" => InfraIdentifier::Neo4j(name),
+            "Redis" => InfraIdentifier::Redis(name),
+            _ => InfraIdentifier::Plugin {
+                infra_type: key,
+                name,
+            },
+        }
+    }
+}
+
+impl Serialize for InfraIdentifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.key(), self.name())?;
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for InfraIdentifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = HashMap::<String, String>::deserialize(deserializer)?;
+        if map.len() != 1 {
+            return Err(serde::de::Error::invalid_length(
+                map.len(),
+                &"a map of the infra type to the name",
+            ));
+        }
+        let (key, name) = map.into_iter().next().unwrap();
+        Ok(Self::from_key(key, name))
+    }
 }
 
 impl IntoPy<PyObject> for InfraIdentifier {
@@ -41,34 +125,59 @@ impl FromPyObject<'_> for InfraIdentifier {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Infra {
     Neo4j(Neo4jConnector, Option<InfraConfig>),
     Redis(RedisConnector, Option<InfraConfig>),
+    /// Connector built by a [ConnectorFactory](pi::ConnectorFactory)
+    Plugin(Arc<dyn Connector>, Option<InfraConfig>),
 }
 
 impl Infra {
+    pub fn connector(&self) -> &dyn Connector {
+        match self {
+            Infra::Neo4j(connector, _) => connector,
+            Infra::Redis(connector, _) => connector,
+            Infra::Plugin(connector, _) => connector.as_ref(),
+        }
+    }
+
+    pub fn config(&self) -> Option<&InfraConfig> {
+        match self {
+            Infra::Neo4j(_, config) | Infra::Redis(_, config) | Infra::Plugin(_, config) => {
+                config.as_ref()
+            }
+        }
+    }
+
     pub fn get_uri(&self) -> String {
-        let config = match &self {
-            Infra::Neo4j(_, config) => config.as_ref().unwrap(),
-            Infra::Redis(_, config) => config.as_ref().unwrap(),
-        };
-        match config {
+        match self.config().unwrap() {
             InfraConfig::Neo4jDatabaseProviderConfig { uri, .. } => uri.clone(),
-            InfraConfig::RedisClientConfig { uri } => uri.clone(),
+            InfraConfig::RedisClientConfig { uri, .. } => uri.clone(),
+            InfraConfig::Plugin { properties, .. } => properties
+                .get("uri")
+                .and_then(|uri| uri.as_str())
+                .unwrap_or_default()
+                .to_string(),
         }
     }
 }
 
-#[derive(Default)]
+/// Infras of the feature store. Infras can be added, replaced and removed at runtime, and the infras
+/// of the config that cannot be connected are kept [unavailable](Self::unavailable) rather than
+/// failing the startup.
+#[derive(Default, Clone)]
 pub struct InfraManager {
     pub infras: HashMap<InfraIdentifier, Infra>,
+    /// Errors of connecting the infras that are configured but unavailable
+    pub unavailable: HashMap<InfraIdentifier, String>,
 }
 
 impl InfraManager {
     fn new() -> Self {
         Self {
             infras: HashMap::new(),
+            unavailable: HashMap::new(),
         }
     }
 
@@ -76,32 +185,12 @@ impl InfraManager {
         let mut infras = InfraManager::new();
         for (name, infra) in config {
             debug!("Creating infra: {} {:?}", name, infra);
-            match infra {
-                InfraConfig::Neo4jDatabaseProviderConfig {
-                    uri,
-                    username,
-                    password,
-                } => {
-                    infras.register_neo4j_connector(
-                        name,
-                        Neo4jConnector::new(
-                            uri.to_string(),
-                            username.to_string(),
-                            password.to_string(),
-                            None,
-                            Some(InfraIdentifier::Neo4j(name.to_owned())),
-                        )
-                        .await
-                        .unwrap(),
-                        Some(infra.clone()),
-                    );
-                }
-                InfraConfig::RedisClientConfig { uri } => {
-                    infras.register_redis_connector(
-                        name,
-                        RedisConnector::new(uri.to_string()),
-                        Some(infra.clone()),
-                    );
+            match Self::connect(name, infra).await {
+                Ok((infra_id, infra)) => infras.add_infra(infra_id, infra),
+                Err(e) => {
+                    let infra_id = InfraIdentifier::of_config(name, infra);
+                    error!("Infra {infra_id:?} is unavailable: {e}");
+                    infras.unavailable.insert(infra_id, e.to_string());
                 }
             }
         }
@@ -109,9 +198,85 @@ impl InfraManager {
         infras
     }
 
+    /// Connects infra `name` of `config`
+    pub async fn connect(name: &str, config: &InfraConfig) -> SeResult<(InfraIdentifier, Infra)> {
+        let infra_id = InfraIdentifier::of_config(name, config);
+        let infra = match config {
+            InfraConfig::Neo4jDatabaseProviderConfig {
+                uri,
+                username,
+                password,
+                options,
+            } => Infra::Neo4j(
+                Neo4jConnector::new(
+                    uri.to_string(),
+                    username.to_string(),
+                    password.to_string(),
+                    options,
+                    Some(infra_id.clone()),
+                )
+                .await?,
+                Some(config.clone()),
+            ),
+            InfraConfig::RedisClientConfig {
+                uri,
+                username,
+                password,
+                options,
+            } => Infra::Redis(
+                RedisConnector::with_options(
+                    uri.as_str(),
+                    username.clone(),
+                    password.clone(),
+                    options,
+                )?,
+                Some(config.clone()),
+            ),
+            InfraConfig::Plugin {
+                infra_type,
+                properties,
+            } => {
+                let factory = get_connector_factory(infra_type).ok_or_else(|| {
+                    format!("No connector factory of infra type {infra_type} of infra {name}")
+                })?;
+                let connector = factory.create(&infra_id, properties).await?;
+                Infra::Plugin(connector.into(), Some(config.clone()))
+            }
+        };
+        Ok((infra_id, infra))
+    }
+
+    /// Removes infra `infra_id`, whether it is available or not. Returns whether there was one.
+    pub fn remove_infra(&mut self, infra_id: &InfraIdentifier) -> bool {
+        debug!("Removing infra: {infra_id:?}");
+        let removed = self.infras.remove(infra_id).is_some();
+        self.unavailable.remove(infra_id).is_some() || removed
+    }
+
+    /// Probes the infras, see [Connector::health_checks]. Infras that could not be connected are
+    /// reported with their failed `connect` check.
+    pub async fn health(&self) -> Vec<InfraHealth> {
+        let checks = self.infras.iter().map(|(infra_id, infra)| async move {
+            InfraHealth::new(infra_id.clone(), infra.connector().health_checks().await)
+        });
+        let mut health = join_all(checks).await;
+        health.extend(self.unavailable.iter().map(|(infra_id, e)| {
+            InfraHealth::new(
+                infra_id.clone(),
+                vec![HealthCheck::new("connect", Err::<(), _>(e))],
+            )
+        }));
+        health.sort_by(|a, b| {
+            (a.infra_id.infra_type(), a.infra_id.name())
+                .cmp(&(b.infra_id.infra_type(), b.infra_id.name()))
+        });
+        health
+    }
+
     #[inline]
     pub fn add_infra(&mut self, infra_id: InfraIdentifier, infra: Infra) {
         debug!("Adding infra: {infra_id:?}");
+        self.unavailable.remove(&infra_id);
         self.infras.insert(infra_id, infra);
     }
 
@@ -121,40 +286,28 @@ impl InfraManager {
     }
 
     pub fn get_infra_config(&self, infra_id: &InfraIdentifier) -> Option<&InfraConfig> {
-        self.infras.get(infra_id).map(|infra| match infra {
-            Infra::Neo4j(_, Some(conf)) => conf,
-            Infra::Redis(_, Some(conf)) => conf,
-            _ => panic!("Cannot get conf"),
-        })
+        self.infras
+            .get(infra_id)
+            .map(|infra| infra.config().expect("Cannot get conf"))
     }
 
     pub fn get_storage_infra(&self, infra_id: &InfraIdentifier) -> Option<&dyn StorageConnector> {
-        match self.get_infra(infra_id) {
-            Some(Infra::Redis(connector, _)) => Some(connector),
-            Some(Infra::Neo4j(connector, _)) => Some(connector),
-            _ => None,
-        }
+        self.get_infra(infra_id)?.connector().as_storage()
     }
 
     pub fn get_storage_infra_cloned(
         &self,
         infra_id: &InfraIdentifier,
     ) -> Option<Box<dyn StorageConnector>> {
-        match self.get_infra(infra_id) {
-            Some(Infra::Redis(connector, _)) => Some(Box::new(connector.clone())),
-            Some(Infra::Neo4j(connector, _)) => Some(Box::new(connector.clone())),
-            _ => None,
-        }
+        self.get_infra(infra_id)?.connector().clone_storage()
     }
 
     #[cfg(feature = "dashboard")]
     pub fn get_graph_transformation_infra_ids(&self) -> Vec<InfraIdentifier> {
         self.infras
-            .keys()
-            .filter_map(|id| match id {
-                InfraIdentifier::Neo4j(_) => Some(id.clone()),
-                InfraIdentifier::Redis(_) => None,
-            })
+            .iter()
+            .filter(|(_, infra)| infra.connector().as_transformation().is_some())
+            .map(|(id, _)| id.clone())
             .collect()
     }
 
@@ -162,20 +315,14 @@ impl InfraManager {
         &self,
         infra_id: &InfraIdentifier,
     ) -> Option<&dyn TransformationConnector> {
-        match self.get_infra(infra_id) {
-            Some(Infra::Neo4j(connector, _)) => Some(connector),
-            _ => None,
-        }
+        self.get_infra(infra_id)?.connector().as_transformation()
     }
 
     pub fn get_graph_transformation_infra_cloned(
         &self,
         infra_id: &InfraIdentifier,
     ) -> Option<Box<dyn TransformationConnector>> {
-        match self.get_infra(infra_id) {
-            Some(Infra::Neo4j(connector, _)) => Some(Box::new(connector.clone())),
-            _ => None,
-        }
+        self.get_infra(infra_id)?.connector().clone_transformation()
     }
 
     pub fn register_neo4j_connector(
@@ -216,6 +363,16 @@ impl InfraManager {
         }
     }
 
+    pub fn get_local_file_connector(
+        &self,
+        infra_id_name: impl Into<String>,
+    ) -> Option<&LocalFileConnector> {
+        self.get_infra(&InfraIdentifier::new("local_file", infra_id_name))?
+            .connector()
+            .as_any()?
+            .downcast_ref()
+    }
+
     pub fn get_infra_info(&self) -> Vec<(InfraIdentifier, String)> {
         self.infras
             .iter()
@@ -223,16 +380,32 @@ impl InfraManager {
             .collect()
     }
 
-    pub fn get_schema_provider(&self, infra_id: &InfraIdentifier) -> Arc<dyn SchemaProvider> {
-        match infra_id {
-            InfraIdentifier::Neo4j(neo4j_id) => {
-                self.get_neo4j_connector(neo4j_id).unwrap().get_database()
-            }
-            InfraIdentifier::Redis(_) => {
-                // TODO(Pond): create schema provider for redis
-                panic!("Not implemented");
+    /// Finds the stale graph projections of all Neo4j infras, and drops them unless `dry_run`
+    /// is set, see [Neo4jConnector::collect_stale_projections].
+    pub async fn collect_stale_projections(
+        &self,
+        max_age: chrono::Duration,
+        dry_run: bool,
+    ) -> SeResult<Vec<(InfraIdentifier, StaleProjection)>> {
+        let mut stale = Vec::new();
+        for (infra_id, infra) in &self.infras {
+            if let Infra::Neo4j(connector, _) = infra {
+                for projection in connector
+                    .collect_stale_projections(max_age, dry_run)
+                    .await?
+                {
+                    stale.push((infra_id.clone(), projection));
+                }
             }
         }
+        Ok(stale)
+    }
+
+    pub fn get_schema_provider(&self, infra_id: &InfraIdentifier) -> Arc<dyn SchemaProvider> {
+        // TODO(Pond): create schema provider for redis
+        self.get_infra(infra_id)
+            .and_then(|infra| infra.connector().schema_provider())
+            .unwrap_or_else(|| panic!("No schema provider of infra {infra_id:?}"))
     }
 }
 
@@ -249,7 +422,7 @@ async fn test_infra_manager_bolt_conn() {
             "localhost:7687",
             "neo4j",
             "ofnil",
-            None,
+            &Default::default(),
             Some(InfraIdentifier::Neo4j("neo4j_1".to_string())),
         )
         .await
@@ -277,3 +450,147 @@ async fn test_infra_manager_bolt_conn() {
     assert!(Success::try_from(msg).is_ok(), "PULL failure");
     println!("records: {records:?}");
 }
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_connector_factory() -> SeResult<()> {
+    use pi::{storage::Storage, ConnectorFactory, Sinkable, Sourceable};
+
+    #[derive(Debug, Clone)]
+    struct FileConnector;
+
+    impl Sinkable for FileConnector {
+        fn get_supported_sources(&self) -> Vec<Storage> {
+            vec![Storage::OfnilRow]
+        }
+    }
+
+    impl Sourceable for FileConnector {
+        fn get_supported_sinks(&self) -> Vec<Storage> {
+            vec![Storage::OfnilRow]
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Connector for FileConnector {
+        fn as_storage(&self) -> Option<&dyn StorageConnector> {
+            Some(self)
+        }
+
+        async fn health_checks(&self) -> Vec<HealthCheck> {
+            vec![HealthCheck::new("exists", Ok::<_, String>(()))]
+        }
+    }
+
+    struct FileConnectorFactory;
+
+    #[async_trait::async_trait]
+    impl ConnectorFactory for FileConnectorFactory {
+        fn infra_type(&self) -> &str {
+            "test_file"
+        }
+
+        async fn create(
+            &self,
+            _infra_id: &InfraIdentifier,
+            properties: &toml::Table,
+        ) -> SeResult<Box<dyn Connector>> {
+            properties.get("path").ok_or("Missing path")?;
+            Ok(Box::new(FileConnector))
+        }
+    }
+
+    pi::plugin::register_connector_factory(FileConnectorFactory);
+    let config = HashMap::from([(
+        "files".to_string(),
+        InfraConfig::Plugin {
+            infra_type: "test_file".to_string(),
+            properties: toml::toml! { uri = "file:///tmp/features" path = "/tmp/features" },
+        },
+    )]);
+    let infra_manager = InfraManager::from_config(&config).await;
+    let infra_id = InfraIdentifier::new("test_file", "files");
+    assert!(infra_manager.get_storage_infra(&infra_id).is_some());
+    assert!(infra_manager
+        .get_graph_transformation_infra(&infra_id)
+        .is_none());
+    assert_eq!(
+        infra_manager.get_infra_info(),
+        vec![(infra_id.clone(), "file:///tmp/features".to_string())]
+    );
+    assert!(matches!(
+        infra_manager.infras.get(&infra_id),
+        Some(Infra::Plugin(_, Some(InfraConfig::Plugin { .. })))
+    ));
+
+    let json = serde_json::to_string(&infra_id)?;
+    assert_eq!(json, r#"{"test_file":"files"}"#);
+    assert_eq!(serde_json::from_str::<InfraIdentifier>(&json)?, infra_id);
+    assert_eq!(
+        serde_json::from_str::<InfraIdentifier>(r#"{"Neo4j":"neo4j"}"#)?,
+        InfraIdentifier::Neo4j("neo4j".to_string())
+    );
+
+    let config = HashMap::from([(
+        "cache".to_string(),
+        InfraConfig::Plugin {
+            infra_type: "test_unregistered".to_string(),
+            properties: toml::Table::new(),
+        },
+    )]);
+    let mut infra_manager = InfraManager::from_config(&config).await;
+    assert!(infra_manager.infras.is_empty());
+    let (files_id, files) = InfraManager::connect(
+        "files",
+        &InfraConfig::Plugin {
+            infra_type: "test_file".to_string(),
+            properties: toml::toml! { path = "/tmp/features" },
+        },
+    )
+    .await?;
+    infra_manager.add_infra(files_id, files);
+    let health = infra_manager.health().await;
+    assert_eq!(health.len(), 2);
+    assert_eq!(
+        health[0].infra_id,
+        InfraIdentifier::new("test_file", "files")
+    );
+    assert!(health[0].available);
+    assert_eq!(
+        health[1].infra_id,
+        InfraIdentifier::new("test_unregistered", "cache")
+    );
+    assert!(!health[1].available);
+    assert_eq!(health[1].checks[0].name, "connect");
+
+    assert!(infra_manager.remove_infra(&health[1].infra_id));
+    assert!(!infra_manager.remove_infra(&health[1].infra_id));
+    assert_eq!(infra_manager.health().await.len(), 1);
+    Ok(())
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_local_file_infra() -> SeResult<()> {
+    let dir = std::env::temp_dir().join(format!("ofnil_local_file_infra_{}", std::process::id()));
+    let config = HashMap::from([(
+        "offline".to_string(),
+        InfraConfig::Plugin {
+            infra_type: "local_file".to_string(),
+            properties: toml::toml! { header = false }
+                .into_iter()
+                .chain([("uri".to_string(), dir.display().to_string().into())])
+                .collect(),
+        },
+    )]);
+    let infra_manager = InfraManager::from_config(&config).await;
+    let infra_id = InfraIdentifier::new("local_file", "offline");
+    assert!(infra_manager.get_storage_infra(&infra_id).is_some());
+    assert!(infra_manager.get_local_file_connector("offline").is_some());
+    assert_eq!(
+        serde_json::to_string(&infra_id)?,
+        r#"{"local_file":"offline"}"#
+    );
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
@@ -1,8 +1,11 @@
+pub mod file;
 pub mod gdb;
 pub mod kv;
 
 pub use gdb::identifier_map::IdentifierMap;
 
 // re-export connector implementations at `crate::connectors` level
-pub use gdb::neo4j::Neo4jConnector;
+pub use file::local::{LocalFileConnector, LocalFileConnectorFactory};
+pub use gdb::embedded::{EmbeddedGraphConnector, EmbeddedGraphConnectorFactory};
+pub use gdb::neo4j::{Neo4jConnector, StaleProjection};
 pub use kv::redis::RedisConnector;
@@ -0,0 +1,462 @@
+use std::{any::Any, path::PathBuf};
+
+use crate::{
+    infra::{
+        health::HealthCheck,
+        pi::{storage::*, Connector, ConnectorFactory, Sinkable, Sourceable, StorageConnector},
+    },
+    InfraIdentifier, SeResult,
+};
+
+// submodules
+mod csv_rows;
+mod parquet_rows;
+mod topology_files;
+use csv_rows::*;
+use parquet_rows::*;
+
+/// Connector of the files of a directory of the local file system, e.g. an offline store of
+/// transformation outputs. The rows of each vertex or edge type are kept in file
+/// `{label}.{format}` of the directory. A row of a vertex type has the vertex id followed by the
+/// fields, and a row of an edge type has the src and dst vertex ids followed by the edge fields.
+/// Topologies are exported to directories of binary files, see
+/// [write_topology](LocalFileConnector::write_topology).
+#[derive(Debug, Clone)]
+pub struct LocalFileConnector {
+    root: PathBuf,
+    format: FileFormat,
+    /// Whether the CSV files start with a header of the column names, `id` or `src` and `dst`
+    /// followed by the field names. Parquet files always name their columns.
+    header: bool,
+}
+
+impl LocalFileConnector {
+    /// Creates a connector of directory `root`, which is created if it does not exist.
+    /// The rows are kept in [FileFormat::CSV] or [FileFormat::Parquet] files.
+    pub fn new(root: impl Into<PathBuf>, format: FileFormat, header: bool) -> SeResult<Self> {
+        if !matches!(format, FileFormat::CSV | FileFormat::Parquet) {
+            return Err(format!("{format:?} files of rows are not supported").into());
+        }
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            format,
+            header,
+        })
+    }
+
+    pub fn get_storage(&self) -> Storage {
+        Storage::File {
+            fs: FileSystemIdentifier::Local,
+            format: self.format.clone(),
+        }
+    }
+
+    /// Gets the file of the rows of `schema`
+    pub fn get_path(&self, schema: &Schema) -> SeResult<PathBuf> {
+        let label = match schema {
+            Schema::Tabular(schema) => schema.tlabel.as_ref(),
+            Schema::Edge(schema) => schema.edge_info.tlabel.as_ref(),
+        }
+        .ok_or_else(|| format!("Cannot name the file of rows without a label. {schema:?}"))?;
+        let extension = format!("{:?}", self.format).to_lowercase();
+        Ok(self.root.join(format!("{label}.{extension}")))
+    }
+}
+
+impl Sinkable for LocalFileConnector {
+    fn get_supported_sources(&self) -> Vec<Storage> {
+        vec![Storage::OfnilRow]
+    }
+
+    fn insert_rows(&self, type_info: Schema) -> Box<dyn Sink<Row>> {
+        match self.format {
+            FileFormat::Parquet => Box::new(ParquetRowSink::new(self.clone(), type_info)),
+            _ => Box::new(CsvRowSink::new(self.clone(), type_info)),
+        }
+    }
+}
+
+impl Sourceable for LocalFileConnector {
+    fn get_supported_sinks(&self) -> Vec<Storage> {
+        vec![Storage::OfnilRow]
+    }
+
+    /// Reads the rows of `type_info` from its file
+    fn produce_rows(&self, type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+        Ok(match self.format {
+            FileFormat::Parquet => Box::new(ParquetRowSource::new(self.clone(), type_info)),
+            _ => Box::new(CsvRowSource::new(self.clone(), type_info)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for LocalFileConnector {
+    fn as_storage(&self) -> Option<&dyn StorageConnector> {
+        Some(self)
+    }
+
+    fn clone_storage(&self) -> Option<Box<dyn StorageConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    async fn health_checks(&self) -> Vec<HealthCheck> {
+        let directory = match std::fs::metadata(&self.root) {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(format!("{} is not a directory", self.root.display())),
+            Err(e) => Err(e.to_string()),
+        };
+        vec![HealthCheck::new("directory", directory)]
+    }
+}
+
+/// Builds the connectors of the `local_file` infras. `uri` is the directory of the files, as a
+/// path or a `file://` URI, and `format` (`csv` by default, or `parquet`) and `header` (`true` by
+/// default) are optional.
+pub struct LocalFileConnectorFactory;
+
+#[async_trait::async_trait]
+impl ConnectorFactory for LocalFileConnectorFactory {
+    fn infra_type(&self) -> &str {
+        "local_file"
+    }
+
+    async fn create(
+        &self,
+        infra_id: &InfraIdentifier,
+        properties: &toml::Table,
+    ) -> SeResult<Box<dyn Connector>> {
+        let name = infra_id.name();
+        let uri = properties
+            .get("uri")
+            .and_then(toml::Value::as_str)
+            .ok_or_else(|| format!("Missing uri of infra {name}"))?;
+        let format = match properties.get("format").map(toml::Value::as_str) {
+            None | Some(Some("csv")) => FileFormat::CSV,
+            Some(Some("parquet")) => FileFormat::Parquet,
+            Some(format) => {
+                return Err(format!("Unsupported file format {format:?} of infra {name}").into())
+            }
+        };
+        let header = match properties.get("header") {
+            None => true,
+            Some(header) => header
+                .as_bool()
+                .ok_or_else(|| format!("Invalid header {header} of infra {name}"))?,
+        };
+        let root = uri.strip_prefix("file://").unwrap_or(uri);
+        Ok(Box::new(LocalFileConnector::new(root, format, header)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{transformation::utils::transport_source_to_sink, FeatureValueType};
+
+    struct RowCollector(Vec<Row>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Collector<Row> for RowCollector {
+        async fn collect(&mut self, record: Row) -> SeResult<()> {
+            self.0.push(record);
+            Ok(())
+        }
+    }
+
+    async fn read_all(source: &dyn Source<Row>) -> SeResult<Vec<Row>> {
+        let mut reader = source.create_reader().await?;
+        let mut rows = RowCollector(Vec::new());
+        while reader.next(&mut rows).await? {}
+        Ok(rows.0)
+    }
+
+    async fn write_all(
+        connector: &LocalFileConnector,
+        schema: &Schema,
+        rows: Vec<Row>,
+    ) -> SeResult<()> {
+        let sink = connector.insert_rows(schema.clone());
+        let mut writer = sink.create_write_collector().await?;
+        for row in rows {
+            writer.collect(row).await?;
+        }
+        writer.finish().await
+    }
+
+    #[tokio::test]
+    async fn test_csv_rows() -> SeResult<()> {
+        let dir = std::env::temp_dir().join(format!("ofnil_local_file_{}", std::process::id()));
+        let vertex_schema = Schema::Tabular(TabularSchema {
+            field_names: vec!["rank".to_string(), "tags".to_string()],
+            field_types: vec![
+                FeatureValueType::Float,
+                FeatureValueType::Array(Box::new(FeatureValueType::Int)),
+            ],
+            tlabel: Some("Reviewer".to_string()),
+            primary_key: None,
+        });
+        let edge_schema = Schema::Edge(EdgeSchema {
+            src_vertex_tlabel: "Reviewer".to_string(),
+            dst_vertex_tlabel: "Product".to_string(),
+            src_vertex_primary_key: "reviewerID".to_string(),
+            dst_vertex_primary_key: "asin".to_string(),
+            directed: true,
+            edge_info: TabularSchema {
+                field_names: vec!["rating".to_string()],
+                field_types: vec![FeatureValueType::Int],
+                tlabel: Some("rates".to_string()),
+                primary_key: None,
+            },
+        });
+        let vertex_rows = || {
+            vec![
+                Row::new(vec![
+                    RowCell::String("r1".to_string()),
+                    RowCell::Double(0.5),
+                    RowCell::Array(vec![RowCell::Int(1), RowCell::Int(2)]),
+                ]),
+                Row::new(vec![
+                    RowCell::String("r2".to_string()),
+                    RowCell::Null,
+                    RowCell::Array(vec![RowCell::Int(3)]),
+                ]),
+            ]
+        };
+
+        for header in [true, false] {
+            let connector = LocalFileConnector::new(&dir, FileFormat::CSV, header)?;
+            let sink = connector.insert_rows(vertex_schema.clone());
+            let mut writer = sink.create_write_collector().await?;
+            for row in vertex_rows() {
+                writer.collect(row).await?;
+            }
+            writer.finish().await?;
+            assert_eq!(
+                connector.get_path(&vertex_schema)?,
+                dir.join("Reviewer.csv")
+            );
+            let source = connector.produce_rows(vertex_schema.clone())?;
+            assert_eq!(read_all(source.as_ref()).await?, vertex_rows());
+
+            // copy the rows of the file to the file of another label
+            let rows = connector.produce_rows(vertex_schema.clone())?;
+            let copy = Schema::Tabular(TabularSchema {
+                tlabel: Some("Copy".to_string()),
+                ..vertex_schema.clone().into_tabular()
+            });
+            transport_source_to_sink(rows.as_ref(), connector.insert_rows(copy.clone()).as_ref())
+                .await?;
+            let source = connector.produce_rows(copy)?;
+            assert_eq!(read_all(source.as_ref()).await?, vertex_rows());
+        }
+
+        let connector = LocalFileConnector::new(&dir, FileFormat::CSV, true)?;
+        std::fs::write(dir.join("rates.csv"), "rating,dst,src\n5,p1,r1\n,p2,r2\n")?;
+        let source = connector.produce_rows(edge_schema)?;
+        assert_eq!(
+            read_all(source.as_ref()).await?,
+            vec![
+                Row::new(vec![
+                    RowCell::String("r1".to_string()),
+                    RowCell::String("p1".to_string()),
+                    RowCell::Int(5),
+                ]),
+                Row::new(vec![
+                    RowCell::String("r2".to_string()),
+                    RowCell::String("p2".to_string()),
+                    RowCell::Null,
+                ]),
+            ]
+        );
+        assert!(connector.health_checks().await[0].passed());
+
+        // topology of ids of the mappings of the vertex keys
+        let reviewers = IdMapping::from_keys(["r1", "r2"].map(String::from));
+        let products = IdMapping::from_keys(["p1", "p2", "p3"].map(String::from));
+        let edges = [("r2", "p3", 7), ("r1", "p1", 8), ("r2", "p1", 9)]
+            .map(|(src, dst, id)| (src.to_string(), dst.to_string(), Some(id)));
+        let coo = CooTopology::from_edges(edges, &reviewers, &products, true)?;
+        let path = connector.write_topology(
+            "u2i",
+            ("Reviewer", &reviewers),
+            ("Product", &products),
+            &coo,
+            &[FileFormat::CSR, FileFormat::COO],
+        )?;
+        assert_eq!(path, dir.join("u2i"));
+        assert_eq!(connector.read_coo("u2i")?, coo);
+        assert_eq!(
+            connector.read_csr("u2i")?,
+            CsrTopology::from_coo(&coo, reviewers.len())
+        );
+        assert_eq!(connector.read_id_mapping("u2i", "Product")?, products);
+        assert!(connector
+            .write_topology(
+                "u2i",
+                ("Reviewer", &reviewers),
+                ("Product", &products),
+                &coo,
+                &[FileFormat::Parquet],
+            )
+            .is_err());
+        assert!(LocalFileConnector::new(&dir, FileFormat::CSR, true).is_err());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_csv_rows_round_trip() -> SeResult<()> {
+        let dir = std::env::temp_dir().join(format!("ofnil_csv_round_trip_{}", std::process::id()));
+        let connector = LocalFileConnector::new(&dir, FileFormat::CSV, true)?;
+        let schema = Schema::Tabular(TabularSchema {
+            field_names: vec!["name".to_string(), "tags".to_string()],
+            field_types: vec![
+                FeatureValueType::String,
+                FeatureValueType::Array(Box::new(FeatureValueType::String)),
+            ],
+            tlabel: Some("Product".to_string()),
+            primary_key: None,
+        });
+        let rows = || {
+            let tags = |tags: &[&str]| {
+                RowCell::Array(
+                    tags.iter()
+                        .map(|tag| RowCell::String(tag.to_string()))
+                        .collect(),
+                )
+            };
+            vec![
+                Row::new(vec![
+                    RowCell::String("p1".to_string()),
+                    RowCell::String(String::new()),
+                    tags(&["a,b", "", "\"c\""]),
+                ]),
+                Row::new(vec![
+                    RowCell::String("p2".to_string()),
+                    RowCell::String("\"quoted\", with a comma".to_string()),
+                    RowCell::Array(vec![RowCell::Null, RowCell::String("d".to_string())]),
+                ]),
+                Row::new(vec![
+                    RowCell::String("p3".to_string()),
+                    RowCell::Null,
+                    RowCell::Null,
+                ]),
+                Row::new(vec![
+                    RowCell::String("p4".to_string()),
+                    RowCell::String("plain".to_string()),
+                    tags(&[]),
+                ]),
+            ]
+        };
+        let sink = connector.insert_rows(schema.clone());
+        let mut writer = sink.create_write_collector().await?;
+        for row in rows() {
+            writer.collect(row).await?;
+        }
+        writer.finish().await?;
+        let source = connector.produce_rows(schema)?;
+        assert_eq!(read_all(source.as_ref()).await?, rows());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_parquet_rows_round_trip() -> SeResult<()> {
+        let dir =
+            std::env::temp_dir().join(format!("ofnil_parquet_round_trip_{}", std::process::id()));
+        let connector = LocalFileConnector::new(&dir, FileFormat::Parquet, false)?;
+        let vertex_schema = Schema::Tabular(TabularSchema {
+            field_names: vec![
+                "rank".to_string(),
+                "tags".to_string(),
+                "verified".to_string(),
+            ],
+            field_types: vec![
+                FeatureValueType::Float,
+                FeatureValueType::Array(Box::new(FeatureValueType::String)),
+                FeatureValueType::Boolean,
+            ],
+            tlabel: Some("Reviewer".to_string()),
+            primary_key: None,
+        });
+        let vertex_rows = || {
+            vec![
+                Row::new(vec![
+                    RowCell::String("r1".to_string()),
+                    RowCell::Double(0.5),
+                    RowCell::Array(vec![RowCell::String("a,b".to_string()), RowCell::Null]),
+                    RowCell::Boolean(true),
+                ]),
+                Row::new(vec![
+                    RowCell::String("r2".to_string()),
+                    RowCell::Null,
+                    RowCell::Array(vec![]),
+                    RowCell::Null,
+                ]),
+            ]
+        };
+        let edge_schema = Schema::Edge(EdgeSchema {
+            src_vertex_tlabel: "Reviewer".to_string(),
+            dst_vertex_tlabel: "Product".to_string(),
+            src_vertex_primary_key: "reviewerID".to_string(),
+            dst_vertex_primary_key: "asin".to_string(),
+            directed: true,
+            edge_info: TabularSchema {
+                field_names: vec!["rating".to_string()],
+                field_types: vec![FeatureValueType::Int],
+                tlabel: Some("rates".to_string()),
+                primary_key: None,
+            },
+        });
+        let edge_rows = || {
+            vec![
+                Row::new(vec![
+                    RowCell::String("r1".to_string()),
+                    RowCell::String("p1".to_string()),
+                    RowCell::Int(5),
+                ]),
+                Row::new(vec![
+                    RowCell::String("r2".to_string()),
+                    RowCell::String("p2".to_string()),
+                    RowCell::Null,
+                ]),
+            ]
+        };
+
+        write_all(&connector, &vertex_schema, vertex_rows()).await?;
+        let source = connector.produce_rows(vertex_schema)?;
+        assert_eq!(read_all(source.as_ref()).await?, vertex_rows());
+        write_all(&connector, &edge_schema, edge_rows()).await?;
+        let source = connector.produce_rows(edge_schema)?;
+        assert_eq!(read_all(source.as_ref()).await?, edge_rows());
+        assert!(dir.join("Reviewer.parquet").exists());
+        assert!(dir.join("rates.parquet").exists());
+
+        // columns are read by name
+        let reordered = Schema::Tabular(TabularSchema {
+            field_names: vec!["verified".to_string()],
+            field_types: vec![FeatureValueType::Boolean],
+            tlabel: Some("Reviewer".to_string()),
+            primary_key: None,
+        });
+        let source = connector.produce_rows(reordered)?;
+        assert_eq!(
+            read_all(source.as_ref()).await?,
+            vec![
+                Row::new(vec![
+                    RowCell::String("r1".to_string()),
+                    RowCell::Boolean(true)
+                ]),
+                Row::new(vec![RowCell::String("r2".to_string()), RowCell::Null]),
+            ]
+        );
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use super::LocalFileConnector;
+use crate::{infra::pi::storage::*, FeatureValueType, SeResult};
+
+const READ_BATCH_SIZE: usize = 1024;
+
+#[derive(Debug)]
+pub(super) struct CsvRowSink {
+    connector: LocalFileConnector,
+    type_info: Schema,
+}
+
+impl CsvRowSink {
+    pub(super) fn new(connector: LocalFileConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Sink<Row> for CsvRowSink {
+    /// Overwrites the file of the rows
+    async fn create_writer(&self) -> SeResult<Box<dyn Writer<Row> + '_>> {
+        let mut writer = csv::Writer::from_path(self.connector.get_path(&self.type_info)?)?;
+        let columns = self.type_info.columns();
+        if self.connector.header {
+            writer.write_record(columns.iter().map(|(name, _)| name))?;
+        }
+        Ok(Box::new(CsvRowWriter {
+            writer,
+            num_columns: columns.len(),
+        }))
+    }
+}
+
+pub(super) struct CsvRowWriter {
+    writer: csv::Writer<std::fs::File>,
+    num_columns: usize,
+}
+
+#[async_trait::async_trait(?Send)]
+impl Writer<Row> for CsvRowWriter {
+    async fn write(&mut self, record: Row) -> SeResult<()> {
+        if record.len() != self.num_columns {
+            return Err(format!(
+                "Expect a row of {} columns, but got {record:?}",
+                self.num_columns
+            )
+            .into());
+        }
+        self.writer
+            .write_record((0..record.len()).map(|idx| record.get(idx).encode()))?;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> SeResult<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct CsvRowSource {
+    connector: LocalFileConnector,
+    type_info: Schema,
+}
+
+impl CsvRowSource {
+    pub(super) fn new(connector: LocalFileConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source<Row> for CsvRowSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<Row> + '_>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(self.connector.header)
+            .from_path(self.connector.get_path(&self.type_info)?)?;
+        let columns = self.type_info.columns();
+        // columns are found by name if the file has a header, or else by position
+        let indices = if self.connector.header {
+            let headers = reader.headers()?;
+            columns
+                .iter()
+                .map(|(name, _)| {
+                    headers
+                        .iter()
+                        .position(|header| header == name)
+                        .ok_or_else(|| format!("Missing column {name} in header {headers:?}"))
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            (0..columns.len()).collect()
+        };
+        Ok(Box::new(CsvRowReader {
+            reader,
+            columns: columns
+                .into_iter()
+                .map(|(_, value_type)| value_type)
+                .collect(),
+            indices,
+        }))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.type_info
+    }
+}
+
+pub(super) struct CsvRowReader {
+    reader: csv::Reader<std::fs::File>,
+    /// Types of the columns of the rows
+    columns: Vec<FeatureValueType>,
+    /// Index of each column of the rows in the file records
+    indices: Vec<usize>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<Row> for CsvRowReader {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        let mut record = csv::StringRecord::new();
+        for _ in 0..READ_BATCH_SIZE {
+            if !self.reader.read_record(&mut record)? {
+                return Ok(false);
+            }
+            let cells = self
+                .indices
+                .iter()
+                .zip(&self.columns)
+                .map(|(idx, value_type)| RowCell::parse(record.get(*idx).unwrap_or(""), value_type))
+                .collect::<SeResult<_>>()?;
+            output.collect(Row::new(cells)).await?;
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs::File;
+
+use arrow::record_batch::RecordBatchReader;
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReader, ArrowWriter};
+
+use super::LocalFileConnector;
+use crate::{infra::pi::storage::*, SeResult};
+
+const READ_BATCH_SIZE: usize = 1024;
+const WRITE_BATCH_SIZE: usize = 1024;
+
+#[derive(Debug)]
+pub(super) struct ParquetRowSink {
+    connector: LocalFileConnector,
+    type_info: Schema,
+}
+
+impl ParquetRowSink {
+    pub(super) fn new(connector: LocalFileConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Sink<Row> for ParquetRowSink {
+    /// Overwrites the file of the rows, of a column per column of [Schema::columns]
+    async fn create_writer(&self) -> SeResult<Box<dyn Writer<Row> + '_>> {
+        let file = File::create(self.connector.get_path(&self.type_info)?)?;
+        let writer = ArrowWriter::try_new(file, self.type_info.arrow_schema(), None)?;
+        Ok(Box::new(ParquetRowWriter {
+            writer: Some(writer),
+            type_info: &self.type_info,
+            rows: Vec::new(),
+        }))
+    }
+}
+
+pub(super) struct ParquetRowWriter<'a> {
+    /// Taken when the file is closed by [Writer::finish]
+    writer: Option<ArrowWriter<File>>,
+    type_info: &'a Schema,
+    /// Rows that are not written yet
+    rows: Vec<Row>,
+}
+
+impl ParquetRowWriter<'_> {
+    fn flush(&mut self) -> SeResult<()> {
+        let writer = self.writer.as_mut().ok_or("Writer is already finished")?;
+        if !self.rows.is_empty() {
+            writer.write(&rows_to_batch(&self.rows, self.type_info)?)?;
+            self.rows.clear();
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Writer<Row> for ParquetRowWriter<'_> {
+    async fn write(&mut self, record: Row) -> SeResult<()> {
+        self.rows.push(record);
+        if self.rows.len() >= WRITE_BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> SeResult<()> {
+        self.flush()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct ParquetRowSource {
+    connector: LocalFileConnector,
+    type_info: Schema,
+}
+
+impl ParquetRowSource {
+    pub(super) fn new(connector: LocalFileConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source<Row> for ParquetRowSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<Row> + '_>> {
+        let file = File::open(self.connector.get_path(&self.type_info)?)?;
+        let reader = ParquetRecordBatchReader::try_new(file, READ_BATCH_SIZE)?;
+        // columns are found by name, as Parquet files always name their columns
+        let file_schema = reader.schema();
+        let indices = self
+            .type_info
+            .columns()
+            .iter()
+            .map(|(name, _)| {
+                file_schema
+                    .index_of(name)
+                    .map_err(|_| format!("Missing column {name} in schema {file_schema:?}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ParquetRowReader {
+            reader,
+            type_info: &self.type_info,
+            indices,
+        }))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.type_info
+    }
+}
+
+pub(super) struct ParquetRowReader<'a> {
+    reader: ParquetRecordBatchReader,
+    type_info: &'a Schema,
+    /// Index of each column of the rows in the file batches
+    indices: Vec<usize>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<Row> for ParquetRowReader<'_> {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        let Some(batch) = self.reader.next() else {
+            return Ok(false);
+        };
+        let batch = batch?.project(&self.indices)?;
+        for row in batch_to_rows(&batch, self.type_info)? {
+            output.collect(row).await?;
+        }
+        Ok(true)
+    }
+}
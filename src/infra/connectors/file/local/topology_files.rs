@@ -0,0 +1,132 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::LocalFileConnector;
+use crate::{infra::pi::storage::*, SeResult};
+
+fn write_u64s(path: &Path, values: &[u64]) -> SeResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(writer.flush()?)
+}
+
+fn read_u64s(path: &Path) -> SeResult<Vec<u64>> {
+    let mut bytes = Vec::new();
+    BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+    if bytes.len() % 8 != 0 {
+        return Err(format!("{} is not an array of u64", path.display()).into());
+    }
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+/// Reads the edge ids of `path` if they are exported
+fn read_edge_ids(path: PathBuf) -> SeResult<Option<Vec<u64>>> {
+    path.exists().then(|| read_u64s(&path)).transpose()
+}
+
+impl LocalFileConnector {
+    /// Gets the directory of the files of topology `name`
+    pub fn get_topology_path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Writes topology `name` of the vertices of labels `src` and `dst` to directory
+    /// [get_topology_path](Self::get_topology_path), in each of `formats`. The directory has
+    /// - `{tlabel}.ids.csv`, the key of vertex id `i` of the label on line `i`
+    /// - for [FileFormat::CSR], `csr_indptr.bin`, `csr_indices.bin` and `csr_edge_ids.bin`
+    /// - for [FileFormat::COO], `coo_src.bin`, `coo_dst.bin` and `coo_edge_ids.bin`
+    ///
+    /// The `.bin` files are arrays of little-endian u64, and the edge ids files are only written
+    /// if `coo` has edge ids.
+    pub fn write_topology(
+        &self,
+        name: &str,
+        src: (&str, &IdMapping),
+        dst: (&str, &IdMapping),
+        coo: &CooTopology,
+        formats: &[FileFormat],
+    ) -> SeResult<PathBuf> {
+        let dir = self.get_topology_path(name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        for (tlabel, mapping) in [src, dst] {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_path(dir.join(format!("{tlabel}.ids.csv")))?;
+            for key in mapping.keys() {
+                writer.write_record([key])?;
+            }
+            writer.flush()?;
+        }
+        for format in formats {
+            let files = match format {
+                FileFormat::CSR => {
+                    let csr = CsrTopology::from_coo(coo, src.1.len());
+                    [("csr_indptr", csr.indptr), ("csr_indices", csr.indices)]
+                        .into_iter()
+                        .chain(csr.edge_ids.map(|edge_ids| ("csr_edge_ids", edge_ids)))
+                        .collect::<Vec<_>>()
+                }
+                FileFormat::COO => [("coo_src", &coo.src), ("coo_dst", &coo.dst)]
+                    .into_iter()
+                    .chain(
+                        coo.edge_ids
+                            .as_ref()
+                            .map(|edge_ids| ("coo_edge_ids", edge_ids)),
+                    )
+                    .map(|(file, values)| (file, values.clone()))
+                    .collect(),
+                _ => return Err(format!("Cannot export a topology to {format:?} files").into()),
+            };
+            for (file, values) in files {
+                write_u64s(&dir.join(format!("{file}.bin")), &values)?;
+            }
+        }
+        Ok(dir)
+    }
+
+    /// Reads the id mapping of the vertices of `tlabel` of topology `name`
+    pub fn read_id_mapping(&self, name: &str, tlabel: &str) -> SeResult<IdMapping> {
+        let path = self
+            .get_topology_path(name)
+            .join(format!("{tlabel}.ids.csv"));
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(path)?;
+        let mut keys = Vec::new();
+        for record in reader.records() {
+            keys.push(record?.get(0).unwrap_or_default().to_string());
+        }
+        Ok(IdMapping::from_keys(keys))
+    }
+
+    /// Reads the [FileFormat::CSR] files of topology `name`
+    pub fn read_csr(&self, name: &str) -> SeResult<CsrTopology> {
+        let dir = self.get_topology_path(name);
+        Ok(CsrTopology {
+            indptr: read_u64s(&dir.join("csr_indptr.bin"))?,
+            indices: read_u64s(&dir.join("csr_indices.bin"))?,
+            edge_ids: read_edge_ids(dir.join("csr_edge_ids.bin"))?,
+        })
+    }
+
+    /// Reads the [FileFormat::COO] files of topology `name`
+    pub fn read_coo(&self, name: &str) -> SeResult<CooTopology> {
+        let dir = self.get_topology_path(name);
+        Ok(CooTopology {
+            src: read_u64s(&dir.join("coo_src.bin"))?,
+            dst: read_u64s(&dir.join("coo_dst.bin"))?,
+            edge_ids: read_edge_ids(dir.join("coo_edge_ids.bin"))?,
+        })
+    }
+}
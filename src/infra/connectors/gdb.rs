@@ -2,4 +2,5 @@
 pub mod identifier_map;
 
 // connector implementations
+pub mod embedded;
 pub mod neo4j;
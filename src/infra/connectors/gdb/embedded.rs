@@ -0,0 +1,543 @@
+mod algorithms;
+mod executor;
+mod in_memory_graph;
+mod tables;
+
+use std::{
+    any::Any,
+    sync::{Arc, Mutex},
+};
+
+use executor::EmbeddedExecutor;
+use tables::{TableStorage, Tables};
+
+use crate::{
+    infra::pi::{storage::*, *},
+    transformation::TransformationArgs,
+    InfraIdentifier, SeResult,
+};
+
+/// In-process graph engine, which runs the built-in graph algorithms without a graph database,
+/// e.g. in tests and on small graphs. The input graph of a transformation is read by
+/// [Sourceable::produce_rows] from the infra it is in, which is the engine itself if the rows
+/// were inserted into it, and loaded into memory as a CSR for each execution. Inserted rows are
+/// kept in memory by the connector and its clones.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedGraphConnector {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl TransformationConnector for EmbeddedGraphConnector {
+    fn get_supported_funcs(&self) -> Vec<GAF> {
+        vec![
+            GAF::AggregateNeighbors,
+            GAF::BetweennessCentrality,
+            GAF::PageRank,
+            GAF::TriangleCount,
+        ]
+    }
+
+    fn get_graph_executor(
+        &self,
+        func: &GAF,
+        args: TransformationArgs,
+        _source_type: Vec<Storage>,
+        sink_type: Storage,
+    ) -> Box<dyn GraphComputationExecutor> {
+        assert!(self.supports_func(func));
+        Box::new(EmbeddedExecutor::new(
+            args,
+            sink_type,
+            func.clone(),
+            Arc::new(self.clone()),
+        ))
+    }
+}
+
+impl Sinkable for EmbeddedGraphConnector {
+    fn get_supported_sources(&self) -> Vec<Storage> {
+        vec![Storage::OfnilRow]
+    }
+
+    /// Keeps the rows in memory, see [TableStorage]
+    fn insert_rows(&self, type_info: Schema) -> Box<dyn Sink<Row>> {
+        Box::new(TableStorage::new(self.tables.clone(), type_info))
+    }
+}
+
+impl Sourceable for EmbeddedGraphConnector {
+    fn get_supported_sinks(&self) -> Vec<Storage> {
+        vec![Storage::Arrow, Storage::OfnilRow]
+    }
+
+    fn produce_rows(&self, type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+        Ok(Box::new(TableStorage::new(self.tables.clone(), type_info)))
+    }
+
+    fn produce_batches(&self, type_info: Schema) -> SeResult<Box<dyn Source<RecordBatch>>> {
+        Ok(Box::new(TableStorage::new(self.tables.clone(), type_info)))
+    }
+}
+
+impl Connector for EmbeddedGraphConnector {
+    fn as_storage(&self) -> Option<&dyn StorageConnector> {
+        Some(self)
+    }
+
+    fn clone_storage(&self) -> Option<Box<dyn StorageConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn as_transformation(&self) -> Option<&dyn TransformationConnector> {
+        Some(self)
+    }
+
+    fn clone_transformation(&self) -> Option<Box<dyn TransformationConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// Builds the connectors of the `embedded` infras, which take no properties
+pub struct EmbeddedGraphConnectorFactory;
+
+#[async_trait::async_trait]
+impl ConnectorFactory for EmbeddedGraphConnectorFactory {
+    fn infra_type(&self) -> &str {
+        "embedded"
+    }
+
+    async fn create(
+        &self,
+        _infra_id: &InfraIdentifier,
+        _properties: &toml::Table,
+    ) -> SeResult<Box<dyn Connector>> {
+        Ok(Box::new(EmbeddedGraphConnector::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        feature::{EdgeEntity, Variant},
+        infra::connectors::LocalFileConnector,
+        transformation::{
+            built_in_fns::{
+                aggregate_neighbor_args::AggregateNeighborArgs, page_rank_args::PageRankArgs,
+                triangle_count_args::TriangleCountArgs, BuiltInFnArgs,
+            },
+            dataframes::AggregateFunc,
+            GraphProjectionArgs, TransformationOutputHandler,
+        },
+        FeatureValueType,
+    };
+
+    struct RowCollector(Vec<Row>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Collector<Row> for RowCollector {
+        async fn collect(&mut self, record: Row) -> SeResult<()> {
+            self.0.push(record);
+            Ok(())
+        }
+    }
+
+    struct BatchCollector(Vec<RecordBatch>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Collector<RecordBatch> for BatchCollector {
+        async fn collect(&mut self, record: RecordBatch) -> SeResult<()> {
+            self.0.push(record);
+            Ok(())
+        }
+    }
+
+    /// Infra that looks up the vertices by their primary keys like Neo4j, and fails to read the
+    /// rows of a vertex or edge type without the keys
+    #[derive(Debug)]
+    struct KeyedConnector {
+        inner: EmbeddedGraphConnector,
+        primary_keys: HashMap<String, String>,
+    }
+
+    impl KeyedConnector {
+        fn check_key(&self, tlabel: &str, primary_key: Option<&String>) -> SeResult<()> {
+            match primary_key {
+                Some(key) if self.primary_keys.get(tlabel) == Some(key) => Ok(()),
+                key => Err(format!("Cannot look up vertices of {tlabel} by {key:?}").into()),
+            }
+        }
+    }
+
+    impl Sinkable for KeyedConnector {
+        fn get_supported_sources(&self) -> Vec<Storage> {
+            Vec::new()
+        }
+    }
+
+    impl Sourceable for KeyedConnector {
+        fn get_supported_sinks(&self) -> Vec<Storage> {
+            vec![Storage::OfnilRow]
+        }
+
+        fn produce_rows(&self, type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+            match &type_info {
+                Schema::Tabular(schema) => self.check_key(
+                    schema.tlabel.as_deref().unwrap_or_default(),
+                    schema.primary_key.as_ref(),
+                )?,
+                Schema::Edge(schema) => {
+                    self.check_key(
+                        &schema.src_vertex_tlabel,
+                        Some(&schema.src_vertex_primary_key),
+                    )?;
+                    self.check_key(
+                        &schema.dst_vertex_tlabel,
+                        Some(&schema.dst_vertex_primary_key),
+                    )?;
+                }
+            }
+            self.inner.produce_rows(type_info)
+        }
+    }
+
+    /// Executes `func` of `args` on the graph of `connector`, and gets the sorted output rows
+    async fn execute(
+        connector: Arc<dyn StorageConnector>,
+        func: GAF,
+        args: TransformationArgs,
+    ) -> SeResult<Vec<Row>> {
+        let executor = EmbeddedGraphConnector::default().get_graph_executor(
+            &func,
+            args,
+            vec![Storage::OfnilRow],
+            Storage::OfnilRow,
+        );
+        let input = TransformationOutputHandler::InfraSource {
+            infra_id: InfraIdentifier::new("local_file", "graph"),
+            connector,
+        };
+        let TransformationOutputHandler::TabularSource(source) =
+            executor.execute(&vec![input]).await?
+        else {
+            panic!("Expect rows")
+        };
+        let mut reader = source.create_reader().await?;
+        let mut rows = RowCollector(Vec::new());
+        while reader.next(&mut rows).await? {}
+        rows.0.sort_by_key(|row| row.get(0).to_string());
+        Ok(rows.0)
+    }
+
+    fn row(key: &str, value: Option<f64>) -> Row {
+        Row::new(vec![
+            RowCell::String(key.to_string()),
+            value.map_or(RowCell::Null, RowCell::Double),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_embedded_graph() -> SeResult<()> {
+        let dir = std::env::temp_dir().join(format!("ofnil_embedded_graph_{}", std::process::id()));
+        let connector = LocalFileConnector::new(&dir, FileFormat::CSV, true)?;
+        std::fs::write(dir.join("follows.csv"), "src,dst\nu1,u2\nu2,u3\nu3,u1\n")?;
+        std::fs::write(dir.join("rates.csv"), "src,dst\nu1,p1\nu2,p1\nu2,p2\n")?;
+        std::fs::write(dir.join("Product.csv"), "id,price\np1,1.5\np2,\n")?;
+        let edge = |tlabel: &str, dst_tlabel: &str| EdgeEntity {
+            name: tlabel.to_string(),
+            tlabel: tlabel.to_string(),
+            src_tlabel: "User".to_string(),
+            dst_tlabel: dst_tlabel.to_string(),
+            src_entity_id: "User".to_string(),
+            dst_entity_id: dst_tlabel.to_string(),
+            directed: true,
+            primary_key: None,
+            variant: Variant::default(),
+            deprecated: false,
+        };
+        let args = |algorithm, edge: EdgeEntity| {
+            TransformationArgs::new_vertex_feature_args(
+                algorithm,
+                GraphProjectionArgs {
+                    vertices: vec![
+                        ("User".to_string(), Some("name".to_string())),
+                        ("Product".to_string(), Some("asin".to_string())),
+                    ],
+                    edges: vec![edge],
+                    make_edges_undirected: false,
+                },
+                "User".to_string(),
+                "name".to_string(),
+                vec!["feature".to_string()],
+            )
+        };
+
+        // every user of the cycle keeps the initial score
+        let page_rank = BuiltInFnArgs::PageRank(PageRankArgs {
+            damping_factor: 0.85,
+            max_iteration: 200,
+            tolerance: 1e-9,
+        });
+        let rows = execute(
+            Arc::new(connector.clone()),
+            GAF::PageRank,
+            args(page_rank, edge("follows", "User")),
+        )
+        .await?;
+        assert_eq!(rows.len(), 3);
+        assert!(rows
+            .iter()
+            .all(|row| matches!(row.get(1), RowCell::Double(score) if (score - 1.0).abs() < 1e-6)));
+
+        let aggregate = |func| {
+            let algorithm = BuiltInFnArgs::AggregateNeighbor(AggregateNeighborArgs {
+                func,
+                properties: vec!["price".to_string()],
+            });
+            args(algorithm, edge("rates", "Product"))
+        };
+        let rows = execute(
+            Arc::new(connector.clone()),
+            GAF::AggregateNeighbors,
+            aggregate(AggregateFunc::Sum),
+        )
+        .await?;
+        assert_eq!(rows, vec![row("u1", Some(1.5)), row("u2", Some(1.5))]);
+        let rows = execute(
+            Arc::new(connector.clone()),
+            GAF::AggregateNeighbors,
+            aggregate(AggregateFunc::Count),
+        )
+        .await?;
+        assert_eq!(rows, vec![row("u1", Some(1.0)), row("u2", Some(1.0))]);
+
+        let triangle_count = BuiltInFnArgs::TriangleCount(TriangleCountArgs { max_degree: None });
+        let rows = execute(
+            Arc::new(connector.clone()),
+            GAF::TriangleCount,
+            args(triangle_count.clone(), edge("follows", "User")),
+        )
+        .await?;
+        assert_eq!(
+            rows,
+            vec![
+                row("u1", Some(1.0)),
+                row("u2", Some(1.0)),
+                row("u3", Some(1.0))
+            ]
+        );
+
+        let executor = EmbeddedGraphConnector::default().get_graph_executor(
+            &GAF::TriangleCount,
+            args(triangle_count, edge("follows", "User")),
+            vec![Storage::OfnilRow],
+            Storage::Arrow,
+        );
+        let input = TransformationOutputHandler::InfraSource {
+            infra_id: InfraIdentifier::new("local_file", "graph"),
+            connector: Arc::new(connector.clone()),
+        };
+        let TransformationOutputHandler::BatchSource(source) =
+            executor.execute(&vec![input]).await?
+        else {
+            panic!("Expect batches")
+        };
+        let mut reader = source.create_reader().await?;
+        let mut batches = BatchCollector(Vec::new());
+        while reader.next(&mut batches).await? {}
+        assert_eq!(batches.0.len(), 1);
+        assert_eq!(batches.0[0].schema(), source.get_schema().arrow_schema());
+        let mut rows = batch_to_rows(&batches.0[0], source.get_schema())?;
+        rows.sort_by_key(|row| row.get(0).to_string());
+        assert_eq!(
+            rows,
+            vec![
+                row("u1", Some(1.0)),
+                row("u2", Some(1.0)),
+                row("u3", Some(1.0))
+            ]
+        );
+        let output = executor
+            .execute(&vec![TransformationOutputHandler::EmptyOutput])
+            .await?;
+        assert!(matches!(output, TransformationOutputHandler::EmptyOutput));
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_embedded_tables() -> SeResult<()> {
+        let engine = EmbeddedGraphConnector::default();
+        let edge_schema = Schema::Edge(EdgeSchema {
+            src_vertex_tlabel: "User".to_string(),
+            dst_vertex_tlabel: "Product".to_string(),
+            src_vertex_primary_key: "name".to_string(),
+            dst_vertex_primary_key: "asin".to_string(),
+            directed: true,
+            edge_info: TabularSchema {
+                field_names: Vec::new(),
+                field_types: Vec::new(),
+                tlabel: Some("rates".to_string()),
+                primary_key: None,
+            },
+        });
+        let vertex_schema = |fields: &[&str]| {
+            Schema::Tabular(TabularSchema {
+                field_names: fields.iter().map(|field| field.to_string()).collect(),
+                field_types: vec![FeatureValueType::Float; fields.len()],
+                tlabel: Some("Product".to_string()),
+                primary_key: Some("asin".to_string()),
+            })
+        };
+        let edge_rows = || -> Vec<Row> {
+            [("u1", "p1"), ("u2", "p1"), ("u2", "p2")]
+                .into_iter()
+                .map(|(src, dst)| {
+                    Row::new(vec![
+                        RowCell::String(src.to_string()),
+                        RowCell::String(dst.to_string()),
+                    ])
+                })
+                .collect()
+        };
+        let sink = engine.insert_rows(edge_schema.clone());
+        let mut writer = sink.create_write_collector().await?;
+        for row in edge_rows() {
+            writer.collect(row).await?;
+        }
+        writer.finish().await?;
+        // the fields of the vertices are merged
+        for (field, row) in [
+            ("price", row("p1", Some(1.5))),
+            ("rank", row("p2", Some(2.0))),
+        ] {
+            let sink = engine.insert_rows(vertex_schema(&[field]));
+            let mut writer = sink.create_write_collector().await?;
+            writer.collect(row).await?;
+            writer.finish().await?;
+        }
+
+        let read = |schema: Schema| {
+            let engine = engine.clone();
+            async move {
+                let source = engine.produce_rows(schema)?;
+                let mut reader = source.create_reader().await?;
+                let mut rows = RowCollector(Vec::new());
+                while reader.next(&mut rows).await? {}
+                SeResult::Ok(rows.0)
+            }
+        };
+        assert_eq!(read(edge_schema).await?, edge_rows());
+        let products = vec![
+            Row::new(vec![
+                RowCell::String("p1".to_string()),
+                RowCell::Double(1.5),
+                RowCell::Null,
+            ]),
+            Row::new(vec![
+                RowCell::String("p2".to_string()),
+                RowCell::Null,
+                RowCell::Double(2.0),
+            ]),
+        ];
+        assert_eq!(read(vertex_schema(&["price", "rank"])).await?, products);
+        let source = engine.produce_batches(vertex_schema(&["price", "rank"]))?;
+        let mut reader = source.create_reader().await?;
+        let mut batches = BatchCollector(Vec::new());
+        while reader.next(&mut batches).await? {}
+        assert_eq!(batch_to_rows(&batches.0[0], source.get_schema())?, products);
+
+        // the neighbor properties are read from an infra that looks up vertices by key
+        let args = |vertices: Vec<(String, Option<String>)>| {
+            TransformationArgs::new_vertex_feature_args(
+                BuiltInFnArgs::AggregateNeighbor(AggregateNeighborArgs {
+                    func: AggregateFunc::Sum,
+                    properties: vec!["price".to_string()],
+                }),
+                GraphProjectionArgs {
+                    vertices,
+                    edges: vec![EdgeEntity {
+                        name: "rates".to_string(),
+                        tlabel: "rates".to_string(),
+                        src_tlabel: "User".to_string(),
+                        dst_tlabel: "Product".to_string(),
+                        src_entity_id: "User".to_string(),
+                        dst_entity_id: "Product".to_string(),
+                        directed: true,
+                        primary_key: None,
+                        variant: Variant::default(),
+                        deprecated: false,
+                    }],
+                    make_edges_undirected: false,
+                },
+                "User".to_string(),
+                "name".to_string(),
+                vec!["feature".to_string()],
+            )
+        };
+        let keyed = Arc::new(KeyedConnector {
+            inner: engine.clone(),
+            primary_keys: HashMap::from([
+                ("User".to_string(), "name".to_string()),
+                ("Product".to_string(), "asin".to_string()),
+            ]),
+        });
+        let vertices = vec![
+            ("User".to_string(), Some("name".to_string())),
+            ("Product".to_string(), Some("asin".to_string())),
+        ];
+        let rows = execute(
+            keyed.clone(),
+            GAF::AggregateNeighbors,
+            args(vertices.clone()),
+        )
+        .await?;
+        assert_eq!(rows, vec![row("u1", Some(1.5)), row("u2", Some(1.5))]);
+        // the primary key of the neighbors is missing
+        let unkeyed = vec![("User".to_string(), None), ("Product".to_string(), None)];
+        assert!(execute(keyed, GAF::AggregateNeighbors, args(unkeyed))
+            .await
+            .is_err());
+
+        // the rows inserted into the engine are its own input graph
+        let executor = engine.get_graph_executor(
+            &GAF::AggregateNeighbors,
+            args(vertices),
+            vec![Storage::OfnilRow],
+            Storage::OfnilRow,
+        );
+        let input = TransformationOutputHandler::InfraHandler {
+            infra_id: InfraIdentifier::new("embedded", "embedded"),
+        };
+        let TransformationOutputHandler::TabularSource(source) =
+            executor.execute(&vec![input]).await?
+        else {
+            panic!("Expect rows")
+        };
+        let mut reader = source.create_reader().await?;
+        let mut rows = RowCollector(Vec::new());
+        while reader.next(&mut rows).await? {}
+        rows.0.sort_by_key(|row| row.get(0).to_string());
+        assert_eq!(rows.0, vec![row("u1", Some(1.5)), row("u2", Some(1.5))]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_embedded_graph_connector_factory() -> SeResult<()> {
+        let infra_id = InfraIdentifier::new("embedded", "engine");
+        assert!(matches!(infra_id, InfraIdentifier::Plugin { .. }));
+        let factory = plugin::get_connector_factory("embedded").expect("built-in factory");
+        let connector = factory.create(&infra_id, &toml::Table::new()).await?;
+        assert!(connector
+            .as_any()
+            .is_some_and(|connector| connector.is::<EmbeddedGraphConnector>()));
+        assert!(connector.as_transformation().is_some());
+        Ok(())
+    }
+}
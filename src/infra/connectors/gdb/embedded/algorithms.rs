@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+
+use super::in_memory_graph::InMemoryGraph;
+use crate::transformation::{
+    built_in_fns::{
+        betweenness_centrality_args::BetweennessCentralityArgs, page_rank_args::PageRankArgs,
+    },
+    dataframes::AggregateFunc,
+};
+
+/// Scores of the vertices by the PageRank of Neo4j GDS: every vertex starts from and receives
+/// `1 - damping_factor`, and the scores of vertices without out edges are not redistributed.
+/// Iterates until no score changes by `tolerance` or more, or for `max_iteration` iterations.
+pub(super) fn page_rank(graph: &InMemoryGraph, args: &PageRankArgs) -> Vec<f64> {
+    let damping_factor = args.damping_factor as f64;
+    let base = 1.0 - damping_factor;
+    let mut scores = vec![base; graph.num_vertices()];
+    for _ in 0..args.max_iteration {
+        let mut next = vec![base; scores.len()];
+        for (v, score) in scores.iter().enumerate() {
+            let neighbors = graph.neighbors(v);
+            if neighbors.is_empty() {
+                continue;
+            }
+            let share = damping_factor * score / neighbors.len() as f64;
+            for u in neighbors {
+                next[*u as usize] += share;
+            }
+        }
+        let converged = scores
+            .iter()
+            .zip(&next)
+            .all(|(score, next)| (score - next).abs() < args.tolerance as f64);
+        scores = next;
+        if converged {
+            break;
+        }
+    }
+    scores
+}
+
+/// Undirected simple graph of `graph`, without self loops and parallel edges
+fn undirected_neighbors(graph: &InMemoryGraph) -> Vec<Vec<u64>> {
+    let mut neighbors = vec![Vec::new(); graph.num_vertices()];
+    for v in 0..graph.num_vertices() {
+        for u in graph.neighbors(v) {
+            if *u as usize != v {
+                neighbors[v].push(*u);
+                neighbors[*u as usize].push(v as u64);
+            }
+        }
+    }
+    for neighbors in &mut neighbors {
+        neighbors.sort_unstable();
+        neighbors.dedup();
+    }
+    neighbors
+}
+
+/// Number of the triangles of each vertex in the undirected graph of `graph`. Vertices of a
+/// degree higher than `max_degree` are left out, with a count of -1.
+pub(super) fn triangle_count(graph: &InMemoryGraph, max_degree: Option<u64>) -> Vec<i64> {
+    let mut neighbors = undirected_neighbors(graph);
+    let excluded: Vec<bool> = neighbors
+        .iter()
+        .map(|neighbors| max_degree.is_some_and(|max_degree| neighbors.len() as u64 > max_degree))
+        .collect();
+    for neighbors in &mut neighbors {
+        neighbors.retain(|u| !excluded[*u as usize]);
+    }
+    let mut counts = vec![0; neighbors.len()];
+    for v in 0..neighbors.len() {
+        if excluded[v] {
+            counts[v] = -1;
+            continue;
+        }
+        // count each triangle v < u < w once, at v
+        for (i, u) in neighbors[v].iter().enumerate() {
+            if *u as usize <= v {
+                continue;
+            }
+            for w in &neighbors[v][i + 1..] {
+                if neighbors[*u as usize].binary_search(w).is_ok() {
+                    counts[v] += 1;
+                    counts[*u as usize] += 1;
+                    counts[*w as usize] += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Generator of the sampled source vertices of [betweenness_centrality], xorshift64*
+struct Sampler(u64);
+
+impl Sampler {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) % bound as u64) as usize
+    }
+}
+
+/// Betweenness centrality of the vertices by Brandes' algorithm, from `sampling_size` sources
+/// sampled by `sampling_seed` if given, or else from all vertices. Each pair of vertices is
+/// counted once if the graph is `undirected`, i.e. has the reverse of every edge.
+pub(super) fn betweenness_centrality(
+    graph: &InMemoryGraph,
+    args: &BetweennessCentralityArgs,
+    undirected: bool,
+) -> Vec<f64> {
+    let n = graph.num_vertices();
+    let mut sources: Vec<usize> = (0..n).collect();
+    if let Some(sampling_size) = args.sampling_size.map(|size| size as usize) {
+        if sampling_size < n {
+            let seed = args
+                .sampling_seed
+                .map(u64::from)
+                .or_else(|| {
+                    chrono::Utc::now()
+                        .timestamp_nanos_opt()
+                        .map(|nanos| nanos as u64)
+                })
+                .unwrap_or_default();
+            // the state of xorshift must not be zero
+            let mut sampler = Sampler(seed | 1);
+            for i in 0..sampling_size {
+                let j = i + sampler.next(n - i);
+                sources.swap(i, j);
+            }
+            sources.truncate(sampling_size);
+        }
+    }
+
+    let mut centrality = vec![0.0; n];
+    let mut order = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+    let mut predecessors = vec![Vec::new(); n];
+    let mut num_paths = vec![0.0; n];
+    let mut distance = vec![-1; n];
+    let mut dependency = vec![0.0; n];
+    for source in sources {
+        order.clear();
+        predecessors.iter_mut().for_each(Vec::clear);
+        num_paths.fill(0.0);
+        distance.fill(-1);
+        dependency.fill(0.0);
+        num_paths[source] = 1.0;
+        distance[source] = 0;
+        queue.push_back(source);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for u in graph.neighbors(v) {
+                let u = *u as usize;
+                if distance[u] < 0 {
+                    distance[u] = distance[v] + 1;
+                    queue.push_back(u);
+                }
+                if distance[u] == distance[v] + 1 {
+                    num_paths[u] += num_paths[v];
+                    predecessors[u].push(v);
+                }
+            }
+        }
+        for &u in order.iter().rev() {
+            for &v in &predecessors[u] {
+                dependency[v] += num_paths[v] / num_paths[u] * (1.0 + dependency[u]);
+            }
+            if u != source {
+                centrality[u] += dependency[u];
+            }
+        }
+    }
+    if undirected {
+        centrality.iter_mut().for_each(|score| *score /= 2.0);
+    }
+    centrality
+}
+
+/// Aggregates `values` as [AggregateFunc::as_cypher_str] in Cypher, e.g. `None` for the mean of
+/// no values, and the sample standard deviation
+pub(super) fn aggregate(func: AggregateFunc, values: &[f64]) -> Option<f64> {
+    let sum = || values.iter().sum::<f64>();
+    match func {
+        AggregateFunc::Count => Some(values.len() as f64),
+        AggregateFunc::Sum => Some(sum()),
+        AggregateFunc::Mean => (!values.is_empty()).then(|| sum() / values.len() as f64),
+        AggregateFunc::Min => values.iter().copied().reduce(f64::min),
+        AggregateFunc::Max => values.iter().copied().reduce(f64::max),
+        AggregateFunc::Std => {
+            if values.len() < 2 {
+                return Some(0.0);
+            }
+            let mean = sum() / values.len() as f64;
+            let squares: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+            Some((squares / (values.len() - 1) as f64).sqrt())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::connectors::gdb::embedded::in_memory_graph::GraphBuilder;
+
+    /// Graph of vertices `0..` of edges `edges`
+    fn graph(edges: &[(u32, u32)], undirected: bool) -> InMemoryGraph {
+        let mut builder = GraphBuilder::default();
+        for (src, dst) in edges {
+            builder.add_edge(("V", src.to_string()), ("V", dst.to_string()), undirected);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_page_rank() {
+        // a cycle 0 -> 1 -> 2 -> 0, where every vertex keeps the score of 1
+        let cycle = graph(&[(0, 1), (1, 2), (2, 0)], false);
+        let args = PageRankArgs {
+            damping_factor: 0.85,
+            max_iteration: 200,
+            tolerance: 1e-9,
+        };
+        assert!(page_rank(&cycle, &args)
+            .iter()
+            .all(|score| (score - 1.0).abs() < 1e-6));
+
+        // a star into vertex 0, which is given id 1 after vertex 1
+        let star = graph(&[(1, 0), (2, 0), (3, 0)], false);
+        let scores = page_rank(&star, &args);
+        assert_eq!(star.get_vertex(1), ("V", "0"));
+        assert!((scores[1] - (0.15 + 3.0 * 0.85 * 0.15)).abs() < 1e-6);
+        assert!((scores[0] - 0.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_triangle_count() {
+        // two triangles 0-1-2 and 0-2-3 sharing edge 0-2, in both directions and with a self loop
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 0), (0, 2), (3, 3)];
+        let g = graph(&edges, false);
+        assert_eq!(triangle_count(&g, None), vec![2, 1, 2, 1]);
+        // vertex 0 and 2 are of degree 3
+        assert_eq!(triangle_count(&g, Some(2)), vec![-1, 0, -1, 0]);
+    }
+
+    #[test]
+    fn test_betweenness_centrality() {
+        // a path 0 - 1 - 2 - 3
+        let path = graph(&[(0, 1), (1, 2), (2, 3)], true);
+        let all = BetweennessCentralityArgs {
+            sampling_size: None,
+            sampling_seed: None,
+        };
+        assert_eq!(
+            betweenness_centrality(&path, &all, true),
+            vec![0.0, 2.0, 2.0, 0.0]
+        );
+        // directed, only 0 -> 2 and 1 -> 3 pass through 1 and 2 respectively, and 0 -> 3 both
+        let path = graph(&[(0, 1), (1, 2), (2, 3)], false);
+        assert_eq!(
+            betweenness_centrality(&path, &all, false),
+            vec![0.0, 2.0, 2.0, 0.0]
+        );
+
+        let sampled = BetweennessCentralityArgs {
+            sampling_size: Some(2),
+            sampling_seed: Some(42),
+        };
+        assert_eq!(
+            betweenness_centrality(&path, &sampled, false),
+            betweenness_centrality(&path, &sampled, false)
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let values = [1.0, 2.0, 3.0];
+        assert_eq!(aggregate(AggregateFunc::Count, &values), Some(3.0));
+        assert_eq!(aggregate(AggregateFunc::Sum, &values), Some(6.0));
+        assert_eq!(aggregate(AggregateFunc::Mean, &values), Some(2.0));
+        assert_eq!(aggregate(AggregateFunc::Min, &values), Some(1.0));
+        assert_eq!(aggregate(AggregateFunc::Max, &values), Some(3.0));
+        assert_eq!(aggregate(AggregateFunc::Std, &values), Some(1.0));
+        assert_eq!(aggregate(AggregateFunc::Mean, &[]), None);
+    }
+}
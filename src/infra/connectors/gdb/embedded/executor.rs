@@ -0,0 +1,381 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow::array::{ArrayRef, Float64Array, StringArray};
+
+use super::{algorithms, in_memory_graph::GraphBuilder};
+use crate::{
+    feature::EdgeEntity,
+    infra::pi::{storage::*, GraphComputationExecutor, StorageConnector, GAF},
+    transformation::{
+        transformation_args::VertexFeatureTransformationArgs, TransformationArgs,
+        TransformationIOT, TransformationOutputHandler,
+    },
+    FeatureValueType, SeResult,
+};
+
+/// Input graph of an [EmbeddedExecutor]
+#[derive(Debug)]
+enum GraphInput {
+    /// Infra to read the edges and vertex properties of the graph projection from, which is the
+    /// engine itself for an [TransformationOutputHandler::InfraHandler] of the execution infra
+    Infra(Arc<dyn StorageConnector>),
+    /// Rows of the edges of a [Schema::Edge]
+    Edges(Arc<dyn Source<Row>>),
+}
+
+/// Executes the built-in graph algorithms in process. The input graph is loaded into an
+/// [InMemoryGraph](super::in_memory_graph::InMemoryGraph) by each reader of the output, see [EmbeddedResultSource].
+pub(super) struct EmbeddedExecutor {
+    args: TransformationArgs,
+    sink_type: Storage,
+    func: GAF,
+    /// The engine that executes the transformation, which keeps the rows inserted into it
+    connector: Arc<dyn StorageConnector>,
+}
+
+impl EmbeddedExecutor {
+    pub(super) fn new(
+        args: TransformationArgs,
+        sink_type: Storage,
+        func: GAF,
+        connector: Arc<dyn StorageConnector>,
+    ) -> Self {
+        Self {
+            args,
+            sink_type,
+            func,
+            connector,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GraphComputationExecutor for EmbeddedExecutor {
+    async fn execute(&self, input: &TransformationIOT) -> SeResult<TransformationOutputHandler> {
+        let input = match input.first().ok_or("Input graph is expected")? {
+            TransformationOutputHandler::EmptyOutput => {
+                return Ok(TransformationOutputHandler::EmptyOutput)
+            }
+            TransformationOutputHandler::InfraHandler { .. } => {
+                GraphInput::Infra(self.connector.clone())
+            }
+            TransformationOutputHandler::InfraSource { connector, .. } => {
+                GraphInput::Infra(connector.clone())
+            }
+            TransformationOutputHandler::TabularSource(source)
+                if matches!(source.get_schema(), Schema::Edge(_)) =>
+            {
+                GraphInput::Edges(source.clone())
+            }
+            input => {
+                return Err(
+                    format!("Unexpected input {input:?} of the embedded graph engine").into(),
+                )
+            }
+        };
+        let args = self.args.as_vertex_feature().clone();
+        let source = EmbeddedResultSource {
+            schema: Schema::Tabular(TabularSchema {
+                tlabel: Some(args.target_vertex_tlabel.clone()),
+                field_names: args.output_names.clone(),
+                field_types: vec![FeatureValueType::Float; args.output_names.len()],
+                primary_key: Some(args.target_vertex_primary_key.clone()),
+            }),
+            input,
+            args,
+            func: self.func.clone(),
+        };
+        match self.sink_type {
+            Storage::OfnilRow => Ok(TransformationOutputHandler::TabularSource(Arc::new(source))),
+            Storage::Arrow => Ok(TransformationOutputHandler::BatchSource(Arc::new(source))),
+            _ => Err(format!(
+                "The embedded graph engine only outputs rows and batches, but got {:?}",
+                self.sink_type
+            )
+            .into()),
+        }
+    }
+}
+
+struct RowCollector(Vec<Row>);
+
+#[async_trait::async_trait(?Send)]
+impl Collector<Row> for RowCollector {
+    async fn collect(&mut self, record: Row) -> SeResult<()> {
+        self.0.push(record);
+        Ok(())
+    }
+}
+
+async fn read_all(source: &dyn Source<Row>) -> SeResult<Vec<Row>> {
+    let mut reader = source.create_reader().await?;
+    let mut rows = RowCollector(Vec::new());
+    while reader.next(&mut rows).await? {}
+    Ok(rows.0)
+}
+
+/// Gets the key of the vertex of `cell`, `None` for null
+fn get_key(cell: &RowCell) -> Option<String> {
+    (cell != &RowCell::Null).then(|| cell.to_string())
+}
+
+fn get_number(cell: &RowCell) -> Option<f64> {
+    match cell {
+        RowCell::Double(value) => Some(*value),
+        RowCell::Float(value) => Some(*value as f64),
+        RowCell::Int(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+/// Source of the output vertex features of an [EmbeddedExecutor], which are computed when a
+/// reader is created
+#[derive(Debug)]
+struct EmbeddedResultSource {
+    input: GraphInput,
+    args: VertexFeatureTransformationArgs,
+    func: GAF,
+    schema: Schema,
+}
+
+impl EmbeddedResultSource {
+    /// Primary key of the vertices of `tlabel`, given by the graph projection or the target
+    /// vertex, by which infras like Neo4j look up the vertices
+    fn get_primary_key(&self, tlabel: &String) -> SeResult<String> {
+        let args = &self.args;
+        args.graph_projection
+            .vertices
+            .iter()
+            .find(|(label, _)| label == tlabel)
+            .and_then(|(_, primary_key)| primary_key.clone())
+            .or_else(|| {
+                (tlabel == &args.target_vertex_tlabel)
+                    .then(|| args.target_vertex_primary_key.clone())
+            })
+            .ok_or_else(|| format!("Missing the primary key of vertex {tlabel}").into())
+    }
+
+    /// Schema of the rows of `edge`
+    fn get_edge_schema(&self, edge: &EdgeEntity) -> SeResult<Schema> {
+        Ok(Schema::Edge(EdgeSchema {
+            src_vertex_tlabel: edge.src_tlabel.clone(),
+            dst_vertex_tlabel: edge.dst_tlabel.clone(),
+            src_vertex_primary_key: self.get_primary_key(&edge.src_tlabel)?,
+            dst_vertex_primary_key: self.get_primary_key(&edge.dst_tlabel)?,
+            directed: edge.directed,
+            edge_info: TabularSchema {
+                field_names: vec![],
+                field_types: vec![],
+                tlabel: Some(edge.tlabel.clone()),
+                primary_key: None,
+            },
+        }))
+    }
+
+    /// Reads the edges of the input as the `(src key, dst key)` of each edge schema
+    async fn read_edges(&self) -> SeResult<Vec<(EdgeSchema, Vec<(String, String)>)>> {
+        let sources: Vec<Arc<dyn Source<Row>>> = match &self.input {
+            GraphInput::Infra(connector) => self
+                .args
+                .graph_projection
+                .edges
+                .iter()
+                .map(|edge| {
+                    Ok(Arc::from(
+                        connector.produce_rows(self.get_edge_schema(edge)?)?,
+                    ))
+                })
+                .collect::<SeResult<_>>()?,
+            GraphInput::Edges(source) => vec![source.clone()],
+        };
+        let mut edges = Vec::with_capacity(sources.len());
+        for source in sources {
+            let schema = source.get_schema().clone().into_edge();
+            let rows = read_all(source.as_ref()).await?;
+            let keys = rows
+                .iter()
+                .filter_map(|row| Some((get_key(row.get(0))?, get_key(row.get(1))?)))
+                .collect();
+            edges.push((schema, keys));
+        }
+        Ok(edges)
+    }
+
+    async fn compute(&self) -> SeResult<RecordBatch> {
+        let target = &self.args.target_vertex_tlabel;
+        if self.func == GAF::AggregateNeighbors {
+            return self.aggregate_neighbors().await;
+        }
+
+        let mut builder = GraphBuilder::default();
+        let undirected = self.args.graph_projection.make_edges_undirected;
+        for (schema, keys) in self.read_edges().await? {
+            for (src, dst) in keys {
+                builder.add_edge(
+                    (&schema.src_vertex_tlabel, src),
+                    (&schema.dst_vertex_tlabel, dst),
+                    undirected,
+                );
+            }
+        }
+        let graph = builder.build();
+        let scores: Vec<f64> = match &self.func {
+            GAF::PageRank => algorithms::page_rank(&graph, self.args.algorithm.as_page_rank()),
+            GAF::TriangleCount => {
+                let max_degree = self.args.algorithm.as_triangle_count().max_degree;
+                algorithms::triangle_count(&graph, max_degree)
+                    .into_iter()
+                    .map(|count| count as f64)
+                    .collect()
+            }
+            GAF::BetweennessCentrality => algorithms::betweenness_centrality(
+                &graph,
+                self.args.algorithm.as_betweenness_centrality(),
+                undirected,
+            ),
+            func => {
+                return Err(format!("Unexpected func {func:?} of the embedded graph engine").into())
+            }
+        };
+        let (keys, scores) = graph
+            .vertices(target)
+            .map(|(key, v)| (key.to_string(), Some(scores[v])))
+            .unzip();
+        self.to_batch(keys, vec![scores])
+    }
+
+    /// Builds a batch of the `keys` of the target vertices and the `columns` of their features
+    fn to_batch(&self, keys: Vec<String>, columns: Vec<Vec<Option<f64>>>) -> SeResult<RecordBatch> {
+        let arrays = std::iter::once(Arc::new(StringArray::from(keys)) as ArrayRef)
+            .chain(
+                columns
+                    .into_iter()
+                    .map(|values| Arc::new(Float64Array::from(values)) as ArrayRef),
+            )
+            .collect();
+        Ok(RecordBatch::try_new(self.schema.arrow_schema(), arrays)?)
+    }
+
+    /// Aggregates the properties of the neighbors of each target vertex over the first edge of
+    /// the graph projection, as the Cypher query of neighbor aggregation. Target vertices
+    /// without neighbors are left out.
+    async fn aggregate_neighbors(&self) -> SeResult<RecordBatch> {
+        let GraphInput::Infra(connector) = &self.input else {
+            return Err("Neighbor aggregation reads the neighbor properties from an infra".into());
+        };
+        let algorithm_args = self.args.algorithm.as_aggregate_neighbor();
+        let target = &self.args.target_vertex_tlabel;
+        let edge = self
+            .args
+            .graph_projection
+            .edges
+            .first()
+            .ok_or("Neighbor aggregation expects an edge")?;
+        let target_is_src = &edge.src_tlabel == target;
+        let neighbor = if target_is_src {
+            &edge.dst_tlabel
+        } else {
+            &edge.src_tlabel
+        };
+        // both ends are targets if an edge between vertices of the same label is undirected
+        let both = !edge.directed && edge.src_tlabel == edge.dst_tlabel;
+
+        let mut builder = GraphBuilder::default();
+        let edges = connector.produce_rows(self.get_edge_schema(edge)?)?;
+        let rows = read_all(edges.as_ref()).await?;
+        for row in &rows {
+            let (Some(src), Some(dst)) = (get_key(row.get(0)), get_key(row.get(1))) else {
+                continue;
+            };
+            let (target_key, neighbor_key) = if target_is_src {
+                (src, dst)
+            } else {
+                (dst, src)
+            };
+            builder.add_edge((target, target_key), (neighbor, neighbor_key), both);
+        }
+        let graph = builder.build();
+
+        let properties = connector.produce_rows(Schema::Tabular(TabularSchema {
+            field_names: algorithm_args.properties.clone(),
+            field_types: vec![FeatureValueType::Float; algorithm_args.properties.len()],
+            tlabel: Some(neighbor.clone()),
+            primary_key: Some(self.get_primary_key(neighbor)?),
+        }))?;
+        let properties: HashMap<String, Row> = read_all(properties.as_ref())
+            .await?
+            .into_iter()
+            .filter_map(|row| Some((get_key(row.get(0))?, row)))
+            .collect();
+
+        let mut keys = Vec::with_capacity(graph.num_vertices());
+        let mut columns =
+            vec![Vec::with_capacity(graph.num_vertices()); algorithm_args.properties.len()];
+        for (key, v) in graph.vertices(target) {
+            let neighbors = graph.neighbors(v);
+            if neighbors.is_empty() {
+                continue;
+            }
+            keys.push(key.to_string());
+            for (idx, column) in columns.iter_mut().enumerate() {
+                let values: Vec<f64> = neighbors
+                    .iter()
+                    .filter_map(|u| {
+                        let (_, neighbor_key) = graph.get_vertex(*u as usize);
+                        get_number(properties.get(neighbor_key)?.get(idx + 1))
+                    })
+                    .collect();
+                column.push(algorithms::aggregate(algorithm_args.func, &values));
+            }
+        }
+        self.to_batch(keys, columns)
+    }
+}
+
+/// Reader of the computed rows, which are read at once
+struct ResultReader<T>(Option<T>);
+
+#[async_trait::async_trait(?Send)]
+impl Source<Row> for EmbeddedResultSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<Row> + '_>> {
+        let rows = batch_to_rows(&self.compute().await?, &self.schema)?;
+        Ok(Box::new(ResultReader(Some(rows))))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<Row> for ResultReader<Vec<Row>> {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        if let Some(rows) = self.0.take() {
+            for row in rows {
+                output.collect(row).await?;
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source<RecordBatch> for EmbeddedResultSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<RecordBatch> + '_>> {
+        Ok(Box::new(ResultReader(Some(self.compute().await?))))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<RecordBatch> for ResultReader<RecordBatch> {
+    async fn next(&mut self, output: &mut dyn Collector<RecordBatch>) -> SeResult<bool> {
+        if let Some(batch) = self.0.take().filter(|batch| batch.num_rows() > 0) {
+            output.collect(batch).await?;
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::infra::pi::storage::{CooTopology, CsrTopology, IdMapping};
+
+/// Vertex of a [GraphBuilder], by the index of its label and its id in the label
+type LabeledVertex = (usize, u64);
+
+/// Collects the edges of an [InMemoryGraph], mapping the vertex keys of each label to ids
+#[derive(Debug, Default)]
+pub(super) struct GraphBuilder {
+    labels: Vec<(String, IdMapping)>,
+    edges: Vec<(LabeledVertex, LabeledVertex)>,
+}
+
+impl GraphBuilder {
+    fn vertex(&mut self, tlabel: &str, key: String) -> LabeledVertex {
+        let label = match self.labels.iter().position(|(name, _)| name == tlabel) {
+            Some(label) => label,
+            None => {
+                self.labels.push((tlabel.to_string(), IdMapping::default()));
+                self.labels.len() - 1
+            }
+        };
+        (label, self.labels[label].1.get_or_insert(key))
+    }
+
+    /// Adds an edge from vertex `src` to vertex `dst`, both given by label and key, and the
+    /// reverse edge if `undirected`
+    pub(super) fn add_edge(&mut self, src: (&str, String), dst: (&str, String), undirected: bool) {
+        let src = self.vertex(src.0, src.1);
+        let dst = self.vertex(dst.0, dst.1);
+        self.edges.push((src, dst));
+        if undirected {
+            self.edges.push((dst, src));
+        }
+    }
+
+    pub(super) fn build(self) -> InMemoryGraph {
+        let mut offsets = Vec::with_capacity(self.labels.len());
+        let mut num_vertices = 0;
+        for (_, mapping) in &self.labels {
+            offsets.push(num_vertices);
+            num_vertices += mapping.len() as u64;
+        }
+        let global_id = |(label, id): LabeledVertex| offsets[label] + id;
+        let coo = CooTopology {
+            src: self.edges.iter().map(|(src, _)| global_id(*src)).collect(),
+            dst: self.edges.iter().map(|(_, dst)| global_id(*dst)).collect(),
+            edge_ids: None,
+        };
+        InMemoryGraph {
+            csr: CsrTopology::from_coo(&coo, num_vertices as usize),
+            labels: self.labels,
+            offsets,
+        }
+    }
+}
+
+/// Graph of the vertices of the edges of a [GraphBuilder], with the out edges of each vertex in
+/// a CSR. The vertices of all labels have contiguous ids `0..num_vertices`.
+#[derive(Debug)]
+pub(super) struct InMemoryGraph {
+    labels: Vec<(String, IdMapping)>,
+    /// Id of the first vertex of each label
+    offsets: Vec<u64>,
+    csr: CsrTopology,
+}
+
+impl InMemoryGraph {
+    pub(super) fn num_vertices(&self) -> usize {
+        self.csr.indptr.len() - 1
+    }
+
+    /// Dst vertices of the out edges of vertex `v`, in ascending order
+    pub(super) fn neighbors(&self, v: usize) -> &[u64] {
+        &self.csr.indices[self.csr.indptr[v] as usize..self.csr.indptr[v + 1] as usize]
+    }
+
+    /// Keys and ids of the vertices of `tlabel`
+    pub(super) fn vertices<'a>(
+        &'a self,
+        tlabel: &'a str,
+    ) -> impl Iterator<Item = (&'a str, usize)> {
+        self.labels
+            .iter()
+            .zip(&self.offsets)
+            .filter(move |((name, _), _)| name == tlabel)
+            .flat_map(|((_, mapping), offset)| {
+                mapping
+                    .keys()
+                    .iter()
+                    .enumerate()
+                    .map(move |(id, key)| (key.as_str(), *offset as usize + id))
+            })
+    }
+
+    /// Gets the label and key of vertex `v`
+    pub(super) fn get_vertex(&self, v: usize) -> (&str, &str) {
+        let label = self.offsets.partition_point(|offset| *offset <= v as u64) - 1;
+        let (tlabel, mapping) = &self.labels[label];
+        (
+            tlabel,
+            mapping.get_key(v as u64 - self.offsets[label]).unwrap(),
+        )
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::{infra::pi::storage::*, SeResult};
+
+/// Rows kept in memory by an [EmbeddedGraphConnector](super::EmbeddedGraphConnector)
+#[derive(Debug, Default)]
+pub(super) struct Tables {
+    /// Fields of the vertices by their label and id. The fields written to a vertex are merged
+    /// into the ones written before, as in Redis.
+    vertices: HashMap<String, BTreeMap<String, HashMap<String, RowCell>>>,
+    /// Field names and rows of the edges by their label, which are overwritten by each writer
+    edges: HashMap<String, (Vec<String>, Vec<Row>)>,
+}
+
+fn get_tlabel(schema: &TabularSchema) -> SeResult<&str> {
+    schema
+        .tlabel
+        .as_deref()
+        .ok_or_else(|| format!("Cannot keep rows without a label. {schema:?}").into())
+}
+
+impl Tables {
+    /// Rows of `schema` laid out as [Schema::columns]. A vertex row is read for each vertex of
+    /// which any field of the schema is set, and the unset fields are null.
+    fn read(&self, schema: &Schema) -> SeResult<Vec<Row>> {
+        Ok(match schema {
+            Schema::Tabular(schema) => {
+                let Some(vertices) = self.vertices.get(get_tlabel(schema)?) else {
+                    return Ok(Vec::new());
+                };
+                vertices
+                    .iter()
+                    .filter(|(_, fields)| {
+                        schema
+                            .field_names
+                            .iter()
+                            .any(|name| fields.contains_key(name))
+                    })
+                    .map(|(id, fields)| {
+                        let cells = std::iter::once(RowCell::String(id.clone())).chain(
+                            schema
+                                .field_names
+                                .iter()
+                                .map(|name| fields.get(name).cloned().unwrap_or(RowCell::Null)),
+                        );
+                        Row::new(cells.collect())
+                    })
+                    .collect()
+            }
+            Schema::Edge(schema) => {
+                let Some((names, rows)) = self.edges.get(get_tlabel(&schema.edge_info)?) else {
+                    return Ok(Vec::new());
+                };
+                // the src and dst keys followed by the fields of the schema in the written rows
+                let indices: Vec<_> = [Some(0), Some(1)]
+                    .into_iter()
+                    .chain(schema.edge_info.field_names.iter().map(|name| {
+                        names
+                            .iter()
+                            .position(|written| written == name)
+                            .map(|idx| idx + 2)
+                    }))
+                    .collect();
+                rows.iter()
+                    .map(|row| {
+                        Row::new(
+                            indices
+                                .iter()
+                                .map(|idx| idx.map_or(RowCell::Null, |idx| row.get(idx).clone()))
+                                .collect(),
+                        )
+                    })
+                    .collect()
+            }
+        })
+    }
+}
+
+/// Sink and source of the rows of a schema in the [Tables] of an embedded graph engine
+#[derive(Debug)]
+pub(super) struct TableStorage {
+    tables: Arc<Mutex<Tables>>,
+    type_info: Schema,
+}
+
+impl TableStorage {
+    pub(super) fn new(tables: Arc<Mutex<Tables>>, type_info: Schema) -> Self {
+        Self { tables, type_info }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Sink<Row> for TableStorage {
+    /// Overwrites the edges of an edge schema, or merges the fields into the vertices of a
+    /// tabular schema
+    async fn create_writer(&self) -> SeResult<Box<dyn Writer<Row> + '_>> {
+        if let Schema::Edge(schema) = &self.type_info {
+            let tlabel = get_tlabel(&schema.edge_info)?.to_string();
+            let names = schema.edge_info.field_names.clone();
+            self.tables
+                .lock()
+                .unwrap()
+                .edges
+                .insert(tlabel, (names, Vec::new()));
+        }
+        Ok(Box::new(TableWriter {
+            tables: &self.tables,
+            type_info: &self.type_info,
+            num_columns: self.type_info.columns().len(),
+        }))
+    }
+}
+
+struct TableWriter<'a> {
+    tables: &'a Mutex<Tables>,
+    type_info: &'a Schema,
+    num_columns: usize,
+}
+
+#[async_trait::async_trait(?Send)]
+impl<'a> Writer<Row> for TableWriter<'a> {
+    async fn write(&mut self, record: Row) -> SeResult<()> {
+        if record.len() != self.num_columns {
+            return Err(format!(
+                "Expect a row of {} columns, but got {record:?}",
+                self.num_columns
+            )
+            .into());
+        }
+        let mut tables = self.tables.lock().unwrap();
+        match self.type_info {
+            Schema::Tabular(schema) => {
+                if record.get(0) == &RowCell::Null {
+                    return Err(format!("Expect a vertex id, but got {record:?}").into());
+                }
+                let fields = tables
+                    .vertices
+                    .entry(get_tlabel(schema)?.to_string())
+                    .or_default()
+                    .entry(record.get(0).to_string())
+                    .or_default();
+                for (idx, name) in schema.field_names.iter().enumerate() {
+                    fields.insert(name.clone(), record.get(idx + 1).clone());
+                }
+            }
+            Schema::Edge(schema) => {
+                let tlabel = get_tlabel(&schema.edge_info)?;
+                let (_, rows) = tables
+                    .edges
+                    .get_mut(tlabel)
+                    .ok_or_else(|| format!("Edges of {tlabel} are not being written"))?;
+                rows.push(record);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reader of the rows of a [TableStorage], which are read at once
+struct TableReader(Option<Vec<Row>>);
+
+#[async_trait::async_trait(?Send)]
+impl Source<Row> for TableStorage {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<Row> + '_>> {
+        let rows = self.tables.lock().unwrap().read(&self.type_info)?;
+        Ok(Box::new(TableReader(Some(rows))))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.type_info
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<Row> for TableReader {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        for row in self.0.take().unwrap_or_default() {
+            output.collect(row).await?;
+        }
+        Ok(false)
+    }
+}
+
+/// Reader of the rows of a [TableStorage] as a single batch
+struct TableBatchReader(Option<RecordBatch>);
+
+#[async_trait::async_trait(?Send)]
+impl Source<RecordBatch> for TableStorage {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<RecordBatch> + '_>> {
+        let rows = self.tables.lock().unwrap().read(&self.type_info)?;
+        let batch = rows_to_batch(&rows, &self.type_info)?;
+        Ok(Box::new(TableBatchReader(Some(batch))))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.type_info
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Reader<RecordBatch> for TableBatchReader {
+    async fn next(&mut self, output: &mut dyn Collector<RecordBatch>) -> SeResult<bool> {
+        if let Some(batch) = self.0.take().filter(|batch| batch.num_rows() > 0) {
+            output.collect(batch).await?;
+        }
+        Ok(false)
+    }
+}
@@ -7,6 +7,7 @@ mod neo4j_query_row_source;
 use neo4j_query_row_source::*;
 mod graph_projection;
 use graph_projection::GraphProjection;
+pub use graph_projection::StaleProjection;
 mod topology_to_vf_executor;
 use topology_to_vf_executor::TopologyToVFExecutor;
 mod cypher_executor;
@@ -15,8 +16,11 @@ mod graph_csv_sink;
 mod input;
 
 use crate::{
-    infra::{pi::storage::*, pi::*},
-    transformation::{GraphProjectionArgs, TransformationArgs},
+    config::Neo4jOptions,
+    infra::{health::HealthCheck, pi::storage::*, pi::*},
+    transformation::{
+        transformation_args::CypherTransformationArgs, GraphProjectionArgs, TransformationArgs,
+    },
     InfraIdentifier, SeResult,
 };
 use std::sync::Arc;
@@ -33,20 +37,22 @@ impl Neo4jConnector {
         self.inner.clone()
     }
 
+    /// Connects with the TLS, database, pool size and timeout of `options`, see
+    /// [Neo4jDatabaseProvider::with_options].
     pub(crate) async fn new(
         bolt_uri: impl Into<String>,
         username: impl Into<String>,
         password: impl Into<String>,
-        max_pool_size: Option<u32>,
+        options: &Neo4jOptions,
         sink_infra_id: Option<InfraIdentifier>,
     ) -> SeResult<Self> {
         Ok(Self {
             inner: Arc::new(
-                Neo4jDatabaseProvider::new(
+                Neo4jDatabaseProvider::with_options(
                     bolt_uri,
                     username,
                     password,
-                    max_pool_size,
+                    options,
                     sink_infra_id,
                 )
                 .await?,
@@ -115,6 +121,137 @@ impl Sinkable for Neo4jConnector {
 
 impl Sourceable for Neo4jConnector {
     fn get_supported_sinks(&self) -> Vec<Storage> {
-        vec![Storage::Neo4j, Storage::OfnilRow]
+        vec![Storage::Neo4j, Storage::Arrow, Storage::OfnilRow]
+    }
+
+    /// Produces the nodes of a vertex schema or the relationships of an edge schema, see
+    /// [source_query]
+    fn produce_rows(&self, type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+        Ok(Box::new(self.query_source(type_info)?))
+    }
+
+    /// Produces a batch of the nodes or relationships of the schema per pull
+    fn produce_batches(&self, type_info: Schema) -> SeResult<Box<dyn Source<RecordBatch>>> {
+        Ok(Box::new(self.query_source(type_info)?))
+    }
+}
+
+impl Neo4jConnector {
+    fn query_source(&self, type_info: Schema) -> SeResult<Neo4JQueryRowSource> {
+        Ok(Neo4JQueryRowSource::new(
+            self.get_database(),
+            CypherTransformationArgs::new(source_query(&type_info)?, type_info),
+            PULL_SIZE,
+        ))
+    }
+}
+
+/// Cypher query of the rows of `type_info`, which identifies the nodes by their primary keys
+fn source_query(type_info: &Schema) -> SeResult<String> {
+    let fields = |variable: &str, schema: &TabularSchema| {
+        schema
+            .field_names
+            .iter()
+            .map(|field| format!(", {variable}.{field}"))
+            .collect::<String>()
+    };
+    let tlabel = |schema: &TabularSchema| {
+        schema
+            .tlabel
+            .clone()
+            .ok_or_else(|| format!("Cannot read rows without a label. {schema:?}"))
+    };
+    Ok(match type_info {
+        Schema::Tabular(vertex) => format!(
+            "MATCH (n:{}) RETURN n.{}{}",
+            tlabel(vertex)?,
+            vertex
+                .primary_key
+                .as_ref()
+                .ok_or_else(|| format!("Cannot read vertices without a primary key. {vertex:?}"))?,
+            fields("n", vertex)
+        ),
+        Schema::Edge(edge) => format!(
+            "MATCH (s:{})-[e:{}]->(d:{}) RETURN s.{}, d.{}{}",
+            edge.src_vertex_tlabel,
+            tlabel(&edge.edge_info)?,
+            edge.dst_vertex_tlabel,
+            edge.src_vertex_primary_key,
+            edge.dst_vertex_primary_key,
+            fields("e", &edge.edge_info)
+        ),
+    })
+}
+
+#[async_trait::async_trait]
+impl Connector for Neo4jConnector {
+    fn as_storage(&self) -> Option<&dyn StorageConnector> {
+        Some(self)
+    }
+
+    fn clone_storage(&self) -> Option<Box<dyn StorageConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn as_transformation(&self) -> Option<&dyn TransformationConnector> {
+        Some(self)
+    }
+
+    fn clone_transformation(&self) -> Option<Box<dyn TransformationConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn schema_provider(&self) -> Option<Arc<dyn SchemaProvider>> {
+        Some(self.get_database())
+    }
+
+    /// Pings the server, and checks that the Graph Data Science library of the graph algorithms
+    /// is installed
+    async fn health_checks(&self) -> Vec<HealthCheck> {
+        let ping = self.inner.query("RETURN 1", None).await;
+        let ping = HealthCheck::new("ping", ping);
+        let gds = self.inner.query("RETURN gds.version()", None).await;
+        vec![ping, HealthCheck::new("gds", gds)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FeatureValueType;
+
+    #[test]
+    fn test_source_query() -> SeResult<()> {
+        let mut vertex = TabularSchema {
+            field_names: vec!["price".to_string()],
+            field_types: vec![FeatureValueType::Float],
+            tlabel: Some("Product".to_string()),
+            primary_key: Some("asin".to_string()),
+        };
+        assert_eq!(
+            source_query(&Schema::Tabular(vertex.clone()))?,
+            "MATCH (n:Product) RETURN n.asin, n.price"
+        );
+        let edge = Schema::Edge(EdgeSchema {
+            src_vertex_tlabel: "Reviewer".to_string(),
+            dst_vertex_tlabel: "Product".to_string(),
+            src_vertex_primary_key: "reviewerID".to_string(),
+            dst_vertex_primary_key: "asin".to_string(),
+            directed: true,
+            edge_info: TabularSchema {
+                field_names: Vec::new(),
+                field_types: Vec::new(),
+                tlabel: Some("Rates".to_string()),
+                primary_key: None,
+            },
+        });
+        assert_eq!(
+            source_query(&edge)?,
+            "MATCH (s:Reviewer)-[e:Rates]->(d:Product) RETURN s.reviewerID, d.asin"
+        );
+
+        vertex.primary_key = None;
+        assert!(source_query(&Schema::Tabular(vertex)).is_err());
+        Ok(())
     }
 }
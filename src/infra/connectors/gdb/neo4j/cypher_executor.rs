@@ -37,14 +37,15 @@ impl CypherExecutor {
 #[async_trait::async_trait]
 impl GraphComputationExecutor for CypherExecutor {
     async fn execute(&self, input: &TransformationIOT) -> SeResult<TransformationOutputHandler> {
-        if !handle_graph_input(input.first().unwrap(), &self.db, &self.source_types[0]) {
+        let input_graph = input.first().ok_or("Input graph is expected")?;
+        if !handle_graph_input(input_graph, &self.db, &self.source_types[0])? {
             return Ok(TransformationOutputHandler::EmptyOutput);
         }
+        let source = Neo4JQueryRowSource::new(self.db.clone(), self.args.clone(), PULL_SIZE);
         match self.sink_type {
-            Storage::OfnilRow => Ok(TransformationOutputHandler::TabularSource(Arc::new(
-                Neo4JQueryRowSource::new(self.db.clone(), self.args.clone(), PULL_SIZE),
-            ))),
-            _ => unimplemented!("Now only support in-process row data"),
+            Storage::OfnilRow => Ok(TransformationOutputHandler::TabularSource(Arc::new(source))),
+            Storage::Arrow => Ok(TransformationOutputHandler::BatchSource(Arc::new(source))),
+            _ => unimplemented!("Now only support in-process row and batch data"),
         }
     }
 }
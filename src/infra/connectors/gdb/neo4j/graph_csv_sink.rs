@@ -27,6 +27,7 @@ impl Neo4jCSVSink {
 
 pub struct Neo4jCSVWriter<'a> {
     bolt_conn: PooledConnection<'a, Manager>,
+    run_metadata: Option<Metadata>,
     schema: &'a Schema,
     periodic_commit: bool,
 }
@@ -43,6 +44,7 @@ impl Sink<File> for Neo4jCSVSink {
     async fn create_writer(&self) -> SeResult<Box<dyn Writer<File> + '_>> {
         Ok(Box::new(Neo4jCSVWriter {
             bolt_conn: self.db.get_bolt_connection().await?,
+            run_metadata: self.db.run_metadata(),
             schema: &self.schema,
             periodic_commit: false,
         }))
@@ -139,8 +141,12 @@ fn get_message(msg: &Message) -> String {
     }
 }
 
-async fn exec_query(query: &String, conn: &mut PooledConnection<'_, Manager>) -> SeResult<()> {
-    let result = conn.run(query, None, None).await.unwrap();
+async fn exec_query(
+    query: &String,
+    conn: &mut PooledConnection<'_, Manager>,
+    run_metadata: Option<Metadata>,
+) -> SeResult<()> {
+    let result = conn.run(query, None, run_metadata).await.unwrap();
     let msg = get_message(&result);
     if Success::try_from(result).is_err() {
         info!("Failed: {}", &msg);
@@ -230,7 +236,7 @@ impl<'a> Writer<File> for Neo4jCSVWriter<'a> {
                 query
             }
         };
-        exec_query(&csv_query, &mut self.bolt_conn).await
+        exec_query(&csv_query, &mut self.bolt_conn, self.run_metadata.clone()).await
     }
 }
 
@@ -258,6 +264,7 @@ mod test {
         exec_query(
             &create_index,
             &mut arc_db.get_bolt_connection().await.unwrap(),
+            arc_db.run_metadata(),
         )
         .await
         .unwrap();
@@ -275,6 +282,7 @@ mod test {
                 FeatureValueType::String,
             ],
             tlabel: Some(String::from("Product")),
+            primary_key: None,
         });
         let node_sink = Neo4jCSVSink::new(arc_db.clone(), node_schema);
         let mut node_writer = node_sink.create_writer().await.unwrap();
@@ -296,6 +304,7 @@ mod test {
                 field_names: vec![],
                 field_types: vec![],
                 tlabel: Some("is_similar".to_string()),
+                primary_key: None,
             },
         };
         let edge_sink = Neo4jCSVSink::new(arc_db.clone(), Schema::Edge(edge_schema));
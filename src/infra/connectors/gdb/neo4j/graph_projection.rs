@@ -1,9 +1,29 @@
-use super::{GraphProjectionArgs, Neo4jDatabaseProvider, Neo4jDatabaseProviderError};
+use super::{
+    GraphProjectionArgs, Neo4jConnector, Neo4jDatabaseProvider, Neo4jDatabaseProviderError,
+};
 use crate::{infra::pi::transformation::GAF, SeResult};
 use bb8_bolt::bolt_client::Metadata;
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use log::info;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// A graph projection created by ofnil that is left in the graph catalog
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StaleProjection {
+    pub graph_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Parses the name of a projection created by [GraphProjection], `{func}_{unix timestamp}`.
+/// Returns `None` for the graphs created by others.
+fn parse_graph_name(graph_name: &str) -> Option<(GAF, DateTime<Utc>)> {
+    let (func, timestamp) = graph_name.rsplit_once('_')?;
+    let func = func.parse::<GAF>().ok()?;
+    let created_at = Utc.timestamp_opt(timestamp.parse().ok()?, 0).single()?;
+    Some((func, created_at))
+}
+
 /// Handles neo4j graph projection
 /// TODO(tatiana): manage graph projections in the database provider?
 pub(crate) struct GraphProjection<'a> {
@@ -21,7 +41,7 @@ impl<'a> GraphProjection<'a> {
         Self {
             args,
             db,
-            graph_name: format!("{}_{}", func, chrono::Utc::now().timestamp()),
+            graph_name: format!("{}_{}", func, Utc::now().timestamp()),
         }
     }
 
@@ -75,7 +95,9 @@ impl<'a> GraphProjection<'a> {
         );
 
         let mut bolt_conn = self.db.get_bolt_connection().await?;
-        bolt_conn.run(named_graph_query.clone(), None, None).await?;
+        bolt_conn
+            .run(named_graph_query.clone(), None, self.db.run_metadata())
+            .await?;
         let (records, msg) = bolt_conn
             .pull(Some(Metadata::from_iter(vec![("n", 1)])))
             .await
@@ -99,3 +121,52 @@ impl<'a> GraphProjection<'a> {
         Ok(&self.graph_name)
     }
 }
+
+impl Neo4jConnector {
+    /// Finds the graph projections created by ofnil more than `max_age` ago, which graph
+    /// transformations leave in the graph catalog, and drops them unless `dry_run` is set.
+    /// Younger projections may still be read by running transformations and are kept.
+    pub async fn collect_stale_projections(
+        &self,
+        max_age: Duration,
+        dry_run: bool,
+    ) -> SeResult<Vec<StaleProjection>> {
+        let db = self.get_database();
+        let deadline = Utc::now() - max_age;
+        let mut stale = Vec::new();
+        for graph_name in db.list_named_graphs().await? {
+            match parse_graph_name(&graph_name) {
+                Some((_, created_at)) if created_at < deadline => stale.push(StaleProjection {
+                    graph_name,
+                    created_at,
+                }),
+                _ => {}
+            }
+        }
+        if !dry_run {
+            for projection in &stale {
+                info!("Dropping stale graph projection {}", projection.graph_name);
+                db.drop_named_graph(&projection.graph_name).await?;
+            }
+        }
+        Ok(stale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_graph_name() {
+        let (func, created_at) = parse_graph_name("page_rank_1700000000").unwrap();
+        assert_eq!(func, GAF::PageRank);
+        assert_eq!(created_at.timestamp(), 1700000000);
+        assert_eq!(
+            parse_graph_name("triangle_count_1").map(|(func, _)| func),
+            Some(GAF::TriangleCount)
+        );
+        assert!(parse_graph_name("my_graph").is_none());
+        assert!(parse_graph_name("page_rank").is_none());
+    }
+}
@@ -2,29 +2,32 @@ use std::sync::Arc;
 
 use crate::{
     infra::pi::storage::Storage, transformation::TransformationOutputHandler, InfraIdentifier,
+    SeResult,
 };
 
 use super::neo4j_database_provider::Neo4jDatabaseProvider;
 
+/// Checks that the input graph of a transformation is in Neo4j. Returns false if the input is
+/// empty, and an error if the graph is in another infra or given as data, as loading it into
+/// Neo4j is not supported yet.
 pub(super) fn handle_graph_input(
     input: &TransformationOutputHandler,
     _db: &Arc<Neo4jDatabaseProvider>,
     source_type: &Storage,
-) -> bool {
+) -> SeResult<bool> {
     match input {
-        TransformationOutputHandler::EmptyOutput => {
-            return false;
-        }
+        TransformationOutputHandler::EmptyOutput => Ok(false),
+        TransformationOutputHandler::InfraHandler {
+            infra_id: InfraIdentifier::Neo4j(_),
+        } => Ok(true),
         TransformationOutputHandler::InfraHandler { infra_id } => {
-            assert!(
-                matches!(infra_id, InfraIdentifier::Neo4j(_)),
-                "Input is expected to be in Neo4j, but got {infra_id:?}"
-            );
+            Err(format!("Input is expected to be in Neo4j, but got {infra_id:?}").into())
         }
         // TODO(tatiana): If input is topology source, load data into database
-        _ => {
-            unimplemented!("unexpected input {input:?} of type {:?}", source_type)
-        }
+        TransformationOutputHandler::InfraSource { infra_id, .. } => Err(format!(
+            "Loading the input graph in {infra_id:?} into Neo4j is not supported yet"
+        )
+        .into()),
+        _ => Err(format!("Unexpected input {input:?} of type {source_type:?}").into()),
     }
-    true
 }
@@ -5,6 +5,7 @@ mod plan_op_constant;
 
 use super::PULL_SIZE;
 use crate::{
+    config::Neo4jOptions,
     feature::EdgeEntity,
     feature::{ResourceOp, VertexEntity},
     fields,
@@ -14,14 +15,14 @@ use crate::{
 use bb8_bolt::{
     bb8::{ManageConnection, Pool, PooledConnection},
     bolt_client::error::{CommunicationError, ConnectionError, Error as ClientError},
-    bolt_client::Metadata,
+    bolt_client::{Metadata, Params},
     bolt_proto,
     bolt_proto::error::Error as ProtocolError,
     bolt_proto::message::Record,
     bolt_proto::{version::*, Value},
     Manager,
 };
-use log::{debug, error, info};
+use log::{error, info};
 use std::io::ErrorKind::{ConnectionAborted, ConnectionRefused};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::{collections::HashMap, error::Error};
@@ -34,6 +35,8 @@ pub struct Neo4jDatabaseProvider {
     node_field_resource: Arc<Mutex<Vec<Record>>>,
     rel_field_resource: Arc<Mutex<Vec<Record>>>,
     neo4j_infra_id: Option<InfraIdentifier>,
+    /// Database of the queries, or the default database of the server if `None`
+    database: Option<String>,
 }
 
 impl Neo4jDatabaseProvider {
@@ -44,9 +47,34 @@ impl Neo4jDatabaseProvider {
         max_pool_size: Option<u32>, // default: 128
         infra_id: Option<InfraIdentifier>,
     ) -> Result<Self, Box<dyn Error>> {
+        let options = Neo4jOptions {
+            max_pool_size,
+            ..Default::default()
+        };
+        Self::with_options(bolt_uri, username, password, &options, infra_id).await
+    }
+
+    /// Connects to the Bolt server at `bolt_uri`, `{host}:{port}`, with the TLS, database, pool
+    /// size and timeout of `options`.
+    pub async fn with_options(
+        bolt_uri: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+        options: &Neo4jOptions,
+        infra_id: Option<InfraIdentifier>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let bolt_uri = bolt_uri.into();
+        // the certificate of the server is verified against its host name
+        let domain = options.tls.then(|| {
+            bolt_uri
+                .rsplit_once(':')
+                .map_or(bolt_uri.as_str(), |(host, _)| host)
+                .to_string()
+        });
+        let connect_timeout = options.connect_timeout_secs.map(Duration::from_secs);
         let manager = Manager::new(
-            bolt_uri.into(),
-            None::<String>,
+            bolt_uri.as_str(),
+            domain,
             [V4_4, 0, 0, 0],
             Metadata::from_iter(vec![
                 ("user_agent", "ofnil-bolt/1.0"),
@@ -59,34 +87,36 @@ impl Neo4jDatabaseProvider {
 
         let mut count_retry: u32 = 0;
         loop {
-            match manager.connect().await {
-                Err(ClientError::ConnectionError(connection_err)) => {
-                    count_retry += 1;
-                    match connection_err {
-                        ConnectionError::HandshakeFailed(_) => {
+            let connected = match connect_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, manager.connect())
+                    .await
+                    .map_err(|_| {
+                        Neo4jDatabaseProviderError::BoltConnection(format!(
+                            "connecting to {bolt_uri} timed out after {timeout:?}"
+                        ))
+                    })?,
+                None => manager.connect().await,
+            };
+            match connected {
+                Err(ClientError::ConnectionError(connection_err)) => match connection_err {
+                    ConnectionError::HandshakeFailed(_) => {
+                        error!(
+                            "bolt connection manager handshake failed: {}",
+                            connection_err
+                        );
+                    }
+                    ConnectionError::IoError(io_error) => {
+                        if io_error.kind() == ConnectionRefused {
                             error!(
-                                "bolt connection manager handshake failed: {}",
-                                connection_err
+                                "bolt connection manager connection io error, neo4j server is not started or the given port is wrong: {:?}",
+                                io_error
                             );
+                        } else {
+                            error!("bolt connection manager connection io error {:?}", io_error);
                         }
-                        ConnectionError::IoError(io_error) => match io_error.kind() {
-                            ConnectionRefused => {
-                                error!(
-                                        "bolt connection manager connection io error, neo4j server is not started or the given port is wrong: {:?}",
-                                        io_error
-                                    );
-                                return Err(Box::new(ConnectionError::IoError(io_error)));
-                            }
-                            _ => {
-                                error!(
-                                    "bolt connection manager connection io error {:?}",
-                                    io_error
-                                );
-                                return Err(Box::new(ConnectionError::IoError(io_error)));
-                            }
-                        },
+                        return Err(Box::new(ConnectionError::IoError(io_error)));
                     }
-                }
+                },
                 Err(ClientError::CommunicationError(communication_err)) => {
                     match communication_err.as_ref() {
                         CommunicationError::InvalidResponse { .. } => {
@@ -103,101 +133,77 @@ impl Neo4jDatabaseProvider {
                         }
                         CommunicationError::UnsupportedOperation { .. }
                         | CommunicationError::ProtocolError { .. } => {
-                            panic!(
-                                "bolt connection manager communication error: {:?}",
-                                communication_err
-                            );
+                            return Err(Box::new(Neo4jDatabaseProviderError::BoltConnection(
+                                format!("communication error: {communication_err:?}"),
+                            )));
                         }
                         CommunicationError::IoError(io_error) => {
-                            match io_error.kind() {
-                                ConnectionAborted => {
-                                    let inner_err = io_error.get_ref().unwrap_or_else(|| {
-                                        panic!(
-                                            "bolt connection manager connection aborted : {:?}",
-                                            io_error
-                                        )
-                                    });
-                                    let err_str = inner_err.to_string();
-                                    let server_code = err_str
-                                        .split("\"code\": String(\"")
-                                        .nth(1)
-                                        .unwrap()
-                                        .split('\"')
-                                        .next()
-                                        .unwrap();
-                                    match server_code {
-                                        // TODO(Pond): support other error messages from server
-                                        // https://neo4j.com/docs/status-codes/current/errors/all-errors/
-                                        "Neo.ClientError.Security.Unauthorized" => panic!(
-                                            "bolt connection manager communication aborted, wrong username or password : {:?}", 
-                                            io_error),
-                                        _ => panic!(
-                                            "bolt connection manager connection aborted with code : {}",
-                                            server_code
-                                        )
-                                    }
-                                }
-                                _ => {
-                                    panic!(
-                                        "bolt connection manager communication io error : {:?}",
-                                        io_error
-                                    );
-                                }
+                            if io_error.kind() == ConnectionAborted {
+                                return Err(Box::new(aborted_error(io_error)));
                             }
+                            return Err(Box::new(Neo4jDatabaseProviderError::BoltConnection(
+                                format!("communication io error: {io_error:?}"),
+                            )));
                         }
                     }
                 }
-                Err(ClientError::ProtocolError(protocol_err)) => match protocol_err {
-                    ProtocolError::ConversionError(conversion_err) => {
-                        panic!(
-                            "bolt connection manager conversion error : {}",
-                            conversion_err
-                        );
-                    }
-                    ProtocolError::SerializationError(serialization_err) => {
-                        panic!(
-                            "bolt connection manager serialization error : {}",
-                            serialization_err
-                        );
-                    }
-                    ProtocolError::DeserializationError(deserialization_err) => {
-                        panic!(
-                            "bolt connection manager deserialization error : {}",
-                            deserialization_err
-                        );
-                    }
-                },
+                Err(ClientError::ProtocolError(protocol_err)) => {
+                    let message = match protocol_err {
+                        ProtocolError::ConversionError(e) => format!("conversion error: {e}"),
+                        ProtocolError::SerializationError(e) => {
+                            format!("serialization error: {e}")
+                        }
+                        ProtocolError::DeserializationError(e) => {
+                            format!("deserialization error: {e}")
+                        }
+                    };
+                    return Err(Box::new(Neo4jDatabaseProviderError::BoltConnection(
+                        message,
+                    )));
+                }
                 Ok(_) => {
                     info!("bolt connection manager handshake succeeded");
                     break;
                 }
             }
 
+            count_retry += 1;
             if count_retry == 3 {
-                panic!("bolt connection manager failed");
+                return Err(Box::new(Neo4jDatabaseProviderError::BoltConnection(
+                    format!("connecting to {bolt_uri} failed after {count_retry} attempts"),
+                )));
             }
             info!("retrying bolt connection manager");
             sleep(Duration::from_secs(2_u64.pow(count_retry))).await;
         }
 
         // Create a connection pool. This should be shared across your application.
-        let pool = Pool::builder()
-            .max_size(max_pool_size.unwrap_or(128))
-            .build(manager)
-            .await?;
+        let mut pool = Pool::builder().max_size(options.max_pool_size.unwrap_or(128));
+        if let Some(timeout) = connect_timeout {
+            pool = pool.connection_timeout(timeout);
+        }
+        let pool = pool.build(manager).await?;
 
         Ok(Self {
             bolt_conn_pool: pool,
             node_field_resource: Arc::new(Mutex::new(Vec::new())),
             rel_field_resource: Arc::new(Mutex::new(Vec::new())),
             neo4j_infra_id: infra_id,
+            database: options.database.clone(),
         })
     }
 
+    /// Metadata of the queries run on the configured database, to pass to `run` of connections
+    pub fn run_metadata(&self) -> Option<Metadata> {
+        self.database
+            .as_ref()
+            .map(|database| Metadata::from_iter(vec![("db", database.as_str())]))
+    }
+
     // A parser borrows a mutable reference of the connection to ensure exclusive usage for `execute` and `pull`
     pub async fn parse_query(&self, query: &str) -> Result<Neo4jQueryParser<'_>, Box<dyn Error>> {
         let bolt_conn = self.get_bolt_connection().await?;
-        Neo4jQueryParser::new(query, bolt_conn).await
+        Neo4jQueryParser::new(query, bolt_conn, self.run_metadata()).await
     }
 
     pub async fn get_bolt_connection(
@@ -218,7 +224,7 @@ impl Neo4jDatabaseProvider {
             format!("CALL gds.graph.exists('{graph_name}') YIELD graphName, exists");
         info!("check_graph_exists_query: {}", check_graph_exists_query);
         let msg = bolt_conn
-            .run(check_graph_exists_query, None, None)
+            .run(check_graph_exists_query, None, self.run_metadata())
             .await
             .unwrap();
         info!("check graph exists msg: {:?}", msg);
@@ -247,7 +253,10 @@ impl Neo4jDatabaseProvider {
 
         let drop_graph_query = format!("CALL gds.graph.drop('{graph_name}')");
         info!("drop_graph_query: {}", drop_graph_query);
-        let msg = bolt_conn.run(drop_graph_query, None, None).await.unwrap();
+        let msg = bolt_conn
+            .run(drop_graph_query, None, self.run_metadata())
+            .await
+            .unwrap();
         info!("drop graph query result: {:?}", msg);
 
         let (records, msg) = bolt_conn
@@ -259,46 +268,130 @@ impl Neo4jDatabaseProvider {
         Ok(())
     }
 
-    pub async fn get_id_mapping(
+    /// Runs `query` with `params` and collects all the returned records, together with the names
+    /// of the returned fields.
+    pub async fn query(
         &self,
-        node_label: &str,
-        primary_key: &str,
-    ) -> Result<HashMap<i64, String>, Box<dyn Error>> {
+        query: &str,
+        params: Option<Params>,
+    ) -> Result<(Vec<String>, Vec<Record>), Box<dyn Error>> {
         let mut bolt_conn = self.get_bolt_connection().await?;
+        let msg = bolt_conn.run(query, params, self.run_metadata()).await?;
+        let success = match bolt_proto::message::Success::try_from(msg) {
+            Ok(success) => success,
+            Err(msg) => {
+                // leave the connection in a usable state for the next borrower
+                bolt_conn.reset().await?;
+                return Err(format!("Query {query} failed: {msg:?}").into());
+            }
+        };
+        let fields = match success.metadata().get("fields") {
+            Some(Value::List(fields)) => fields
+                .iter()
+                .filter_map(|field| match field {
+                    Value::String(field) => Some(field.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
 
-        let mut id_mapping = HashMap::<i64, String>::new();
-        let id_mapping_query =
-            format!("MATCH (n:{node_label}) RETURN id(n) AS id, n.{primary_key} AS external_id");
-        info!("id_mapping_query: {}", id_mapping_query);
-        let msg = bolt_conn.run(id_mapping_query, None, None).await.unwrap();
-        info!("id mapping query result: {:?}", msg);
-        info!("constructing id mapping...");
+        let mut all_records = Vec::new();
         loop {
             let (records, msg) = bolt_conn
                 .pull(Some(Metadata::from_iter(vec![("n", PULL_SIZE)])))
-                .await
-                .unwrap();
-            let success = bolt_proto::message::Success::try_from(msg).unwrap();
+                .await?;
+            let success = bolt_proto::message::Success::try_from(msg)?;
+            all_records.extend(records);
+            if !(success.metadata().contains_key("has_more")
+                && success.metadata()["has_more"] == Value::Boolean(true))
+            {
+                break;
+            }
+        }
+        Ok((fields, all_records))
+    }
+
+    /// Lists the names of the graphs in the graph catalog. Requires neo4j.gds to be installed.
+    pub async fn list_named_graphs(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut bolt_conn = self.get_bolt_connection().await?;
+
+        let list_graphs_query = "CALL gds.graph.list() YIELD graphName RETURN graphName";
+        info!("list_graphs_query: {}", list_graphs_query);
+        let msg = bolt_conn
+            .run(list_graphs_query, None, self.run_metadata())
+            .await?;
+        info!("list graphs query result: {:?}", msg);
+
+        let mut graph_names = Vec::new();
+        loop {
+            let (records, msg) = bolt_conn
+                .pull(Some(Metadata::from_iter(vec![("n", PULL_SIZE)])))
+                .await?;
+            let success = bolt_proto::message::Success::try_from(msg)?;
             for record in records {
-                // info!("id mapping record: {:?}", record);
-                if let Value::Integer(id) = record.fields().get(0).unwrap() {
-                    if let Value::String(primary_key) = record.fields().get(1).unwrap() {
-                        debug!("id: {}, reviewer_id: {}", id, primary_key);
-                        id_mapping.insert(*id, primary_key.to_owned());
-                    }
+                if let Some(Value::String(graph_name)) = record.fields().first() {
+                    graph_names.push(graph_name.to_owned());
                 }
             }
-
             if !(success.metadata().contains_key("has_more")
                 && success.metadata()["has_more"] == Value::Boolean(true))
             {
                 break;
             }
         }
-        info!("id_mapping size: {:?}", id_mapping.len());
+        Ok(graph_names)
+    }
 
+    /// Gets the primary keys of the nodes of `node_label` by their internal ids. Keys that are not
+    /// strings are converted to strings, and a node without a key is an error.
+    pub async fn get_id_mapping(
+        &self,
+        node_label: &str,
+        primary_key: &str,
+    ) -> Result<HashMap<i64, String>, Box<dyn Error>> {
+        let id_mapping_query =
+            format!("MATCH (n:{node_label}) RETURN id(n) AS id, n.{primary_key} AS external_id");
+        info!("id_mapping_query: {}", id_mapping_query);
+        let (_, records) = self.query(&id_mapping_query, None).await?;
+        let id_mapping: HashMap<_, _> = records
+            .iter()
+            .map(|record| match record.fields() {
+                [Value::Integer(id), primary_key] => Ok((*id, key_to_string(primary_key, record)?)),
+                _ => Err(unexpected_record(record)),
+            })
+            .collect::<Result<_, _>>()?;
+        info!("id_mapping size: {:?}", id_mapping.len());
         Ok(id_mapping)
     }
+
+    /// Gets the primary keys of the src and dst nodes and the internal id of the relationships of
+    /// `edge`, whose src and dst nodes have primary keys `src_primary_key` and `dst_primary_key`.
+    /// The keys are converted to strings as in [Neo4jDatabaseProvider::get_id_mapping].
+    pub async fn get_edge_list(
+        &self,
+        edge: &EdgeEntity,
+        src_primary_key: &str,
+        dst_primary_key: &str,
+    ) -> Result<Vec<(String, String, i64)>, Box<dyn Error>> {
+        let edge_list_query = format!(
+            "MATCH (s:{})-[e:{}]->(d:{}) RETURN s.{src_primary_key}, d.{dst_primary_key}, id(e)",
+            edge.src_tlabel, edge.tlabel, edge.dst_tlabel
+        );
+        info!("edge_list_query: {}", edge_list_query);
+        let (_, records) = self.query(&edge_list_query, None).await?;
+        Ok(records
+            .iter()
+            .map(|record| match record.fields() {
+                [src, dst, Value::Integer(id)] => Ok((
+                    key_to_string(src, record)?,
+                    key_to_string(dst, record)?,
+                    *id,
+                )),
+                _ => Err(unexpected_record(record)),
+            })
+            .collect::<Result<_, _>>()?)
+    }
 }
 
 #[async_trait::async_trait]
@@ -348,7 +441,7 @@ impl SchemaProvider for Neo4jDatabaseProvider {
         let get_rel_fields_query = "CALL db.schema.nodeTypeProperties()".to_string();
         info!("node_type_properites_query: {}", get_rel_fields_query);
         let _msg = bolt_conn
-            .run(get_rel_fields_query, None, None)
+            .run(get_rel_fields_query, None, self.run_metadata())
             .await
             .unwrap();
         let mut record_collector = Vec::new();
@@ -382,7 +475,7 @@ impl SchemaProvider for Neo4jDatabaseProvider {
         let get_rel_fields_query = "CALL db.schema.relTypeProperties()".to_string();
         info!("node_type_properites_query: {}", get_rel_fields_query);
         let _msg = bolt_conn
-            .run(get_rel_fields_query, None, None)
+            .run(get_rel_fields_query, None, self.run_metadata())
             .await
             .unwrap();
         let mut record_collector = Vec::new();
@@ -416,7 +509,10 @@ impl SchemaProvider for Neo4jDatabaseProvider {
         let mut all_entities = Vec::new();
         let all_entities_query = "CALL db.schema.visualization".to_string();
         info!("all_entities_query: {}", all_entities_query);
-        let msg = bolt_conn.run(all_entities_query, None, None).await.unwrap();
+        let msg = bolt_conn
+            .run(all_entities_query, None, self.run_metadata())
+            .await
+            .unwrap();
         info!("all entities query result: {:?}", msg);
         info!("constructing all entities...");
         let mut vertex_id2vertex = HashMap::<String, Entity>::new();
@@ -442,6 +538,7 @@ impl SchemaProvider for Neo4jDatabaseProvider {
                                 tlabel: ((node_entity.labels()[0]).to_owned()),
                                 primary_key: "id".to_string(), // a dummy primary key, to be specified according to the properties
                                 variant: Variant::Default(),
+                                deprecated: false,
                             });
                             vertex_id2vertex.insert(
                                 (node_entity.node_identity()).to_string(),
@@ -470,6 +567,7 @@ impl SchemaProvider for Neo4jDatabaseProvider {
                                 dst_entity_id: _dst_vertex_entity.resource_id(),
                                 directed: false,
                                 primary_key: None,
+                                deprecated: false,
                             });
                             all_entities.push(temp_entity);
                         }
@@ -569,6 +667,47 @@ impl SchemaProvider for Neo4jDatabaseProvider {
     }
 }
 
+/// Converts the primary key `value` of a node in `record` to a string. Null keys and keys of types
+/// other than strings, numbers and booleans are errors.
+fn key_to_string(value: &Value, record: &Record) -> Result<String, Neo4jDatabaseProviderError> {
+    match value {
+        Value::String(key) => Ok(key.to_owned()),
+        Value::Integer(key) => Ok(key.to_string()),
+        Value::Float(key) => Ok(key.to_string()),
+        Value::Boolean(key) => Ok(key.to_string()),
+        _ => Err(unexpected_record(record)),
+    }
+}
+
+fn unexpected_record(record: &Record) -> Neo4jDatabaseProviderError {
+    Neo4jDatabaseProviderError::UnexpectedRecord(format!("{:?}", record.fields()))
+}
+
+/// Error of a connection aborted by the server, which gives the reason as a status code, see
+/// https://neo4j.com/docs/status-codes/current/errors/all-errors/
+fn aborted_error(io_error: &std::io::Error) -> Neo4jDatabaseProviderError {
+    let server_code = io_error.get_ref().and_then(|inner| {
+        let message = inner.to_string();
+        let code = message
+            .split("\"code\": String(\"")
+            .nth(1)?
+            .split('\"')
+            .next()?;
+        Some(code.to_string())
+    });
+    match server_code.as_deref() {
+        Some("Neo.ClientError.Security.Unauthorized") => {
+            Neo4jDatabaseProviderError::Unauthorized(io_error.to_string())
+        }
+        Some(code) => Neo4jDatabaseProviderError::BoltConnection(format!(
+            "connection aborted with code {code}"
+        )),
+        None => {
+            Neo4jDatabaseProviderError::BoltConnection(format!("connection aborted: {io_error:?}"))
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Neo4jDatabaseProviderError {
     #[error("Bolt message does not match provider implementation. {0}")]
@@ -578,8 +717,45 @@ pub enum Neo4jDatabaseProviderError {
     // TODO(han): refactor: clippy thinks all variants with `*Error` suffix are redundant, refactor to make things consistent later
     #[error("Error getting bolt connection from pool. {0}")]
     BoltConnection(String),
+    #[error("Bolt server rejected the username or password. {0}")]
+    Unauthorized(String),
     #[error("Error projecting graph. Query: {query}. Error message: {error_msg}")]
     GraphProjection { query: String, error_msg: String },
+    #[error("Record of an unexpected null or type of value. {0}")]
+    UnexpectedRecord(String),
+}
+
+#[test]
+fn test_key_to_string() {
+    let record = Record::new(vec![
+        Value::Integer(0),
+        Value::Integer(42),
+        Value::String("a,b".to_string()),
+        Value::Null,
+    ]);
+    let key = |idx: usize| key_to_string(&record.fields()[idx], &record);
+    assert_eq!(key(1).unwrap(), "42");
+    assert_eq!(key(2).unwrap(), "a,b");
+    assert!(matches!(
+        key(3),
+        Err(Neo4jDatabaseProviderError::UnexpectedRecord(message)) if message.contains("Null")
+    ));
+}
+
+#[test]
+fn test_aborted_error() {
+    let aborted = |code: &str| {
+        let message = format!("{{\"code\": String(\"{code}\"), \"message\": String(\"\")}}");
+        aborted_error(&std::io::Error::new(ConnectionAborted, message))
+    };
+    assert!(matches!(
+        aborted("Neo.ClientError.Security.Unauthorized"),
+        Neo4jDatabaseProviderError::Unauthorized(_)
+    ));
+    assert!(matches!(
+        aborted("Neo.ClientError.Security.AuthenticationRateLimit"),
+        Neo4jDatabaseProviderError::BoltConnection(message) if message.ends_with("AuthenticationRateLimit")
+    ));
 }
 
 #[tokio::test]
@@ -20,6 +20,7 @@ pub struct Neo4jQueryParser<'a> {
     query: String,
     // TODO: guarantee correctness as the underlying bolt connection cannot be shared among async queries.
     bolt_conn: PooledConnection<'a, Manager>,
+    run_metadata: Option<Metadata>,
     plan: Option<(String, PlanOp)>, // db, ops
     returned_fields: Option<Vec<String>>,
     failure: Option<(String, String)>, // code, message
@@ -29,10 +30,12 @@ impl<'a> Neo4jQueryParser<'a> {
     pub async fn new(
         query: &str,
         bolt_conn: PooledConnection<'a, Manager>,
+        run_metadata: Option<Metadata>,
     ) -> Result<Neo4jQueryParser<'a>, Box<dyn Error>> {
         let mut res = Self {
             query: query.to_string(),
             bolt_conn,
+            run_metadata,
             plan: None,
             returned_fields: None,
             failure: None,
@@ -47,7 +50,10 @@ impl<'a> Neo4jQueryParser<'a> {
         } else {
             self.query.clone()
         };
-        let message = self.bolt_conn.run(&query, None, None).await?;
+        let message = self
+            .bolt_conn
+            .run(&query, None, self.run_metadata.clone())
+            .await?;
         match message {
             Message::Success(success) => {
                 if let Some(fields) = success.metadata().get("fields") {
@@ -29,6 +29,7 @@ impl Sink<Row> for Neo4jEdgeSink {
     async fn create_writer(&self) -> SeResult<Box<dyn Writer<Row> + '_>> {
         Ok(Box::new(Neo4jEdgeWriter {
             bolt_conn: self.db.get_bolt_connection().await?,
+            run_metadata: self.db.run_metadata(),
             edge_schema: &self.edge_schema,
         }))
     }
@@ -36,6 +37,7 @@ impl Sink<Row> for Neo4jEdgeSink {
 
 pub(super) struct Neo4jEdgeWriter<'a> {
     bolt_conn: PooledConnection<'a, Manager>,
+    run_metadata: Option<Metadata>,
     edge_schema: &'a EdgeSchema,
 }
 
@@ -80,7 +82,7 @@ impl<'a> Writer<Row> for Neo4jEdgeWriter<'a> {
                      dst_primary_key = self.edge_schema.dst_vertex_primary_key
                 ),
                 Some(Params::from_iter(vec![("props", edge_properties)])),
-                None,
+                self.run_metadata.clone(),
             )
             .await
             .unwrap();
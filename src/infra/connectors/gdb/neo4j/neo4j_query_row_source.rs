@@ -1,12 +1,13 @@
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray};
 use bb8_bolt::{
     bb8::PooledConnection,
     bolt_client::Metadata,
-    bolt_proto::{message, Value},
+    bolt_proto::{message, message::Record, Value},
     Manager,
 };
 use log::info;
 
-use crate::transformation::transformation_args::CypherTransformationArgs;
+use crate::{transformation::transformation_args::CypherTransformationArgs, FeatureValueType};
 
 use super::*;
 
@@ -40,6 +41,7 @@ impl Source<Row> for Neo4JQueryRowSource {
                 self.db.get_bolt_connection().await?,
                 self.cypher_args.query.clone(),
                 self.pull_size,
+                self.db.run_metadata(),
             )
             .await?,
         ))
@@ -60,9 +62,10 @@ impl<'a> Neo4JQueryRowReader<'a> {
         mut bolt_conn: PooledConnection<'a, Manager>,
         query: String,
         pull_size: i32,
+        run_metadata: Option<Metadata>,
     ) -> SeResult<Neo4JQueryRowReader<'a>> {
         info!("run query {query}");
-        let msg = bolt_conn.run(query, None, None).await?;
+        let msg = bolt_conn.run(query, None, run_metadata).await?;
         info!("run query result message {msg:?}");
         Ok(Self {
             bolt_conn,
@@ -71,35 +74,159 @@ impl<'a> Neo4JQueryRowReader<'a> {
     }
 }
 
-#[async_trait::async_trait(?Send)]
-impl<'a> Reader<Row> for Neo4JQueryRowReader<'a> {
-    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+impl<'a> Neo4JQueryRowReader<'a> {
+    /// Pulls the next records. Returns them with whether there are more records.
+    async fn pull(&mut self) -> SeResult<(Vec<Record>, bool)> {
         let (records, msg) = self
             .bolt_conn
             .pull(Some(Metadata::from_iter(vec![("n", self.pull_size)])))
             .await?;
         let success = message::Success::try_from(msg).unwrap();
-        for record in records {
-            if let Value::String(id) = record.fields().get(0).unwrap() {
-                let row_fields = std::iter::once(RowCell::String(id.clone()))
-                    .chain(record.fields().iter().skip(1).map(|field| {
-                        match field {
-                            // TODO(tatiana): a systematic way to handle nulls
-                            Value::Null => RowCell::Null,
-                            Value::Float(val) => RowCell::Double(*val),
-                            Value::Boolean(val) => RowCell::Boolean(*val),
-                            Value::Integer(val) => RowCell::Int(*val),
-                            Value::String(val) => RowCell::String(val.clone()),
-                            _ => unimplemented!("value type not supported yet, got {field:?}"),
-                        }
-                    }))
-                    .collect();
-                output.collect(Row::new(row_fields)).await?;
-            } else {
-                unimplemented!("now only support query that returns an ID and float field(s)")
+        let has_more = success.metadata().contains_key("has_more")
+            && success.metadata()["has_more"] == Value::Boolean(true);
+        Ok((records, has_more))
+    }
+}
+
+/// Converts a record that returns an ID followed by the fields to the cells of a row
+fn to_cells(record: &Record) -> impl Iterator<Item = RowCell> + '_ {
+    let Some(Value::String(id)) = record.fields().first() else {
+        unimplemented!("now only support query that returns an ID and float field(s)")
+    };
+    std::iter::once(RowCell::String(id.clone())).chain(record.fields().iter().skip(1).map(
+        |field| {
+            match field {
+                // TODO(tatiana): a systematic way to handle nulls
+                Value::Null => RowCell::Null,
+                Value::Float(val) => RowCell::Double(*val),
+                Value::Boolean(val) => RowCell::Boolean(*val),
+                Value::Integer(val) => RowCell::Int(*val),
+                Value::String(val) => RowCell::String(val.clone()),
+                _ => unimplemented!("value type not supported yet, got {field:?}"),
             }
+        },
+    ))
+}
+
+#[async_trait::async_trait(?Send)]
+impl<'a> Reader<Row> for Neo4JQueryRowReader<'a> {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        let (records, has_more) = self.pull().await?;
+        for record in records {
+            output
+                .collect(Row::new(to_cells(&record).collect()))
+                .await?;
+        }
+        Ok(has_more)
+    }
+}
+
+/// Outputs a batch of the records of each pull
+#[async_trait::async_trait(?Send)]
+impl Source<RecordBatch> for Neo4JQueryRowSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<RecordBatch> + '_>> {
+        Ok(Box::new(Neo4JQueryBatchReader {
+            inner: Neo4JQueryRowReader::new(
+                self.db.get_bolt_connection().await?,
+                self.cypher_args.query.clone(),
+                self.pull_size,
+                self.db.run_metadata(),
+            )
+            .await?,
+            schema: &self.cypher_args.output_schema,
+        }))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.cypher_args.output_schema
+    }
+}
+
+pub struct Neo4JQueryBatchReader<'a> {
+    inner: Neo4JQueryRowReader<'a>,
+    schema: &'a Schema,
+}
+
+/// Gets the values of column `idx` of `records`, converting the non-null values by `convert`
+fn column_values<'r, T>(
+    records: &'r [Record],
+    idx: usize,
+    value_type: &FeatureValueType,
+    convert: impl Fn(&'r Value) -> Option<T>,
+) -> SeResult<Vec<Option<T>>> {
+    records
+        .iter()
+        .map(|record| match record.fields().get(idx) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => convert(value).map(Some).ok_or_else(|| {
+                format!("Expect a {value_type:?} value in column {idx}, but got {value:?}").into()
+            }),
+        })
+        .collect()
+}
+
+/// Converts column `idx` of `records` to an array of `value_type`
+fn to_array(records: &[Record], idx: usize, value_type: &FeatureValueType) -> SeResult<ArrayRef> {
+    Ok(match value_type {
+        FeatureValueType::Int => Arc::new(Int64Array::from(column_values(
+            records,
+            idx,
+            value_type,
+            |value| match value {
+                Value::Integer(value) => Some(*value),
+                _ => None,
+            },
+        )?)),
+        FeatureValueType::Float => Arc::new(Float64Array::from(column_values(
+            records,
+            idx,
+            value_type,
+            |value| match value {
+                Value::Float(value) => Some(*value),
+                Value::Integer(value) => Some(*value as f64),
+                _ => None,
+            },
+        )?)),
+        FeatureValueType::Boolean => Arc::new(BooleanArray::from(column_values(
+            records,
+            idx,
+            value_type,
+            |value| match value {
+                Value::Boolean(value) => Some(*value),
+                _ => None,
+            },
+        )?)),
+        FeatureValueType::String => Arc::new(StringArray::from(column_values(
+            records,
+            idx,
+            value_type,
+            |value| match value {
+                Value::String(value) => Some(value.as_str()),
+                _ => None,
+            },
+        )?)),
+        _ => return Err(format!("{value_type:?} values are not supported yet").into()),
+    })
+}
+
+#[async_trait::async_trait(?Send)]
+impl<'a> Reader<RecordBatch> for Neo4JQueryBatchReader<'a> {
+    /// Converts the records of each pull to a batch column by column, in which the first column
+    /// is the ID of each record
+    async fn next(&mut self, output: &mut dyn Collector<RecordBatch>) -> SeResult<bool> {
+        let (records, has_more) = self.inner.pull().await?;
+        if !records.is_empty() {
+            let columns = self
+                .schema
+                .columns()
+                .iter()
+                .enumerate()
+                .map(|(idx, (_, value_type))| to_array(&records, idx, value_type))
+                .collect::<SeResult<_>>()?;
+            output
+                .collect(RecordBatch::try_new(self.schema.arrow_schema(), columns)?)
+                .await?;
         }
-        Ok(success.metadata().contains_key("has_more")
-            && success.metadata()["has_more"] == Value::Boolean(true))
+        Ok(has_more)
     }
 }
@@ -122,8 +122,8 @@ impl TopologyToVFExecutor {
 #[async_trait::async_trait]
 impl GraphComputationExecutor for TopologyToVFExecutor {
     async fn execute(&self, input: &TransformationIOT) -> SeResult<TransformationOutputHandler> {
-        let input_graph = input.first().expect("Input graph is expected");
-        if !handle_graph_input(input_graph, &self.db, &self.source_types[0]) {
+        let input_graph = input.first().ok_or("Input graph is expected")?;
+        if !handle_graph_input(input_graph, &self.db, &self.source_types[0])? {
             return Ok(TransformationOutputHandler::EmptyOutput);
         }
 
@@ -139,25 +139,24 @@ impl GraphComputationExecutor for TopologyToVFExecutor {
         };
 
         let query = self.get_query(args, projected_graph);
+        let source = Neo4JQueryRowSource::new(
+            self.db.clone(),
+            CypherTransformationArgs::new(
+                query,
+                Schema::Tabular(TabularSchema {
+                    tlabel: Some(args.target_vertex_tlabel.clone()),
+                    field_names: args.output_names.clone(),
+                    // TODO(tatiana): consider other feature types here?
+                    field_types: vec![FeatureValueType::Float; args.output_names.len()],
+                    primary_key: Some(args.target_vertex_primary_key.clone()),
+                }),
+            ),
+            PULL_SIZE,
+        );
         match self.sink_type {
-            Storage::OfnilRow => {
-                Ok(TransformationOutputHandler::TabularSource(Arc::new(
-                    Neo4JQueryRowSource::new(
-                        self.db.clone(),
-                        CypherTransformationArgs::new(
-                            query,
-                            Schema::Tabular(TabularSchema {
-                                tlabel: Some(args.target_vertex_tlabel.clone()),
-                                field_names: args.output_names.clone(),
-                                // TODO(tatiana): consider other feature types here?
-                                field_types: vec![FeatureValueType::Float; args.output_names.len()],
-                            }),
-                        ),
-                        PULL_SIZE,
-                    ),
-                )))
-            }
-            _ => unimplemented!("Now only support in-process row format"),
+            Storage::OfnilRow => Ok(TransformationOutputHandler::TabularSource(Arc::new(source))),
+            Storage::Arrow => Ok(TransformationOutputHandler::BatchSource(Arc::new(source))),
+            _ => unimplemented!("Now only support in-process row and batch formats"),
         }
     }
 }
@@ -1,15 +1,35 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use crate::infra::pi::{storage::*, Sinkable, Sourceable};
+use redis::{ConnectionAddr, IntoConnectionInfo};
+
+use crate::{
+    config::RedisOptions,
+    infra::health::HealthCheck,
+    infra::pi::{storage::*, Connector, Sinkable, Sourceable, StorageConnector},
+    SeResult,
+};
 
 // submodules
 mod redis_row_sink;
 use redis_row_sink::*;
+mod redis_row_source;
+use redis_row_source::*;
+
+/// Key of the value of field `name` of vertex or edge `id` of label `tlabel`
+fn get_key(tlabel: &str, name: &str, id: &str) -> String {
+    format!("{tlabel}/{name}/{id}")
+}
 
 // TODO(han): enable async redis client
+/// Connector of a Redis server, which keeps each field of the rows of a label as a key-value
+/// pair of key `{tlabel}/{field}/{id}`
 #[derive(Debug, Clone)]
 pub struct RedisConnector {
     client: Arc<Mutex<redis::Client>>,
+    options: RedisOptions,
 }
 
 pub fn get_connection_arc(
@@ -19,14 +39,63 @@ pub fn get_connection_arc(
 }
 
 impl RedisConnector {
-    pub fn new(uri: impl redis::IntoConnectionInfo) -> Self {
+    pub fn new(uri: impl IntoConnectionInfo) -> Self {
         Self {
             client: Arc::new(Mutex::new(redis::Client::open(uri).unwrap())),
+            options: RedisOptions::default(),
         }
     }
 
+    /// Creates a connector of the Redis server at `uri` with the credentials, database, TLS and
+    /// timeouts of `options`, which override those given in `uri`.
+    pub fn with_options(
+        uri: impl IntoConnectionInfo,
+        username: Option<String>,
+        password: Option<String>,
+        options: &RedisOptions,
+    ) -> redis::RedisResult<Self> {
+        let mut info = uri.into_connection_info()?;
+        if username.is_some() {
+            info.redis.username = username;
+        }
+        if password.is_some() {
+            info.redis.password = password;
+        }
+        if options.db != 0 {
+            info.redis.db = options.db;
+        }
+        if options.tls {
+            if let ConnectionAddr::Tcp(host, port) = info.addr {
+                info.addr = ConnectionAddr::TcpTls {
+                    host,
+                    port,
+                    insecure: false,
+                };
+            }
+        }
+        Ok(Self {
+            client: Arc::new(Mutex::new(redis::Client::open(info)?)),
+            options: options.clone(),
+        })
+    }
+
     pub fn get_connection(&self) -> redis::Connection {
-        get_connection_arc(&self.client).unwrap()
+        self.try_get_connection().unwrap()
+    }
+
+    /// Connects with the timeouts of the connector options
+    pub fn try_get_connection(&self) -> redis::RedisResult<redis::Connection> {
+        let conn = match self.options.connect_timeout_secs {
+            Some(secs) => self
+                .client
+                .lock()
+                .unwrap()
+                .get_connection_with_timeout(Duration::from_secs(secs))?,
+            None => get_connection_arc(&self.client)?,
+        };
+        conn.set_read_timeout(self.options.read_timeout_secs.map(Duration::from_secs))?;
+        conn.set_write_timeout(self.options.write_timeout_secs.map(Duration::from_secs))?;
+        Ok(conn)
     }
 
     pub fn get_client(&self) -> Arc<Mutex<redis::Client>> {
@@ -36,11 +105,15 @@ impl RedisConnector {
 
 impl Sinkable for RedisConnector {
     fn get_supported_sources(&self) -> Vec<Storage> {
-        vec![Storage::OfnilRow]
+        vec![Storage::Arrow, Storage::OfnilRow]
     }
 
     fn insert_rows(&self, type_info: Schema) -> Box<dyn Sink<Row>> {
-        Box::new(RedisRowSink::new(self.get_client(), type_info))
+        Box::new(RedisRowSink::new(self.clone(), type_info))
+    }
+
+    fn insert_batches(&self, type_info: Schema) -> Box<dyn Sink<RecordBatch>> {
+        Box::new(RedisBatchSink::new(self.clone(), type_info))
     }
 }
 
@@ -48,6 +121,37 @@ impl Sourceable for RedisConnector {
     fn get_supported_sinks(&self) -> Vec<Storage> {
         vec![Storage::OfnilRow]
     }
+
+    /// Reads the rows of a tabular schema by scanning the keys of its label, decoding the
+    /// values by the field types of the schema
+    fn produce_rows(&self, type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+        Ok(Box::new(RedisRowSource::new(self.clone(), type_info)))
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for RedisConnector {
+    fn as_storage(&self) -> Option<&dyn StorageConnector> {
+        Some(self)
+    }
+
+    fn clone_storage(&self) -> Option<Box<dyn StorageConnector>> {
+        Some(Box::new(self.clone()))
+    }
+
+    async fn health_checks(&self) -> Vec<HealthCheck> {
+        let connector = self.clone();
+        let ping = tokio::task::spawn_blocking(move || {
+            let mut conn = connector.try_get_connection()?;
+            redis::cmd("PING").query::<String>(&mut conn)
+        })
+        .await;
+        let ping = match ping {
+            Ok(ping) => HealthCheck::new("ping", ping),
+            Err(e) => HealthCheck::new("ping", Err::<String, _>(e)),
+        };
+        vec![ping]
+    }
 }
 
 #[cfg(test)]
@@ -66,3 +170,29 @@ async fn test_redis() {
         assert_eq!(val, "456");
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_redis_options() {
+    let options = RedisOptions {
+        tls: true,
+        db: 3,
+        ..Default::default()
+    };
+    let connector = RedisConnector::with_options(
+        "redis://localhost:6379",
+        Some("ofnil".to_string()),
+        Some("secret".to_string()),
+        &options,
+    )
+    .unwrap();
+    let client = connector.get_client();
+    let info = client.lock().unwrap().get_connection_info().clone();
+    assert!(matches!(
+        info.addr,
+        ConnectionAddr::TcpTls { port: 6379, .. }
+    ));
+    assert_eq!(info.redis.db, 3);
+    assert_eq!(info.redis.username.as_deref(), Some("ofnil"));
+    assert_eq!(info.redis.password.as_deref(), Some("secret"));
+}
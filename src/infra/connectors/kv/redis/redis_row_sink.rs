@@ -1,18 +1,24 @@
-use crate::{infra::pi::storage::*, SeResult};
+use crate::{infra::pi::storage::*, FeatureValueType, SeResult};
 
-use super::get_connection_arc;
+use super::{get_key, RedisConnector};
+use arrow::{
+    array::{Array, AsArray, StringArray},
+    datatypes::{DataType, Float64Type, Int64Type, UInt64Type},
+};
 use redis::{Commands, NumericBehavior, ToRedisArgs};
-use std::sync::{Arc, Mutex};
 
 #[derive(Debug)]
 pub struct RedisRowSink {
-    client: Arc<Mutex<redis::Client>>,
+    connector: RedisConnector,
     type_info: Schema,
 }
 
 impl RedisRowSink {
-    pub(super) fn new(client: Arc<Mutex<redis::Client>>, type_info: Schema) -> Self {
-        Self { client, type_info }
+    pub(super) fn new(connector: RedisConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
     }
 }
 
@@ -20,7 +26,7 @@ impl RedisRowSink {
 impl Sink<Row> for RedisRowSink {
     async fn create_writer(&self) -> SeResult<Box<dyn Writer<Row> + '_>> {
         Ok(Box::new(RedisRowWriter::new(
-            get_connection_arc(&self.client)?,
+            self.connector.try_get_connection()?,
             self.type_info.clone(),
         )))
     }
@@ -29,6 +35,7 @@ impl Sink<Row> for RedisRowSink {
 pub struct RedisRowWriter {
     redis_conn: redis::Connection,
     field_names: Vec<String>,
+    field_types: Vec<FeatureValueType>,
     tlabel: String,
 }
 
@@ -41,6 +48,7 @@ impl RedisRowWriter {
                 .tlabel
                 .expect("now assume all tabular data are associated with a vertex/edge"),
             field_names: tabular_schema.field_names,
+            field_types: tabular_schema.field_types,
         }
     }
 }
@@ -54,7 +62,7 @@ impl Writer<Row> for RedisRowWriter {
         let id = record.get(0).string();
         debug_assert_eq!(record.len(), self.field_names.len() + 1); // first element in record is id
         for (idx, name) in self.field_names.iter().enumerate() {
-            let key = format!("{}/{name}/{id}", self.tlabel);
+            let key = get_key(&self.tlabel, name, id);
             let value = record.get(idx + FIELD_OFFSET);
             if let RowCell::Null = value {
                 // TODO(tatiana): handle null
@@ -66,6 +74,112 @@ impl Writer<Row> for RedisRowWriter {
     }
 }
 
+#[derive(Debug)]
+pub struct RedisBatchSink {
+    connector: RedisConnector,
+    type_info: Schema,
+}
+
+impl RedisBatchSink {
+    pub(super) fn new(connector: RedisConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Sink<RecordBatch> for RedisBatchSink {
+    async fn create_writer(&self) -> SeResult<Box<dyn Writer<RecordBatch> + '_>> {
+        Ok(Box::new(RedisBatchWriter {
+            inner: RedisRowWriter::new(
+                self.connector.try_get_connection()?,
+                self.type_info.clone(),
+            ),
+        }))
+    }
+}
+
+/// Writes the key-value pairs of each batch in a pipeline, in the layout of [RedisRowWriter]
+pub struct RedisBatchWriter {
+    inner: RedisRowWriter,
+}
+
+/// Adds the non-null `values` of field `name` of the vertices `ids` to `pipe`
+fn set_all<T: ToRedisArgs>(
+    pipe: &mut redis::Pipeline,
+    tlabel: &str,
+    name: &str,
+    ids: &StringArray,
+    values: impl IntoIterator<Item = Option<T>>,
+) {
+    for (id, value) in ids.iter().zip(values) {
+        if let (Some(id), Some(value)) = (id, value) {
+            pipe.set(get_key(tlabel, name, id), value).ignore();
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Writer<RecordBatch> for RedisBatchWriter {
+    async fn write(&mut self, record: RecordBatch) -> SeResult<()> {
+        let RedisRowWriter {
+            redis_conn,
+            field_names,
+            field_types,
+            tlabel,
+        } = &mut self.inner;
+        let columns = record.columns();
+        debug_assert_eq!(columns.len(), field_names.len() + 1); // first column is id
+        let ids = columns[0]
+            .as_string_opt::<i32>()
+            .ok_or_else(|| format!("Expect a column of ids, but got {:?}", columns[0]))?;
+        let mut pipe = redis::pipe();
+        for ((name, value_type), column) in field_names.iter().zip(field_types).zip(&columns[1..]) {
+            match column.data_type() {
+                DataType::Utf8 => set_all(&mut pipe, tlabel, name, ids, column.as_string::<i32>()),
+                DataType::Int64 => set_all(
+                    &mut pipe,
+                    tlabel,
+                    name,
+                    ids,
+                    column.as_primitive::<Int64Type>(),
+                ),
+                DataType::Float64 => set_all(
+                    &mut pipe,
+                    tlabel,
+                    name,
+                    ids,
+                    column.as_primitive::<Float64Type>(),
+                ),
+                DataType::Boolean => set_all(&mut pipe, tlabel, name, ids, column.as_boolean()),
+                DataType::UInt64 => set_all(
+                    &mut pipe,
+                    tlabel,
+                    name,
+                    ids,
+                    column.as_primitive::<UInt64Type>(),
+                ),
+                DataType::List(_) => {
+                    let values = (0..column.len())
+                        .map(|idx| {
+                            let cell = get_cell(column.as_ref(), idx, value_type)?;
+                            Ok((cell != RowCell::Null).then_some(cell))
+                        })
+                        .collect::<SeResult<Vec<_>>>()?;
+                    set_all(&mut pipe, tlabel, name, ids, values)
+                }
+                data_type => {
+                    return Err(format!("Cannot write values of {data_type:?} to Redis").into())
+                }
+            }
+        }
+        pipe.query::<()>(redis_conn)?;
+        Ok(())
+    }
+}
+
 // we do not encode the type info in redis value but rely on the schema info in registry
 impl ToRedisArgs for RowCell {
     fn write_redis_args<W>(&self, out: &mut W)
@@ -83,13 +197,8 @@ impl ToRedisArgs for RowCell {
             RowCell::Int(value) => value.write_redis_args(out),
             RowCell::Boolean(value) => value.write_redis_args(out),
             RowCell::Duration(value) => value.write_redis_args(out),
-            // TODO(tatiana): array need to be interpreted as a single arg if redis SET is used. Consider a more suitable way to handle array
-            RowCell::Array(vec) => out.write_arg_fmt(
-                vec.iter()
-                    .map(|e| e.to_string())
-                    .collect::<Vec<_>>()
-                    .join(","),
-            ),
+            // an array is a single arg, as a JSON array
+            RowCell::Array(_) => out.write_arg(self.encode().as_bytes()),
         }
     }
 
@@ -105,7 +214,7 @@ impl ToRedisArgs for RowCell {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{infra::connectors::RedisConnector, FeatureValueType};
+    use crate::FeatureValueType;
 
     #[tokio::test]
     async fn test_redis_row_sink() -> SeResult<()> {
@@ -114,7 +223,7 @@ mod test {
 
         if let Ok(uri) = redis_uri {
             let sink = RedisRowSink::new(
-                RedisConnector::new(uri).get_client(),
+                RedisConnector::new(uri),
                 Schema::Tabular(TabularSchema {
                     field_names: vec![
                         "double_col",
@@ -134,6 +243,7 @@ mod test {
                         FeatureValueType::Duration,
                     ],
                     tlabel: Some("TestEntity".to_string()),
+                    primary_key: None,
                 }),
             );
             let mut writer = sink.create_writer().await?;
@@ -149,7 +259,7 @@ mod test {
                 ]))
                 .await?;
 
-            let mut conn = super::get_connection_arc(&sink.client)?;
+            let mut conn = sink.connector.try_get_connection()?;
             let record: f64 = conn.get_del(format!("TestEntity/double_col/{key}"))?;
             assert_eq!(record, 0.618);
             let record: f32 = conn.get_del(format!("TestEntity/float_col/{key}"))?;
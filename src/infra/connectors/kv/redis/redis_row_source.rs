@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+
+use super::{get_key, RedisConnector};
+use crate::{infra::pi::storage::*, FeatureValueType, SeResult};
+
+/// Number of keys that each SCAN iteration of a [RedisRowReader] hints Redis to visit
+const SCAN_COUNT: usize = 1024;
+
+/// Source of the rows of a [TabularSchema] written by [RedisRowWriter](super::RedisRowWriter),
+/// which are found by scanning the `{tlabel}/{field}/{id}` keys of the label
+#[derive(Debug)]
+pub struct RedisRowSource {
+    connector: RedisConnector,
+    type_info: Schema,
+}
+
+impl RedisRowSource {
+    pub(super) fn new(connector: RedisConnector, type_info: Schema) -> Self {
+        Self {
+            connector,
+            type_info,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Source<Row> for RedisRowSource {
+    async fn create_reader(&self) -> SeResult<Box<dyn Reader<Row> + '_>> {
+        let Schema::Tabular(schema) = &self.type_info else {
+            return Err(format!(
+                "Redis only keeps the rows of tabular schemas. {:?}",
+                self.type_info
+            )
+            .into());
+        };
+        let tlabel = schema
+            .tlabel
+            .as_deref()
+            .ok_or_else(|| format!("Cannot scan the keys of rows without a label. {schema:?}"))?;
+        Ok(Box::new(RedisRowReader {
+            redis_conn: self.connector.try_get_connection()?,
+            pattern: format!("{}/*", escape_pattern(tlabel)),
+            tlabel,
+            schema,
+            cursor: 0,
+            seen_ids: HashSet::new(),
+        }))
+    }
+
+    fn get_schema(&self) -> &Schema {
+        &self.type_info
+    }
+}
+
+/// Escapes the glob characters of `value` in a MATCH pattern
+fn escape_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Decodes `value` of `value_type` as written by [RedisRowWriter](super::RedisRowWriter), in
+/// which booleans are `1` or `0` and strings are kept as is. Missing values are [RowCell::Null].
+fn decode(value: Option<&str>, value_type: &FeatureValueType) -> SeResult<RowCell> {
+    match (value, value_type) {
+        (None, _) => Ok(RowCell::Null),
+        (Some("1"), FeatureValueType::Boolean) => Ok(RowCell::Boolean(true)),
+        (Some("0"), FeatureValueType::Boolean) => Ok(RowCell::Boolean(false)),
+        (Some(value), FeatureValueType::String) => Ok(RowCell::String(value.to_string())),
+        (Some(value), value_type) => RowCell::parse(value, value_type),
+    }
+}
+
+/// Outputs the rows of the ids found by each SCAN iteration. A row is output for each id of
+/// which any field is set, and its unset fields are null.
+pub struct RedisRowReader<'a> {
+    redis_conn: redis::Connection,
+    /// MATCH pattern of the keys of the label
+    pattern: String,
+    tlabel: &'a str,
+    schema: &'a TabularSchema,
+    /// SCAN cursor, 0 before the first and after the last iteration
+    cursor: u64,
+    /// Ids of the rows output before, as SCAN may return a key more than once
+    seen_ids: HashSet<String>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl<'a> Reader<Row> for RedisRowReader<'a> {
+    async fn next(&mut self, output: &mut dyn Collector<Row>) -> SeResult<bool> {
+        let (cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .cursor_arg(self.cursor)
+            .arg("MATCH")
+            .arg(&self.pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query(&mut self.redis_conn)?;
+        self.cursor = cursor;
+
+        let (tlabel, field_names) = (self.tlabel, &self.schema.field_names);
+        let mut ids = Vec::new();
+        for key in &keys {
+            let Some((field, id)) = key
+                .strip_prefix(tlabel)
+                .and_then(|key| key.strip_prefix('/'))
+                .and_then(|key| key.split_once('/'))
+            else {
+                continue;
+            };
+            if field_names.iter().any(|name| name == field) && self.seen_ids.insert(id.to_string())
+            {
+                ids.push(id.to_string());
+            }
+        }
+        if !ids.is_empty() {
+            let keys: Vec<String> = ids
+                .iter()
+                .flat_map(|id| {
+                    field_names
+                        .iter()
+                        .map(move |name| get_key(tlabel, name, id))
+                })
+                .collect();
+            let values: Vec<Option<String>> =
+                redis::cmd("MGET").arg(keys).query(&mut self.redis_conn)?;
+            for (id, values) in ids.into_iter().zip(values.chunks(field_names.len())) {
+                let cells = std::iter::once(Ok(RowCell::String(id)))
+                    .chain(
+                        values
+                            .iter()
+                            .zip(&self.schema.field_types)
+                            .map(|(value, value_type)| decode(value.as_deref(), value_type)),
+                    )
+                    .collect::<SeResult<_>>()?;
+                output.collect(Row::new(cells)).await?;
+            }
+        }
+        Ok(cursor != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::pi::{Sinkable, Sourceable};
+
+    struct RowCollector(Vec<Row>);
+
+    #[async_trait::async_trait(?Send)]
+    impl Collector<Row> for RowCollector {
+        async fn collect(&mut self, record: Row) -> SeResult<()> {
+            self.0.push(record);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_decode() -> SeResult<()> {
+        assert_eq!(
+            decode(Some("1"), &FeatureValueType::Boolean)?,
+            RowCell::Boolean(true)
+        );
+        assert_eq!(
+            decode(
+                Some("[1,2]"),
+                &FeatureValueType::Array(Box::new(FeatureValueType::Int))
+            )?,
+            RowCell::Array(vec![RowCell::Int(1), RowCell::Int(2)])
+        );
+        assert_eq!(
+            decode(Some(""), &FeatureValueType::String)?,
+            RowCell::String(String::new())
+        );
+        assert_eq!(decode(None, &FeatureValueType::Float)?, RowCell::Null);
+        assert_eq!(escape_pattern("a*b[c]"), "a\\*b\\[c\\]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_source() -> SeResult<()> {
+        let connector = RedisConnector::new("redis://localhost:6379");
+        let schema = Schema::Tabular(TabularSchema {
+            field_names: vec!["rank".to_string()],
+            field_types: vec![FeatureValueType::Float],
+            tlabel: Some("TestRedisRowSource".to_string()),
+            primary_key: None,
+        });
+        let SourceType::Row(source) = connector.get_source(&Storage::OfnilRow, schema.clone())?
+        else {
+            panic!("Expect a source of rows")
+        };
+        assert_eq!(source.get_schema().columns(), schema.columns());
+        assert!(connector.get_source(&Storage::Arrow, schema).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_redis_row_source() -> SeResult<()> {
+        let redis_uri = std::env::var("REDIS_URI");
+        println!("redis_uri={redis_uri:?}");
+
+        if let Ok(uri) = redis_uri {
+            let connector = RedisConnector::new(uri);
+            let schema = Schema::Tabular(TabularSchema {
+                field_names: vec!["rank".to_string(), "active".to_string()],
+                field_types: vec![FeatureValueType::Float, FeatureValueType::Boolean],
+                tlabel: Some("TestRedisRowSource".to_string()),
+                primary_key: None,
+            });
+            let rows = || {
+                vec![
+                    Row::new(vec![
+                        RowCell::String("v/1".to_string()),
+                        RowCell::Double(0.5),
+                        RowCell::Boolean(true),
+                    ]),
+                    Row::new(vec![
+                        RowCell::String("v2".to_string()),
+                        RowCell::Null,
+                        RowCell::Boolean(false),
+                    ]),
+                ]
+            };
+            let sink = connector.insert_rows(schema.clone());
+            let mut writer = sink.create_write_collector().await?;
+            for row in rows() {
+                writer.collect(row).await?;
+            }
+            writer.finish().await?;
+
+            let source = connector.produce_rows(schema)?;
+            let mut reader = source.create_reader().await?;
+            let mut output = RowCollector(Vec::new());
+            while reader.next(&mut output).await? {}
+            output.0.sort_by_key(|row| row.get(0).to_string());
+            assert_eq!(output.0, rows());
+
+            let mut conn = connector.try_get_connection()?;
+            for key in ["v/1", "v2"].into_iter().flat_map(|id| {
+                ["rank", "active"].map(|name| get_key("TestRedisRowSource", name, id))
+            }) {
+                redis::cmd("DEL").arg(key).query::<()>(&mut conn)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use super::InfraIdentifier;
+
+/// Result of a probe of an infra, see [Connector::health_checks](super::pi::Connector::health_checks)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HealthCheck {
+    pub name: String,
+    /// Why the probe failed, or `None` if it passed
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    pub fn new<T, E: Display>(name: impl Into<String>, result: Result<T, E>) -> Self {
+        Self {
+            name: name.into(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Health of an infra reported by [InfraManager::health](super::InfraManager::health)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InfraHealth {
+    pub infra_id: InfraIdentifier,
+    /// Whether the infra is connected and passes all its probes
+    pub available: bool,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl InfraHealth {
+    pub(super) fn new(infra_id: InfraIdentifier, checks: Vec<HealthCheck>) -> Self {
+        Self {
+            infra_id,
+            available: checks.iter().all(HealthCheck::passed),
+            checks,
+        }
+    }
+}
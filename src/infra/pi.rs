@@ -1,8 +1,10 @@
+pub mod plugin;
 mod schema_provider;
 pub mod storage;
 pub mod transformation;
 
-use crate::transformation::TransformationArgs;
+use crate::{transformation::TransformationArgs, SeResult};
+pub use plugin::{Connector, ConnectorFactory};
 pub use schema_provider::SchemaProvider;
 pub use storage::*;
 pub use transformation::gdb::*;
@@ -18,6 +20,7 @@ pub trait Sinkable {
         assert!(self.supports_source(src_storage));
         match src_storage {
             Storage::OfnilRow => SinkType::Row(self.insert_rows(type_info)),
+            Storage::Arrow => SinkType::Arrow(self.insert_batches(type_info)),
             _ => unimplemented!("Unsupported yet"),
         }
     }
@@ -27,6 +30,11 @@ pub trait Sinkable {
         unimplemented!("Not supported")
     }
 
+    /// Implemented for supporting reading from in-memory columnar storage `Storage::Arrow`
+    fn insert_batches(&self, _type_info: Schema) -> Box<dyn Sink<RecordBatch>> {
+        unimplemented!("Not supported")
+    }
+
     fn supports_source(&self, source: &Storage) -> bool {
         self.get_supported_sources().contains(source)
     }
@@ -37,17 +45,25 @@ pub trait Sourceable {
     /// We assume the storage types in the front is preferred to the types in the back
     fn get_supported_sinks(&self) -> Vec<Storage>;
 
-    fn get_source(&self, sink_storage: &Storage) -> SourceType {
-        assert!(self.supports_sink(sink_storage));
+    /// Creates a source of the data of `type_info` kept in the infra as `sink_storage`
+    fn get_source(&self, sink_storage: &Storage, type_info: Schema) -> SeResult<SourceType> {
         match sink_storage {
-            Storage::OfnilRow => SourceType::Row(self.produce_rows()),
-            _ => panic!("Unsupported yet"),
+            Storage::OfnilRow => Ok(SourceType::Row(self.produce_rows(type_info)?)),
+            Storage::Arrow => Ok(SourceType::Arrow(self.produce_batches(type_info)?)),
+            _ => Err(format!("Producing {sink_storage:?} is not supported yet").into()),
         }
     }
 
-    /// Implemented for supporting virtual in-memory storage `Storage::OfnilRow`
-    fn produce_rows(&self) -> Box<dyn Source<Row>> {
-        panic!("Not supported")
+    /// Implemented for supporting virtual in-memory storage `Storage::OfnilRow`, e.g. to read the
+    /// rows of a vertex or edge type kept in the infra as the input of a transformation executed
+    /// in another infra. See [Schema::columns] for the layout of the rows.
+    fn produce_rows(&self, _type_info: Schema) -> SeResult<Box<dyn Source<Row>>> {
+        Err("Producing rows is not supported".into())
+    }
+
+    /// Implemented for supporting in-memory columnar storage `Storage::Arrow`
+    fn produce_batches(&self, _type_info: Schema) -> SeResult<Box<dyn Source<RecordBatch>>> {
+        Err("Producing record batches is not supported".into())
     }
 
     fn supports_sink(&self, sink: &Storage) -> bool {
@@ -57,8 +73,8 @@ pub trait Sourceable {
 
 /// Required trait for all infra connectors that serve as data storage.
 /// Automatically implemented for infra connectors that implement [Sinkable] and [Sourceable].
-pub trait StorageConnector: Sinkable + Sourceable + std::fmt::Debug {}
-impl<T> StorageConnector for T where T: Sinkable + Sourceable + std::fmt::Debug {}
+pub trait StorageConnector: Sinkable + Sourceable + std::fmt::Debug + Send + Sync {}
+impl<T> StorageConnector for T where T: Sinkable + Sourceable + std::fmt::Debug + Send + Sync {}
 
 /// Required trait for all infra connectors that support feature transformation.
 pub trait TransformationConnector: Sinkable + Sourceable + std::fmt::Debug {
@@ -87,10 +103,23 @@ pub trait TransformationConnector: Sinkable + Sourceable + std::fmt::Debug {
     }
 
     // TODO(tatiana): support rules (priority for speed, resources, etc.) as parameter?
-    /// Select an output sink storage type for transformation from available sinks
+    /// Select an output sink storage type for transformation from available sinks. The first
+    /// available type that the connector outputs is selected, except that columnar
+    /// `Storage::Arrow` is preferred to `Storage::OfnilRow` rows if both are available.
     fn select_sink(&self, available: Vec<Storage>) -> Storage {
         debug_assert!(!available.is_empty());
-        available.into_iter().next().unwrap()
+        let outputs = self.get_supported_sinks();
+        let selected = available
+            .iter()
+            .find(|storage| outputs.contains(storage))
+            .unwrap_or(&available[0]);
+        if selected == &Storage::OfnilRow
+            && available.contains(&Storage::Arrow)
+            && outputs.contains(&Storage::Arrow)
+        {
+            return Storage::Arrow;
+        }
+        selected.clone()
     }
 
     // TODO(tatiana): support rules (priority for speed, resources, etc.) as parameter?
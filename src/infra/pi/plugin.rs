@@ -0,0 +1,115 @@
+//! Registration of connectors of infra types other than the [built-in](BUILTIN_INFRA_TYPES) ones,
+//! e.g. by crates that depend on this one. A [ConnectorFactory] registered by
+//! [register_connector_factory] builds the connectors of the infras of its `infra_type` in
+//! `ofnil.toml`, which the [InfraManager](crate::InfraManager) then serves like the built-in ones.
+//! The connectors of the built-in `local_file` and `embedded` infras are built by factories as
+//! well, which are registered from the start.
+
+use super::{SchemaProvider, StorageConnector, TransformationConnector};
+use crate::{
+    infra::{
+        connectors::{
+            file::local::LocalFileConnectorFactory, gdb::embedded::EmbeddedGraphConnectorFactory,
+        },
+        health::HealthCheck,
+    },
+    InfraIdentifier, SeResult,
+};
+
+use lazy_static::lazy_static;
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// Infra types of the connectors of this crate, which cannot be registered
+pub const BUILTIN_INFRA_TYPES: [&str; 4] = ["neo4j", "redis", "local_file", "embedded"];
+
+lazy_static! {
+    static ref FACTORIES: RwLock<HashMap<String, Arc<dyn ConnectorFactory>>> = {
+        let builtin: [Arc<dyn ConnectorFactory>; 2] = [
+            Arc::new(LocalFileConnectorFactory),
+            Arc::new(EmbeddedGraphConnectorFactory),
+        ];
+        let factories = builtin
+            .into_iter()
+            .map(|factory| (factory.infra_type().to_string(), factory));
+        RwLock::new(factories.collect())
+    };
+}
+
+/// Connector of an infra, giving the interfaces that the infra supports. All interfaces are
+/// unsupported by default.
+#[async_trait::async_trait]
+pub trait Connector: std::fmt::Debug + Send + Sync {
+    fn as_storage(&self) -> Option<&dyn StorageConnector> {
+        None
+    }
+
+    fn clone_storage(&self) -> Option<Box<dyn StorageConnector>> {
+        None
+    }
+
+    fn as_transformation(&self) -> Option<&dyn TransformationConnector> {
+        None
+    }
+
+    fn clone_transformation(&self) -> Option<Box<dyn TransformationConnector>> {
+        None
+    }
+
+    fn schema_provider(&self) -> Option<Arc<dyn SchemaProvider>> {
+        None
+    }
+
+    /// The connector as [Any], to get back the concrete connector of an infra, e.g. a
+    /// [LocalFileConnector](crate::infra::connectors::LocalFileConnector)
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+
+    /// Probes whether the infra is reachable and usable, e.g. by a round trip to the server.
+    /// The infra is taken as healthy if it has no probes.
+    async fn health_checks(&self) -> Vec<HealthCheck> {
+        Vec::new()
+    }
+}
+
+/// Builds the connectors of the infras of an infra type from their config.
+#[async_trait::async_trait]
+pub trait ConnectorFactory: Send + Sync {
+    /// The `infra_type` of the infras in `ofnil.toml`
+    fn infra_type(&self) -> &str;
+
+    /// Connects to infra `infra_id` of `properties`, the keys of the infra in `ofnil.toml` with
+    /// `uri`, `username` and `password` resolved, see
+    /// [InfraConfig::Plugin](crate::InfraConfig::Plugin).
+    async fn create(
+        &self,
+        infra_id: &InfraIdentifier,
+        properties: &toml::Table,
+    ) -> SeResult<Box<dyn Connector>>;
+}
+
+/// Registers `factory` for the infras of its infra type, replacing the factory registered before
+/// for the type if any. Factories must be registered before the feature store is created.
+///
+/// # Panics
+/// If the infra type is one of [BUILTIN_INFRA_TYPES].
+pub fn register_connector_factory(factory: impl ConnectorFactory + 'static) {
+    let infra_type = factory.infra_type().to_string();
+    assert!(
+        !BUILTIN_INFRA_TYPES.contains(&infra_type.as_str()),
+        "Cannot register a factory of built-in infra type {infra_type}"
+    );
+    FACTORIES
+        .write()
+        .unwrap()
+        .insert(infra_type, Arc::new(factory));
+}
+
+/// Gets the factory registered for `infra_type`, including the built-in ones
+pub fn get_connector_factory(infra_type: &str) -> Option<Arc<dyn ConnectorFactory>> {
+    FACTORIES.read().unwrap().get(infra_type).cloned()
+}
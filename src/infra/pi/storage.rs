@@ -1,10 +1,13 @@
+mod batch;
 mod file;
 mod sink;
 mod sink_types;
 mod source;
 mod source_types;
 mod tabular;
+mod topology;
 use crate::FeatureValueType;
+pub use batch::*;
 pub use file::*;
 use serde::{Deserialize, Serialize};
 pub use sink::*;
@@ -12,6 +15,7 @@ pub use sink_types::*;
 pub use source::*;
 pub use source_types::*;
 pub use tabular::*;
+pub use topology::*;
 
 #[derive(Eq, Hash, PartialEq, Serialize, Deserialize, Debug, Clone)]
 pub enum FileSystemIdentifier {
@@ -25,7 +29,9 @@ pub enum FileSystemIdentifier {
 pub enum FileFormat {
     CSV,
     Parquet,
+    /// Binary files of a [CsrTopology]
     CSR,
+    /// Binary files of a [CooTopology]
     COO,
 }
 
@@ -39,6 +45,7 @@ pub enum Storage {
     },
     // common in-memory storage
     OfnilRow,
+    /// Columnar [RecordBatch]es
     Arrow,
     // specific storage
     Neo4j,
@@ -51,12 +58,36 @@ pub enum Schema {
     Edge(EdgeSchema),
 }
 
+impl Schema {
+    /// Names and types of the columns of the rows of the schema. A row of a vertex type has the
+    /// vertex `id` followed by the fields, and a row of an edge type has the `src` and `dst`
+    /// vertex ids followed by the edge fields.
+    pub fn columns(&self) -> Vec<(String, FeatureValueType)> {
+        let (keys, fields) = match self {
+            Schema::Tabular(schema) => (vec!["id"], schema),
+            Schema::Edge(schema) => (vec!["src", "dst"], &schema.edge_info),
+        };
+        keys.into_iter()
+            .map(|key| (key.to_string(), FeatureValueType::String))
+            .chain(
+                fields
+                    .field_names
+                    .iter()
+                    .cloned()
+                    .zip(fields.field_types.iter().cloned()),
+            )
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TabularSchema {
     pub field_names: Vec<String>,
     pub field_types: Vec<FeatureValueType>,
     /// the type/label of the associated entity
     pub tlabel: Option<String>,
+    /// the primary key of the vertices of a vertex type, by which infras like Neo4j identify them
+    pub primary_key: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,243 @@
+use std::sync::Arc;
+
+/// Arrow record batch of the columns of a [Schema], see [Schema::arrow_schema]
+pub use arrow::record_batch::RecordBatch;
+use arrow::{
+    array::{
+        make_builder, Array, ArrayBuilder, ArrayRef, AsArray, BooleanBuilder, Float64Builder,
+        Int64Builder, ListBuilder, StringBuilder, UInt64Builder,
+    },
+    datatypes::{DataType, Field, Float64Type, Int64Type, SchemaRef, UInt64Type},
+};
+
+use super::{Row, RowCell, Schema};
+use crate::{FeatureValueType, SeResult};
+
+/// Arrow type of the values of `value_type`. Dates and times are strings, as in [RowCell].
+pub fn data_type(value_type: &FeatureValueType) -> DataType {
+    match value_type {
+        FeatureValueType::Int => DataType::Int64,
+        FeatureValueType::Float => DataType::Float64,
+        FeatureValueType::Boolean => DataType::Boolean,
+        FeatureValueType::Duration => DataType::UInt64,
+        FeatureValueType::Array(element_type) => {
+            DataType::List(Arc::new(Field::new("item", data_type(element_type), true)))
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+impl Schema {
+    /// Arrow schema of the record batches of the schema, of a nullable field per column of
+    /// [Schema::columns]
+    pub fn arrow_schema(&self) -> SchemaRef {
+        let fields: Vec<_> = self
+            .columns()
+            .into_iter()
+            .map(|(name, value_type)| Field::new(name, data_type(&value_type), true))
+            .collect();
+        Arc::new(arrow::datatypes::Schema::new(fields))
+    }
+}
+
+/// Gets the value at `index` of `array` as a cell of `value_type`
+pub fn get_cell(
+    array: &dyn Array,
+    index: usize,
+    value_type: &FeatureValueType,
+) -> SeResult<RowCell> {
+    if array.is_null(index) {
+        return Ok(RowCell::Null);
+    }
+    Ok(match (value_type, array.data_type()) {
+        (FeatureValueType::Int, DataType::Int64) => {
+            RowCell::Int(array.as_primitive::<Int64Type>().value(index))
+        }
+        (FeatureValueType::Float, DataType::Float64) => {
+            RowCell::Double(array.as_primitive::<Float64Type>().value(index))
+        }
+        (FeatureValueType::Boolean, DataType::Boolean) => {
+            RowCell::Boolean(array.as_boolean().value(index))
+        }
+        (FeatureValueType::Duration, DataType::UInt64) => {
+            RowCell::Duration(array.as_primitive::<UInt64Type>().value(index))
+        }
+        (FeatureValueType::Array(element_type), DataType::List(_)) => {
+            let elements = array.as_list::<i32>().value(index);
+            RowCell::Array(
+                (0..elements.len())
+                    .map(|idx| get_cell(elements.as_ref(), idx, element_type))
+                    .collect::<SeResult<_>>()?,
+            )
+        }
+        (value_type, DataType::Utf8) if data_type(value_type) == DataType::Utf8 => {
+            let value = array.as_string::<i32>().value(index).to_string();
+            match value_type {
+                FeatureValueType::Date => RowCell::Date(value),
+                FeatureValueType::Time => RowCell::Time(value),
+                FeatureValueType::DateTime => RowCell::DateTime(value),
+                _ => RowCell::String(value),
+            }
+        }
+        (value_type, data_type) => {
+            return Err(format!("Expect values of {value_type:?}, but got {data_type:?}").into())
+        }
+    })
+}
+
+/// Gets the records of `batch` as rows of `schema`
+pub fn batch_to_rows(batch: &RecordBatch, schema: &Schema) -> SeResult<Vec<Row>> {
+    let columns = schema.columns();
+    if batch.num_columns() != columns.len() {
+        return Err(format!(
+            "Expect a batch of {} columns, but got {:?}",
+            columns.len(),
+            batch.schema()
+        )
+        .into());
+    }
+    (0..batch.num_rows())
+        .map(|index| {
+            Ok(Row::new(
+                batch
+                    .columns()
+                    .iter()
+                    .zip(&columns)
+                    .map(|(array, (_, value_type))| get_cell(array.as_ref(), index, value_type))
+                    .collect::<SeResult<_>>()?,
+            ))
+        })
+        .collect()
+}
+
+/// Builds a batch of `rows` of `schema`. Numbers are converted to the type of their column if
+/// they are of another width, e.g. a [RowCell::Float] in a `Float64` column.
+pub fn rows_to_batch(rows: &[Row], schema: &Schema) -> SeResult<RecordBatch> {
+    let arrow_schema = schema.arrow_schema();
+    let columns = schema.columns();
+    if let Some(row) = rows.iter().find(|row| row.len() != columns.len()) {
+        return Err(format!("Expect a row of {} columns, but got {row:?}", columns.len()).into());
+    }
+    let arrays = arrow_schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let mut builder = make_builder(field.data_type(), rows.len());
+            for row in rows {
+                append(builder.as_mut(), row.get(idx))?;
+            }
+            Ok(builder.finish())
+        })
+        .collect::<SeResult<Vec<ArrayRef>>>()?;
+    Ok(RecordBatch::try_new(arrow_schema, arrays)?)
+}
+
+/// Appends `cell` to `builder` of [make_builder]
+fn append(builder: &mut dyn ArrayBuilder, cell: &RowCell) -> SeResult<()> {
+    let builder = builder.as_any_mut();
+    if let Some(builder) = builder.downcast_mut::<StringBuilder>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::String(value)
+            | RowCell::Date(value)
+            | RowCell::Time(value)
+            | RowCell::DateTime(value) => builder.append_value(value),
+            cell => return Err(format!("Cannot append {cell:?} to a column of Utf8").into()),
+        }
+    } else if let Some(builder) = builder.downcast_mut::<Int64Builder>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::Int(value) => builder.append_value(*value),
+            cell => return Err(format!("Cannot append {cell:?} to a column of Int64").into()),
+        }
+    } else if let Some(builder) = builder.downcast_mut::<Float64Builder>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::Double(value) => builder.append_value(*value),
+            RowCell::Float(value) => builder.append_value((*value).into()),
+            RowCell::Int(value) => builder.append_value(*value as f64),
+            cell => return Err(format!("Cannot append {cell:?} to a column of Float64").into()),
+        }
+    } else if let Some(builder) = builder.downcast_mut::<BooleanBuilder>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::Boolean(value) => builder.append_value(*value),
+            cell => return Err(format!("Cannot append {cell:?} to a column of Boolean").into()),
+        }
+    } else if let Some(builder) = builder.downcast_mut::<UInt64Builder>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::Duration(value) => builder.append_value(*value),
+            cell => return Err(format!("Cannot append {cell:?} to a column of UInt64").into()),
+        }
+    } else if let Some(builder) = builder.downcast_mut::<ListBuilder<Box<dyn ArrayBuilder>>>() {
+        match cell {
+            RowCell::Null => builder.append_null(),
+            RowCell::Array(elements) => {
+                for element in elements {
+                    append(builder.values().as_mut(), element)?;
+                }
+                builder.append(true);
+            }
+            cell => return Err(format!("Cannot append {cell:?} to a column of List").into()),
+        }
+    } else {
+        return Err(format!("Unexpected builder of {cell:?}").into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::pi::storage::TabularSchema;
+
+    #[test]
+    fn test_record_batch() -> SeResult<()> {
+        let schema = Schema::Tabular(TabularSchema {
+            field_names: vec!["rank".to_string(), "tags".to_string()],
+            field_types: vec![
+                FeatureValueType::Float,
+                FeatureValueType::Array(Box::new(FeatureValueType::Int)),
+            ],
+            tlabel: Some("Reviewer".to_string()),
+            primary_key: None,
+        });
+        let rows = vec![
+            Row::new(vec![
+                RowCell::String("r1".to_string()),
+                RowCell::Double(0.5),
+                RowCell::Array(vec![RowCell::Int(1), RowCell::Null]),
+            ]),
+            Row::new(vec![
+                RowCell::String("r2".to_string()),
+                RowCell::Null,
+                RowCell::Null,
+            ]),
+        ];
+        let batch = rows_to_batch(&rows, &schema)?;
+        assert_eq!(batch.schema(), schema.arrow_schema());
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            batch
+                .column(1)
+                .as_primitive::<Float64Type>()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![Some(0.5), None]
+        );
+        assert_eq!(batch_to_rows(&batch, &schema)?, rows);
+
+        // type mismatch and missing columns
+        let mismatch = Row::new(vec![
+            RowCell::String("r3".to_string()),
+            RowCell::Boolean(true),
+            RowCell::Null,
+        ]);
+        assert!(rows_to_batch(&[mismatch], &schema).is_err());
+        let missing = Row::new(vec![RowCell::String("r3".to_string())]);
+        assert!(rows_to_batch(&[missing], &schema).is_err());
+        Ok(())
+    }
+}
@@ -4,12 +4,23 @@ use crate::SeResult;
 #[async_trait::async_trait(?Send)]
 pub trait Writer<T> {
     async fn write(&mut self, record: T) -> SeResult<()>;
+
+    /// Flushes the written records, called after the last record
+    async fn finish(&mut self) -> SeResult<()> {
+        Ok(())
+    }
 }
 
 pub struct WriteCollector<'a, T> {
     writer: Box<dyn Writer<T> + 'a>,
 }
 
+impl<'a, T> WriteCollector<'a, T> {
+    pub async fn finish(&mut self) -> SeResult<()> {
+        self.writer.finish().await
+    }
+}
+
 #[async_trait::async_trait(?Send)]
 impl<'a, T> Collector<T> for WriteCollector<'a, T> {
     async fn collect(&mut self, record: T) -> SeResult<()> {
@@ -1,5 +1,6 @@
-use super::{Row, Sink};
+use super::{RecordBatch, Row, Sink};
 
 pub enum SinkType {
     Row(Box<dyn Sink<Row>>),
+    Arrow(Box<dyn Sink<RecordBatch>>),
 }
@@ -1,5 +1,6 @@
-use super::{Row, Source};
+use super::{RecordBatch, Row, Source};
 
 pub enum SourceType {
     Row(Box<dyn Source<Row>>),
+    Arrow(Box<dyn Source<RecordBatch>>),
 }
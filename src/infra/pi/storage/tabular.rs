@@ -1,6 +1,7 @@
+use crate::{FeatureValueType, SeResult};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum RowCell {
     String(String),
     Float(f32),
@@ -31,6 +32,92 @@ impl RowCell {
             panic!("Value is not Double");
         }
     }
+
+    /// Encodes the value as a string that [RowCell::parse] reads back, e.g. as a CSV field or a
+    /// Redis value. Arrays are JSON arrays, and strings that would read back as another value,
+    /// i.e. empty strings and strings starting with `"`, are JSON strings. Nulls are empty.
+    pub fn encode(&self) -> String {
+        match self {
+            RowCell::String(value) if value.is_empty() || value.starts_with('"') => {
+                serde_json::Value::from(value.as_str()).to_string()
+            }
+            RowCell::Array(_) => self.to_json().to_string(),
+            value => value.to_string(),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            RowCell::String(value)
+            | RowCell::Date(value)
+            | RowCell::Time(value)
+            | RowCell::DateTime(value) => Value::from(value.as_str()),
+            RowCell::Float(value) => Value::from(*value),
+            RowCell::Double(value) => Value::from(*value),
+            RowCell::Int(value) => Value::from(*value),
+            RowCell::Boolean(value) => Value::from(*value),
+            RowCell::Duration(value) => Value::from(*value),
+            RowCell::Array(values) => Value::Array(values.iter().map(RowCell::to_json).collect()),
+            RowCell::Null => Value::Null,
+        }
+    }
+
+    fn from_json(value: &serde_json::Value, value_type: &FeatureValueType) -> SeResult<Self> {
+        use serde_json::Value;
+        let invalid = || format!("Expect a {value_type:?} value, but got {value}");
+        Ok(match (value, value_type) {
+            (Value::Null, _) => RowCell::Null,
+            (Value::String(value), FeatureValueType::String) => RowCell::String(value.clone()),
+            (Value::String(value), FeatureValueType::Date) => RowCell::Date(value.clone()),
+            (Value::String(value), FeatureValueType::Time) => RowCell::Time(value.clone()),
+            (Value::String(value), FeatureValueType::DateTime) => RowCell::DateTime(value.clone()),
+            (Value::Number(value), FeatureValueType::Int) => {
+                RowCell::Int(value.as_i64().ok_or_else(invalid)?)
+            }
+            (Value::Number(value), FeatureValueType::Float) => {
+                RowCell::Double(value.as_f64().ok_or_else(invalid)?)
+            }
+            (Value::Number(value), FeatureValueType::Duration) => {
+                RowCell::Duration(value.as_u64().ok_or_else(invalid)?)
+            }
+            (Value::Bool(value), FeatureValueType::Boolean) => RowCell::Boolean(*value),
+            (Value::Array(values), FeatureValueType::Array(element_type)) => RowCell::Array(
+                values
+                    .iter()
+                    .map(|element| RowCell::from_json(element, element_type))
+                    .collect::<SeResult<_>>()?,
+            ),
+            _ => return Err(invalid().into()),
+        })
+    }
+
+    /// Parses a value of `value_type` encoded by [RowCell::encode]. Empty values are
+    /// [RowCell::Null].
+    pub fn parse(value: &str, value_type: &FeatureValueType) -> SeResult<Self> {
+        if value.is_empty() {
+            return Ok(RowCell::Null);
+        }
+        Ok(match value_type {
+            FeatureValueType::String if value.starts_with('"') => {
+                RowCell::String(serde_json::from_str(value)?)
+            }
+            FeatureValueType::String => RowCell::String(value.to_string()),
+            FeatureValueType::Int => RowCell::Int(value.parse()?),
+            FeatureValueType::Float => RowCell::Double(value.parse()?),
+            FeatureValueType::Boolean => RowCell::Boolean(value.parse()?),
+            FeatureValueType::Date => RowCell::Date(value.to_string()),
+            FeatureValueType::Time => RowCell::Time(value.to_string()),
+            FeatureValueType::DateTime => RowCell::DateTime(value.to_string()),
+            FeatureValueType::Duration => RowCell::Duration(value.parse()?),
+            FeatureValueType::Array(_) => {
+                RowCell::from_json(&serde_json::from_str(value)?, value_type)?
+            }
+            FeatureValueType::Topology => {
+                return Err(format!("Cannot parse {value_type:?} value {value}").into())
+            }
+        })
+    }
 }
 
 impl std::fmt::Display for RowCell {
@@ -56,7 +143,7 @@ impl std::fmt::Display for RowCell {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Row {
     fields: Vec<RowCell>,
 }
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use crate::SeResult;
+
+/// Mapping between the external primary keys of the vertices of a label and contiguous ids
+/// `0..len`, which index the vertices in the [CooTopology] and [CsrTopology] of a snapshot
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdMapping {
+    keys: Vec<String>,
+    ids: HashMap<String, u64>,
+}
+
+impl IdMapping {
+    /// Assigns the ids in the order of `keys`. Duplicate keys keep their first id.
+    pub fn from_keys(keys: impl IntoIterator<Item = String>) -> Self {
+        let mut mapping = Self::default();
+        for key in keys {
+            mapping.get_or_insert(key);
+        }
+        mapping
+    }
+
+    /// Gets the id of `key`, assigning the next id if the key is new
+    pub fn get_or_insert(&mut self, key: String) -> u64 {
+        if let Some(id) = self.ids.get(&key) {
+            return *id;
+        }
+        let id = self.keys.len() as u64;
+        self.ids.insert(key.clone(), id);
+        self.keys.push(key);
+        id
+    }
+
+    pub fn get_id(&self, key: &str) -> Option<u64> {
+        self.ids.get(key).copied()
+    }
+
+    pub fn get_key(&self, id: u64) -> Option<&str> {
+        self.keys.get(id as usize).map(String::as_str)
+    }
+
+    /// Keys in the order of their ids
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Topology in coordinate format: the src and dst vertex ids of each edge
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CooTopology {
+    pub src: Vec<u64>,
+    pub dst: Vec<u64>,
+    /// Ids of the edges in the source infra, e.g. the internal ids of Neo4j relationships
+    pub edge_ids: Option<Vec<u64>>,
+}
+
+impl CooTopology {
+    /// Maps edges `(src key, dst key, edge id)` to vertex ids. Edges of keys missing in the
+    /// mappings are rejected.
+    pub fn from_edges(
+        edges: impl IntoIterator<Item = (String, String, Option<u64>)>,
+        src_mapping: &IdMapping,
+        dst_mapping: &IdMapping,
+        with_edge_ids: bool,
+    ) -> SeResult<Self> {
+        let mut coo = Self {
+            edge_ids: with_edge_ids.then(Vec::new),
+            ..Default::default()
+        };
+        for (src, dst, edge_id) in edges {
+            let get_id = |mapping: &IdMapping, key: &str| {
+                mapping
+                    .get_id(key)
+                    .ok_or_else(|| format!("Edge of unknown vertex {key}"))
+            };
+            coo.src.push(get_id(src_mapping, &src)?);
+            coo.dst.push(get_id(dst_mapping, &dst)?);
+            if let Some(edge_ids) = &mut coo.edge_ids {
+                edge_ids.push(edge_id.ok_or_else(|| format!("Missing id of edge {src}-{dst}"))?);
+            }
+        }
+        Ok(coo)
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.src.len()
+    }
+}
+
+/// Topology in compressed sparse row format: the dst vertex ids of the out edges of src vertex
+/// `i` are `indices[indptr[i]..indptr[i + 1]]`, in ascending order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsrTopology {
+    pub indptr: Vec<u64>,
+    pub indices: Vec<u64>,
+    /// Ids of the edges in the order of `indices`, see [CooTopology::edge_ids]
+    pub edge_ids: Option<Vec<u64>>,
+}
+
+impl CsrTopology {
+    /// Compresses `coo` of `num_src` src vertices
+    pub fn from_coo(coo: &CooTopology, num_src: usize) -> Self {
+        let mut order: Vec<usize> = (0..coo.num_edges()).collect();
+        order.sort_by_key(|&edge| (coo.src[edge], coo.dst[edge]));
+        let mut indptr = vec![0; num_src + 1];
+        for &src in &coo.src {
+            indptr[src as usize + 1] += 1;
+        }
+        for i in 0..num_src {
+            indptr[i + 1] += indptr[i];
+        }
+        Self {
+            indptr,
+            indices: order.iter().map(|&edge| coo.dst[edge]).collect(),
+            edge_ids: coo
+                .edge_ids
+                .as_ref()
+                .map(|edge_ids| order.iter().map(|&edge| edge_ids[edge]).collect()),
+        }
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topology_formats() -> SeResult<()> {
+        let src_mapping = IdMapping::from_keys(["r2", "r1", "r3"].map(String::from));
+        let dst_mapping = IdMapping::from_keys(["p1", "p2"].map(String::from));
+        assert_eq!(src_mapping.get_id("r1"), Some(1));
+        assert_eq!(dst_mapping.get_key(1), Some("p2"));
+
+        let edges = || {
+            [("r1", "p2", 10), ("r2", "p1", 11), ("r1", "p1", 12)]
+                .map(|(src, dst, id)| (src.to_string(), dst.to_string(), Some(id)))
+        };
+        let coo = CooTopology::from_edges(edges(), &src_mapping, &dst_mapping, true)?;
+        assert_eq!(coo.src, vec![1, 0, 1]);
+        assert_eq!(coo.dst, vec![1, 0, 0]);
+
+        let csr = CsrTopology::from_coo(&coo, src_mapping.len());
+        assert_eq!(csr.indptr, vec![0, 1, 3, 3]);
+        assert_eq!(csr.indices, vec![0, 0, 1]);
+        assert_eq!(csr.edge_ids, Some(vec![11, 12, 10]));
+
+        let unknown = [("r4".to_string(), "p1".to_string(), None)];
+        assert!(CooTopology::from_edges(unknown, &src_mapping, &dst_mapping, false).is_err());
+        Ok(())
+    }
+}
@@ -10,13 +10,29 @@ pub mod serving;
 pub mod transformation;
 
 // re-export commonly used items to alleviate user import burden only
+pub use config::{ConfigError, InfraConfig, Neo4jOptions, RedisOptions, PROFILE_ENV_VAR};
 pub use feature::{
-    Entity, FeatureValueType, Field, Graph, GraphDataset, TableFeatureView, Topology,
-    TopologyFeatureView, TopologyType, Transformation, Variant,
+    Entity, FeatureValueType, Field, Graph, GraphDataset, Resource, ResourceKind, TableFeatureView,
+    Topology, TopologyFeatureView, TopologySnapshot, TopologyType, Transformation, Variant,
+};
+pub use feature_registry::{
+    decode_transformation_body, encode_transformation_body, AuditAction, AuditQuery, AuditRecord,
+    BrokenReference, Bundle, EtcdStorage, FeatureRegistry, FeatureRepo, GarbageReport,
+    ImportConflict, ImportReport, LineageDirection, LineageEdge, LineageGraph, LineageNode,
+    LocalStorage, MemoryStorage, Plan, PlannedUpdate, ReferenceProblem, RegistryError,
+    RegistryEvent, RegistryStorage, ResourceRevision, RevisionDiff, SchemaMigrationReport,
+    SearchQuery, SearchResult, StorageEvent, TagFilter, ValueChange, BUNDLE_VERSION,
+    DEFAULT_CLIENT, DEFAULT_PROJECT, SCHEMA_VERSION, SCHEMA_VERSION_KEY,
+    TRANSFORMATION_BODY_VERSION,
 };
-pub use feature_registry::{FeatureRegistry, RegistryError};
 pub use feature_store::FeatureStore;
-pub use infra::{pi::SchemaProvider, Infra, InfraIdentifier, InfraManager};
+pub use infra::{
+    pi::{
+        plugin::{register_connector_factory, Connector, ConnectorFactory},
+        SchemaProvider,
+    },
+    HealthCheck, Infra, InfraHealth, InfraIdentifier, InfraManager,
+};
 pub use transformation::{
     finalize_transformation, DataFrameBase, GraphBase, GraphComputationOps, TransformationContext,
 };
@@ -1,3 +1,5 @@
+mod connection;
+use connection::InfraConnection;
 mod functions;
 mod resources;
 use resources::*;
@@ -7,16 +9,18 @@ pub(crate) use transformation::*;
 
 use super::FeatureStore;
 use crate::{
-    feature::ResourceId, feature::ResourceOp, Entity, Field, Graph, GraphDataset, InfraIdentifier,
-    TableFeatureView, TopologyFeatureView, Variant,
+    feature::ResourceId, feature::ResourceOp, Bundle, Entity, Field, Graph, GraphDataset,
+    InfraIdentifier, TableFeatureView, TopologyFeatureView, Variant,
 };
 use futures::future::join_all;
 use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{collections::HashMap, sync::Arc};
 use tokio::runtime::Runtime;
 
 #[pyclass(unsendable, module = "ofnil")]
 pub(crate) struct ClientInner {
-    pub(crate) rt: Runtime,
+    /// Shared with the [InfraConnection]s of the feature view infos
+    pub(crate) rt: Arc<Runtime>,
     pub(crate) fs: FeatureStore,
 }
 
@@ -24,11 +28,12 @@ pub(crate) struct ClientInner {
 impl ClientInner {
     #[new]
     fn pydefault(ofnil_home: Option<&str>) -> PyResult<Self> {
-        let rt = tokio::runtime::Runtime::new().unwrap();
+        let rt = Arc::new(tokio::runtime::Runtime::new().unwrap());
         Ok(Self {
             fs: rt.block_on(async {
                 FeatureStore::init(ofnil_home)
                     .await
+                    .map(|fs| fs.with_client("python"))
                     .map_err(|err| PyValueError::new_err(err.to_string()))
             })?,
             rt,
@@ -118,6 +123,50 @@ impl ClientInner {
             }
         })
     }
+
+    /// Exports resources and their dependencies into a bundle file at `path`
+    pub fn export_bundle(self_: PyRef<Self>, ids: Vec<ResourceId>, path: String) -> PyResult<()> {
+        self_.rt.block_on(async {
+            let bundle = self_
+                .fs
+                .registry
+                .export_bundle(&ids)
+                .await
+                .map_err(|e| PyValueError::new_err(format!("Cannot export {ids:?}. {e}")))?;
+            bundle
+                .save(&path)
+                .map_err(|e| PyValueError::new_err(format!("Cannot write bundle {path}. {e}")))
+        })
+    }
+
+    /// Imports the bundle file at `path`, returning the ids of the created, unchanged and
+    /// conflicting resources. Only checks the bundle without importing if `dry_run` is set.
+    #[pyo3(signature = (path, overwrite=false, dry_run=false))]
+    pub fn import_bundle(
+        self_: PyRef<Self>,
+        path: String,
+        overwrite: bool,
+        dry_run: bool,
+    ) -> PyResult<HashMap<String, Vec<ResourceId>>> {
+        self_.rt.block_on(async {
+            let bundle = Bundle::load(&path)
+                .map_err(|e| PyValueError::new_err(format!("Cannot read bundle {path}. {e}")))?;
+            let report = if dry_run {
+                self_.fs.registry.plan_import(&bundle).await
+            } else {
+                self_.fs.registry.import_bundle(&bundle, overwrite).await
+            }
+            .map_err(|e| PyValueError::new_err(format!("Cannot import bundle {path}. {e}")))?;
+            Ok(HashMap::from([
+                ("created".to_string(), report.created),
+                ("unchanged".to_string(), report.unchanged),
+                (
+                    "conflicts".to_string(),
+                    report.conflicts.into_iter().map(|c| c.id).collect(),
+                ),
+            ]))
+        })
+    }
 }
 
 impl ClientInner {
@@ -179,7 +228,8 @@ impl ClientInner {
             }
         }
         Ok(TopologyFeatureViewInfo::new(
-            &self.fs.infra_manager,
+            self.rt.clone(),
+            &self.fs.infra_manager(),
             topo_tuples,
             topo_view,
         ))
@@ -200,7 +250,8 @@ impl ClientInner {
         .collect();
         match self.fs.registry.get_entity(&view.entity_id).await {
             Ok(entity) => Ok(TableFeatureViewInfo::new(
-                &self.fs.infra_manager,
+                self.rt.clone(),
+                &self.fs.infra_manager(),
                 view,
                 entity,
                 fields,
@@ -220,6 +271,7 @@ fn ofnil(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<ClientInner>()?;
     module.add_class::<TableFeatureViewInfo>()?;
     module.add_class::<TopologyFeatureViewInfo>()?;
+    module.add_class::<InfraConnection>()?;
     module.add_class::<transformation::PyPipelineContext>()?;
     module.add_class::<transformation::PyGraphFrame>()?;
     module.add_class::<transformation::PyDataFrame>()?;
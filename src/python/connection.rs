@@ -0,0 +1,190 @@
+//! Connections of Python code to the infras of the feature store. Queries run on the Rust side
+//! with the connectors of the [InfraManager], so that Python code never sees the credentials of
+//! the infras.
+
+use crate::{
+    infra::connectors::{Neo4jConnector, RedisConnector},
+    InfraIdentifier, InfraManager,
+};
+use bb8_bolt::{bolt_client::Params, bolt_proto::Value};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyBytes, PyDict, PyList},
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::runtime::Runtime;
+
+#[derive(Clone)]
+enum Connector {
+    Neo4j(Neo4jConnector),
+    Redis(RedisConnector),
+}
+
+/// Opaque handle of the connection to an infra, shared by the feature view infos of the infra.
+#[pyclass(name = "InfraConnection", module = "ofnil")]
+#[derive(Clone)]
+pub struct InfraConnection {
+    rt: Arc<Runtime>,
+    connector: Connector,
+    #[pyo3(get)]
+    infra_id: InfraIdentifier,
+}
+
+impl InfraConnection {
+    /// Gets the connection to infra `infra_id`, or `None` if the infra is not managed or is not
+    /// a Neo4j or Redis infra
+    pub(super) fn new(
+        rt: Arc<Runtime>,
+        infra_manager: &InfraManager,
+        infra_id: &InfraIdentifier,
+    ) -> Option<Self> {
+        let connector = match infra_id {
+            InfraIdentifier::Neo4j(name) => {
+                Connector::Neo4j(infra_manager.get_neo4j_connector(name)?.clone())
+            }
+            InfraIdentifier::Redis(name) => {
+                Connector::Redis(infra_manager.get_redis_connector(name)?.clone())
+            }
+            InfraIdentifier::Plugin { .. } => return None,
+        };
+        Some(Self {
+            rt,
+            connector,
+            infra_id: infra_id.clone(),
+        })
+    }
+}
+
+#[pymethods]
+impl InfraConnection {
+    /// Runs a Cypher query on a Neo4j infra, and returns the records as dicts of the returned
+    /// fields.
+    #[pyo3(signature = (query, parameters = None))]
+    fn run(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        parameters: Option<HashMap<String, &PyAny>>,
+    ) -> PyResult<Vec<PyObject>> {
+        let Connector::Neo4j(connector) = &self.connector else {
+            return Err(PyValueError::new_err(format!(
+                "{:?} does not support Cypher queries",
+                self.infra_id
+            )));
+        };
+        let params = match parameters {
+            Some(parameters) => Some(Params::from(
+                parameters
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, to_bolt_value(value)?)))
+                    .collect::<PyResult<HashMap<_, _>>>()?,
+            )),
+            None => None,
+        };
+        let (fields, records) = py
+            .allow_threads(|| {
+                self.rt
+                    .block_on(connector.get_database().query(query, params))
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(PyRuntimeError::new_err)?;
+        records
+            .into_iter()
+            .map(|record| {
+                let dict = PyDict::new(py);
+                for (field, value) in fields.iter().zip(record.fields()) {
+                    dict.set_item(field, to_py_object(py, value.clone())?)?;
+                }
+                Ok(dict.into())
+            })
+            .collect()
+    }
+
+    /// Gets the values of `keys` from a Redis infra, `None` for the keys that do not exist.
+    fn mget(&self, py: Python<'_>, keys: Vec<String>) -> PyResult<Vec<Option<String>>> {
+        let Connector::Redis(connector) = &self.connector else {
+            return Err(PyValueError::new_err(format!(
+                "{:?} does not support key-value reads",
+                self.infra_id
+            )));
+        };
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        py.allow_threads(|| {
+            let mut conn = connector.try_get_connection()?;
+            redis::cmd("MGET").arg(keys).query(&mut conn)
+        })
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("InfraConnection({:?})", self.infra_id)
+    }
+}
+
+fn to_bolt_value(value: &PyAny) -> PyResult<Value> {
+    if value.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(value) = value.downcast::<PyBool>() {
+        Ok(Value::Boolean(value.is_true()))
+    } else if let Ok(value) = value.extract::<i64>() {
+        Ok(Value::Integer(value))
+    } else if let Ok(value) = value.extract::<f64>() {
+        Ok(Value::Float(value))
+    } else if let Ok(value) = value.extract::<String>() {
+        Ok(Value::String(value))
+    } else if let Ok(value) = value.downcast::<PyDict>() {
+        Ok(Value::Map(
+            value
+                .iter()
+                .map(|(key, value)| Ok((key.extract::<String>()?, to_bolt_value(value)?)))
+                .collect::<PyResult<_>>()?,
+        ))
+    } else if let Ok(values) = value.extract::<Vec<&PyAny>>() {
+        Ok(Value::List(
+            values
+                .into_iter()
+                .map(to_bolt_value)
+                .collect::<PyResult<_>>()?,
+        ))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Unsupported query parameter {value}"
+        )))
+    }
+}
+
+/// Converts a value returned by Neo4j, nodes and relationships to dicts of their properties
+fn to_py_object(py: Python<'_>, value: Value) -> PyResult<PyObject> {
+    let properties = |properties: &HashMap<String, Value>| -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (key, value) in properties {
+            dict.set_item(key, to_py_object(py, value.clone())?)?;
+        }
+        Ok(dict.into())
+    };
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Boolean(value) => Ok(value.into_py(py)),
+        Value::Integer(value) => Ok(value.into_py(py)),
+        Value::Float(value) => Ok(value.into_py(py)),
+        Value::String(value) => Ok(value.into_py(py)),
+        Value::Bytes(value) => Ok(PyBytes::new(py, &value).into()),
+        Value::List(values) => Ok(PyList::new(
+            py,
+            values
+                .into_iter()
+                .map(|value| to_py_object(py, value))
+                .collect::<PyResult<Vec<_>>>()?,
+        )
+        .into()),
+        Value::Map(map) => properties(&map),
+        Value::Node(node) => properties(node.properties()),
+        Value::Relationship(relationship) => properties(relationship.properties()),
+        value => Err(PyValueError::new_err(format!(
+            "Unsupported query result {value:?}"
+        ))),
+    }
+}
@@ -37,7 +37,7 @@ use transformation_plan::{TransformationOp, TransformationPlan};
 
 use crate::{
     feature::ResourceId, feature::ResourceOp, infra::pi::GraphAnalyticFunc, FeatureStore,
-    InfraIdentifier, Variant,
+    InfraIdentifier, Resource, Variant,
 };
 
 /// A TransformationData instance is registered in the TransformationContext, and its
@@ -105,7 +105,7 @@ pub async fn finalize_transformation(
 ) -> Result<ResourceId, Box<dyn Error>> {
     tc.as_ref()
         .borrow_mut()
-        .set_and_validate_infras(fs.infra_manager())?;
+        .set_and_validate_infras(&fs.infra_manager())?;
     let transformation = tc
         .as_ref()
         .borrow_mut()
@@ -114,9 +114,17 @@ pub async fn finalize_transformation(
         .to_owned();
     info!("finalized transformation: {:?}", transformation);
     info!("transformation body: {}", transformation.body);
-    fs.registry.register_resource(&transformation).await?;
-    fs.registry.register_resources(&entities).await?;
-    fs.registry.register_resources(&fields).await?;
-    fs.registry.register_resources(&topos).await?;
+    // register all resources in a single write so that concurrent finalizations cannot interleave
+    let mut resources = vec![Resource::from_resource_op(&transformation)?];
+    for resource in entities {
+        resources.push(Resource::from_resource_op(resource)?);
+    }
+    for resource in fields {
+        resources.push(Resource::from_resource_op(resource)?);
+    }
+    for resource in topos {
+        resources.push(Resource::from_resource_op(resource)?);
+    }
+    fs.registry.register_all(&resources).await?;
     Ok(transformation.resource_id())
 }
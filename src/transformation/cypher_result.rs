@@ -194,6 +194,7 @@ impl TransformationData for CypherResultGraph {
                         // TODO(tatiana): fill schema according to query parsing result
                         field_names: Vec::new(),
                         field_types: Vec::new(),
+                        primary_key: None,
                     },
                 }),
             ),
@@ -293,6 +294,7 @@ impl TransformationData for CypherResultDataFrame {
                         tlabel: None,
                         field_names: Vec::new(),
                         field_types: Vec::new(),
+                        primary_key: None,
                     },
                 ),
             ),
@@ -48,6 +48,9 @@ pub struct AggregateDataFrame {
     func: AggregateFunc,
     edge_entity: Option<Entity>, // can get tlabel, src_tlabel, dst_tlabel
     target_vertex_entity: Entity,
+    /// primary key of the neighbors of the target vertices, if known by the graph
+    #[serde(default)]
+    neighbor_primary_key: Option<String>,
     properties: Vec<String>,
 }
 
@@ -57,6 +60,7 @@ impl AggregateDataFrame {
         aggregator: AggregateFunc,
         edge_entity: Option<Entity>,
         target_vertex_entity: Entity,
+        neighbor_primary_key: Option<String>,
         properties: Vec<String>,
     ) -> Self {
         Self {
@@ -64,6 +68,7 @@ impl AggregateDataFrame {
             func: aggregator,
             edge_entity,
             target_vertex_entity,
+            neighbor_primary_key,
             properties,
         }
     }
@@ -103,6 +108,13 @@ impl TransformationData for AggregateDataFrame {
         };
 
         let target_node_primary_key = self.target_vertex_entity.primary_key().unwrap().to_owned();
+        let primary_key = |tlabel: &String| {
+            if tlabel == &target_node_tlabel {
+                Some(target_node_primary_key.clone())
+            } else {
+                self.neighbor_primary_key.clone()
+            }
+        };
 
         Box::new(AggregateOp::new(
             TransformationArgs::new_vertex_feature_args(
@@ -111,7 +123,10 @@ impl TransformationData for AggregateDataFrame {
                     properties: self.properties.clone(),
                 }),
                 GraphProjectionArgs {
-                    vertices: vec![(src_tlabel, None), (dst_tlabel, None)],
+                    vertices: vec![
+                        (src_tlabel.clone(), primary_key(&src_tlabel)),
+                        (dst_tlabel.clone(), primary_key(&dst_tlabel)),
+                    ],
                     edges: vec![edge_entity],
                     make_edges_undirected: false,
                 },
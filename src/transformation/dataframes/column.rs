@@ -45,6 +45,7 @@ impl Column {
             tags: HashMap::new(),
             owners: Vec::new(),
             sink_infra_id: sink_infra_id.cloned(),
+            deprecated: false,
         }
     }
 }
@@ -28,6 +28,11 @@ pub trait GraphBase: TransformationData {
     fn get_edge_entities(&self) -> &HashMap<String, ResourceId>;
     fn get_topology_type(&self) -> &Option<TopologyType>;
 
+    /// Primary key of the vertices of type `tlabel` in the graph, if known
+    fn get_vertex_primary_key(&self, _tlabel: &str) -> Option<&String> {
+        None
+    }
+
     /// Returns a vertex data frame containing all vertices in the graph
     fn vertices(&self) -> Rc<dyn GraphBase>;
 
@@ -231,6 +236,18 @@ impl<T: GraphBase> GraphComputationOps for T {
         aggregator: &str,
         common_args: Option<CommonTransformationArgs>,
     ) -> Result<Rc<AggregateDataFrame>, Box<dyn Error>> {
+        // the neighbors are at the other end of the edges than the target vertices
+        let neighbor_primary_key = match &edge_entity {
+            Some(Entity::Edge(edge)) => {
+                let neighbor_tlabel = if edge.src_tlabel == target_node_entity.tlabel() {
+                    &edge.dst_tlabel
+                } else {
+                    &edge.src_tlabel
+                };
+                self.get_vertex_primary_key(neighbor_tlabel).cloned()
+            }
+            _ => None,
+        };
         let new_data_context = self.get_context().new_data_context(common_args);
         let name = format!("aggregate_neighbors_{}", new_data_context.id);
         let res = Rc::new(AggregateDataFrame::new(
@@ -248,6 +265,7 @@ impl<T: GraphBase> GraphComputationOps for T {
             AggregateFunc::from_str(aggregator)?,
             edge_entity,
             target_node_entity,
+            neighbor_primary_key,
             properties,
         ));
         self.get_context().register_data(&res);
@@ -27,6 +27,8 @@ pub struct SingleGraph {
     edge_fvs: HashMap<String, (String, Vec<Field>)>,   // tlabel, (view name, fields)
     vertex_entities: HashMap<String, ResourceId>,      // tlabel, entity id
     edge_entities: HashMap<String, ResourceId>,        // tlabel, entity id
+    #[serde(default)]
+    vertex_primary_keys: HashMap<String, String>, // tlabel, primary key
     topology_type: Option<TopologyType>,
 }
 
@@ -46,6 +48,12 @@ impl SingleGraph {
             .iter()
             .map(|(entity, _)| (entity.tlabel().to_string(), entity.resource_id()))
             .collect();
+        let vertex_primary_keys = vertex_entity_fields
+            .iter()
+            .filter_map(|(entity, _)| {
+                Some((entity.tlabel().to_string(), entity.primary_key()?.clone()))
+            })
+            .collect();
         let res = Rc::new(Self {
             context: DataTransformationContext {
                 id: context.as_ref().borrow_mut().new_data_id(),
@@ -69,6 +77,7 @@ impl SingleGraph {
                 .collect(),
             vertex_entities,
             edge_entities,
+            vertex_primary_keys,
             topology_type: None,
         });
         context.as_ref().borrow_mut().add_data(&res);
@@ -92,6 +101,7 @@ impl SingleGraph {
             edge_fvs: HashMap::new(),
             vertex_entities: HashMap::new(),
             edge_entities: HashMap::new(),
+            vertex_primary_keys: HashMap::new(),
             topology_type: None,
         };
         for view in &meta.table_feature_views {
@@ -108,6 +118,8 @@ impl SingleGraph {
                         .insert(entity.tlabel.clone(), (view.name.clone(), fields));
                     res.vertex_entities
                         .insert(entity.tlabel.clone(), view.entity_id.clone());
+                    res.vertex_primary_keys
+                        .insert(entity.tlabel.clone(), entity.primary_key.clone());
                 }
                 Entity::Edge(entity) => {
                     // now treat entity name as edge type
@@ -138,6 +150,7 @@ impl Graph {
         let mut edge_fvs = HashMap::new();
         let mut vertex_entities = HashMap::new();
         let mut edge_entities = HashMap::new();
+        let mut vertex_primary_keys = HashMap::new();
         for id in self.entity_ids.values() {
             let entity = registry.get_entity(id).await?;
             let entity_name = Entity::id_to_name(id);
@@ -150,6 +163,7 @@ impl Graph {
                 Entity::Vertex(entity) => {
                     vertex_fvs.insert(entity.tlabel.clone(), (view_name, fields));
                     vertex_entities.insert(entity.tlabel.clone(), id.clone());
+                    vertex_primary_keys.insert(entity.tlabel.clone(), entity.primary_key.clone());
                 }
                 Entity::Edge(entity) => {
                     edge_fvs.insert(entity.tlabel.clone(), (view_name, fields));
@@ -170,6 +184,7 @@ impl Graph {
             edge_fvs,
             vertex_entities,
             edge_entities,
+            vertex_primary_keys,
             topology_type: None,
         });
         context.as_ref().borrow_mut().add_data(&res);
@@ -270,6 +285,10 @@ impl GraphBase for SingleGraph {
         &self.topology_type
     }
 
+    fn get_vertex_primary_key(&self, tlabel: &str) -> Option<&String> {
+        self.vertex_primary_keys.get(tlabel)
+    }
+
     fn vertices(&self) -> Rc<dyn GraphBase> {
         let res = Rc::new(VertexSelectGraph {
             context: self.get_context().new_data_context(None),
@@ -200,7 +200,7 @@ async fn test_result_handler() -> std::result::Result<(), Box<dyn Error>> {
     let user = "neo4j";
     let password = "ofnil";
 
-    let neo4j_provider = Neo4jConnector::new(uri, user, password, None, None)
+    let neo4j_provider = Neo4jConnector::new(uri, user, password, &Default::default(), None)
         .await?
         .get_database();
     let bolt_conn = neo4j_provider.get_bolt_connection().await?;
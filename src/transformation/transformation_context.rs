@@ -3,6 +3,7 @@ use super::{
     TransformationData, TransformationPlan,
 };
 use crate::{
+    encode_transformation_body,
     feature::{ResourceOp, Transformation},
     InfraIdentifier, InfraManager, SeResult, Variant,
 };
@@ -144,7 +145,7 @@ impl TransformationContext {
         name: Option<String>,
         variant: Variant,
     ) -> Result<Option<&Transformation>, Box<dyn Error>> {
-        let body = serde_json::to_string(&self)?;
+        let body = encode_transformation_body(&self)?;
         if let Some(transformation) = &mut self.transformation {
             if let Some(name_str) = name {
                 transformation.name = name_str;
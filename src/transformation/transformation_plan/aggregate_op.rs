@@ -86,6 +86,7 @@ impl TransformationOp for AggregateOp {
                         field_names: args.output_names.clone(),
                         field_types: vec![FeatureValueType::Float; args.output_names.len()],
                         tlabel: Some(args.target_vertex_tlabel.clone()),
+                        primary_key: Some(args.target_vertex_primary_key.clone()),
                     }),
                 ),
                 self.get_common_args().source_storage_types().clone(),
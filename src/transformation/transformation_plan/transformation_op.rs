@@ -1,6 +1,6 @@
 use crate::{
     infra::pi::{
-        storage::{Row, Source, Storage},
+        storage::{RecordBatch, Row, Source, Storage},
         StorageConnector, TransformationConnector,
     },
     transformation::{
@@ -21,9 +21,22 @@ pub type TransformationIOT = Vec<TransformationOutputHandler>;
 // TransformationOutputHandler should be cheap to clone and invariant on clone
 #[derive(Clone, Debug)]
 pub enum TransformationOutputHandler {
-    CypherOutputHandler { data_id: DataIdT, path: String },
-    InfraHandler { infra_id: InfraIdentifier },
+    CypherOutputHandler {
+        data_id: DataIdT,
+        path: String,
+    },
+    InfraHandler {
+        infra_id: InfraIdentifier,
+    },
     TabularSource(Arc<dyn Source<Row>>),
+    /// Source of columnar batches of `Storage::Arrow`
+    BatchSource(Arc<dyn Source<RecordBatch>>),
+    /// Data in an infra other than the execution infra, which the executor reads by
+    /// [Sourceable::produce_rows](crate::infra::pi::Sourceable::produce_rows)
+    InfraSource {
+        infra_id: InfraIdentifier,
+        connector: Arc<dyn StorageConnector>,
+    },
     EmptyOutput,
     // recursion should be only one level
     PropagatedHandlers(TransformationIOT),
@@ -198,19 +211,23 @@ impl DAGOp {
             .map(|input| {
                 match &input {
                     TransformationOutputHandler::InfraHandler { infra_id } => {
-                        // when the input is a infra handler, get source if the input infra is not the same as the execution infra.
+                        // when the input is a infra handler, pass the input infra if it is not the same as the execution infra.
                         if self.get_execution_infra_id().ne(infra_id) {
-                            let source = context
+                            let connector = context
                                 .infra_manager
-                                .get_storage_infra(infra_id)
-                                .unwrap()
-                                .produce_rows();
-                            TransformationOutputHandler::TabularSource(Arc::from(source))
+                                .get_storage_infra_cloned(infra_id)
+                                .unwrap();
+                            TransformationOutputHandler::InfraSource {
+                                infra_id: infra_id.clone(),
+                                connector: Arc::from(connector),
+                            }
                         } else {
                             input
                         }
                     }
                     TransformationOutputHandler::TabularSource(_)
+                    | TransformationOutputHandler::BatchSource(_)
+                    | TransformationOutputHandler::InfraSource { .. }
                     | TransformationOutputHandler::EmptyOutput => input,
                     _ => panic!("unexpected input {input:?}"),
                 }
@@ -224,25 +241,31 @@ impl DAGOp {
     ) -> SeResult<TransformationOutputHandler> {
         // TODO(tatiana): support other storage types
         if self.is_transport_to_storage() {
-            assert!(Storage::OfnilRow.eq(self.get_sink_storage_type()));
             let sink_infra = self.sink_infra.as_ref().unwrap();
-            let source = match data {
-                TransformationOutputHandler::TabularSource(source) => source,
-                TransformationOutputHandler::EmptyOutput => return Ok(data),
-                _ => panic!(
-                    "Expect Source of {:?}, but got {data:?}",
-                    self.get_sink_storage_type()
-                ),
-            };
             info!(
-                "{}-{} output from {:?} to sink {:?}",
+                "{}-{} output from {:?} to sink {:?} as {:?}",
                 self.data_id,
                 get_type_of(&self.inner_op),
                 self.get_execution_infra_id(),
-                self.sink_infra_id
+                self.sink_infra_id,
+                self.get_sink_storage_type()
             );
-            let sink = sink_infra.insert_rows(source.get_schema().clone());
-            transport_source_to_sink(source.as_ref(), sink.as_ref()).await?;
+            match (self.get_sink_storage_type(), data) {
+                (_, TransformationOutputHandler::EmptyOutput) => {
+                    return Ok(TransformationOutputHandler::EmptyOutput)
+                }
+                (Storage::OfnilRow, TransformationOutputHandler::TabularSource(source)) => {
+                    let sink = sink_infra.insert_rows(source.get_schema().clone());
+                    transport_source_to_sink(source.as_ref(), sink.as_ref()).await?;
+                }
+                (Storage::Arrow, TransformationOutputHandler::BatchSource(source)) => {
+                    let sink = sink_infra.insert_batches(source.get_schema().clone());
+                    transport_source_to_sink(source.as_ref(), sink.as_ref()).await?;
+                }
+                (storage, data) => {
+                    return Err(format!("Expect Source of {storage:?}, but got {data:?}").into())
+                }
+            }
             Ok(TransformationOutputHandler::InfraHandler {
                 infra_id: self.sink_infra_id.clone().unwrap(),
             })
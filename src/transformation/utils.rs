@@ -14,7 +14,7 @@ pub async fn transport_source_to_sink<T>(
             break;
         }
     }
-    Ok(())
+    writer.finish().await
 }
 
 pub fn get_type_of<T>(_: &T) -> &str {